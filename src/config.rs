@@ -1,4 +1,8 @@
+use std::collections::HashMap;
 use std::env;
+use std::net::IpAddr;
+
+use crate::models::money::Money;
 
 /// Central app configuration — loaded once at startup from environment variables.
 /// Every field has a safe default so the server never panics if optional
@@ -7,11 +11,22 @@ use std::env;
 pub struct AppConfig {
     // ── Core ─────────────────────────────────────────────────────────────────
     pub jwt_secret: String,
+    /// Phones that always get the admin role, to bootstrap the first admins.
+    pub admin_phones: Vec<String>,
+    /// Shared rate limit counters across instances; in-memory when unset.
+    pub redis_url: Option<String>,
+    /// "memory" (default) or "redis" to fan WebSocket frames out across replicas.
+    pub broadcast_backend: String,
+    /// Reverse proxies whose `X-Forwarded-For` is believed; empty means the
+    /// TCP peer is always the client.
+    pub trusted_proxies: Vec<IpAddr>,
 
     // ── SMS (Africa's Talking) ────────────────────────────────────────────────
     pub sms_api_key: String,
     pub sms_username: String,
     pub sms_from: String,
+    /// Dev/test only: log OTP codes instead of texting them (`SMS_LOG_ONLY`).
+    pub sms_log_only: bool,
 
     // ── M-Pesa ───────────────────────────────────────────────────────────────
     pub mpesa_environment: String, // "sandbox" | "production"
@@ -29,6 +44,104 @@ pub struct AppConfig {
     // Callback URLs (B2C)
     pub mpesa_b2c_result_url: String,
     pub mpesa_b2c_queue_timeout_url: String,
+
+    // Callback URLs (transaction status query, used after a B2C timeout)
+    pub mpesa_status_result_url: String,
+    pub mpesa_status_timeout_url: String,
+
+    // Callback authentication: a secret last path segment on every callback
+    // URL and/or the addresses Daraja calls from. Empty disables the check.
+    pub mpesa_callback_token: String,
+    pub mpesa_callback_ips: Vec<String>,
+
+    // ── Match data poller ────────────────────────────────────────────────────
+    /// Shared secret the poller signs its webhooks with (HMAC-SHA256).
+    pub poller_webhook_secret: String,
+
+    // ── Commission ───────────────────────────────────────────────────────────
+    pub commission: CommissionConfig,
+}
+
+/// House cut on a settled bet: `percent_bps` basis points of the total pot
+/// plus a flat fee.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommissionRule {
+    pub percent_bps: i64,
+    pub flat: Money,
+}
+
+impl CommissionRule {
+    /// Fee on a pot. Capped at the winner's winnings so a winning bet never
+    /// pays out less than the winner staked.
+    pub fn fee_for(&self, total_pot: Money, winnings: Money) -> Money {
+        let fee = total_pot.mul_ratio(self.percent_bps, 10_000) + self.flat;
+        fee.clamp(Money::ZERO, winnings.max(Money::ZERO))
+    }
+
+    /// Parses `"5%"`, `"10"` (flat KES) or `"2.5%+10"`.
+    fn parse(spec: &str) -> Option<Self> {
+        let mut rule = CommissionRule::default();
+        for term in spec.split('+').map(str::trim).filter(|t| !t.is_empty()) {
+            match term.strip_suffix('%') {
+                // A percentage with two decimals is a whole number of basis points
+                Some(percent) => rule.percent_bps += Money::parse_kes(percent)?.cents(),
+                None => rule.flat += Money::parse_kes(term)?,
+            }
+        }
+        (!rule.percent_bps.is_negative() && !rule.flat.is_negative()).then_some(rule)
+    }
+}
+
+/// Commission rules, most specific first: league, then sport_type, then the
+/// platform default.
+#[derive(Debug, Clone, Default)]
+pub struct CommissionConfig {
+    pub default: CommissionRule,
+    pub by_league: HashMap<String, CommissionRule>,
+    pub by_sport: HashMap<String, CommissionRule>,
+}
+
+impl CommissionConfig {
+    pub fn rule_for(&self, league: &str, sport_type: &str) -> CommissionRule {
+        self.by_league
+            .get(&league.trim().to_lowercase())
+            .or_else(|| self.by_sport.get(&sport_type.trim().to_lowercase()))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// `COMMISSION_DEFAULT` (e.g. `"5%"`, `"5%+10"`) and `COMMISSION_RULES`,
+    /// a `;`-separated list such as
+    /// `league:Premier League=4%;sport:basketball=3%+5`.
+    fn from_env() -> Self {
+        let mut config = CommissionConfig::default();
+
+        if let Ok(spec) = env::var("COMMISSION_DEFAULT") {
+            match CommissionRule::parse(&spec) {
+                Some(rule) => config.default = rule,
+                None => tracing::error!("❌ Invalid COMMISSION_DEFAULT '{}' — using 0%", spec),
+            }
+        }
+
+        let rules = env::var("COMMISSION_RULES").unwrap_or_default();
+        for entry in rules.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(scope, spec)| {
+                let (kind, name) = scope.split_once(':')?;
+                Some((kind.trim(), name.trim().to_lowercase(), CommissionRule::parse(spec)?))
+            });
+            match parsed {
+                Some(("league", name, rule)) => {
+                    config.by_league.insert(name, rule);
+                }
+                Some(("sport", name, rule)) => {
+                    config.by_sport.insert(name, rule);
+                }
+                _ => tracing::error!("❌ Ignoring invalid COMMISSION_RULES entry '{}'", entry),
+            }
+        }
+
+        config
+    }
 }
 
 impl AppConfig {
@@ -40,6 +153,30 @@ impl AppConfig {
             tracing::error!("❌ JWT_SECRET not set — tokens will be invalid");
             "insecure-default-change-me-in-production".to_string()
         });
+        let redis_url = env::var("REDIS_URL").ok().filter(|url| !url.trim().is_empty());
+        let broadcast_backend = env::var("BROADCAST_BACKEND")
+            .map(|backend| backend.trim().to_lowercase())
+            .unwrap_or_else(|_| "memory".to_string());
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .filter_map(|ip| match ip.parse::<IpAddr>() {
+                Ok(ip) => Some(ip),
+                Err(_) => {
+                    tracing::error!("❌ Ignoring invalid TRUSTED_PROXIES entry '{}'", ip);
+                    None
+                }
+            })
+            .collect();
+        let admin_phones = env::var("ADMIN_PHONES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|phone| !phone.is_empty())
+            .map(str::to_string)
+            .collect();
 
         // ── SMS ───────────────────────────────────────────────────────────────
         let sms_api_key = env::var("SMS_API_KEY").unwrap_or_else(|_| {
@@ -48,6 +185,9 @@ impl AppConfig {
         });
         let sms_username = env::var("SMS_USERNAME").unwrap_or_else(|_| "sandbox".to_string());
         let sms_from = env::var("SMS_FROM").unwrap_or_else(|_| "FanClash".to_string());
+        let sms_log_only = env::var("SMS_LOG_ONLY")
+            .map(|flag| matches!(flag.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
 
         // ── M-Pesa ────────────────────────────────────────────────────────────
         let mpesa_environment =
@@ -82,14 +222,60 @@ impl AppConfig {
         let base_url = env::var("API_BASE_URL")
             .unwrap_or_else(|_| "https://fanclash-api.onrender.com".to_string());
 
-        let mpesa_confirmation_url = env::var("MPESA_CONFIRMATION_URL")
-            .unwrap_or_else(|_| format!("{}/api/mpesa/confirmation", base_url));
-        let mpesa_validation_url = env::var("MPESA_VALIDATION_URL")
-            .unwrap_or_else(|_| format!("{}/api/mpesa/validation", base_url));
-        let mpesa_b2c_result_url = env::var("MPESA_B2C_RESULT_URL")
-            .unwrap_or_else(|_| format!("{}/api/mpesa/b2c/result", base_url));
-        let mpesa_b2c_queue_timeout_url = env::var("MPESA_B2C_QUEUE_TIMEOUT_URL")
-            .unwrap_or_else(|_| format!("{}/api/mpesa/b2c/timeout", base_url));
+        let mpesa_callback_token = env::var("MPESA_CALLBACK_TOKEN").unwrap_or_default();
+        let mpesa_callback_ips: Vec<String> = env::var("MPESA_CALLBACK_IPS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .map(str::to_string)
+            .collect();
+        if mpesa_callback_token.is_empty() && mpesa_callback_ips.is_empty() {
+            tracing::warn!(
+                "⚠️  Neither MPESA_CALLBACK_TOKEN nor MPESA_CALLBACK_IPS set — M-Pesa callbacks are unauthenticated"
+            );
+        }
+        let with_token = |url: String| {
+            if mpesa_callback_token.is_empty() {
+                url
+            } else {
+                format!("{}/{}", url.trim_end_matches('/'), mpesa_callback_token)
+            }
+        };
+
+        let mpesa_confirmation_url = with_token(
+            env::var("MPESA_CONFIRMATION_URL")
+                .unwrap_or_else(|_| format!("{}/api/lipaclash/confirmation", base_url)),
+        );
+        let mpesa_validation_url = with_token(
+            env::var("MPESA_VALIDATION_URL")
+                .unwrap_or_else(|_| format!("{}/api/lipaclash/validation", base_url)),
+        );
+        let mpesa_b2c_result_url = with_token(
+            env::var("MPESA_B2C_RESULT_URL")
+                .unwrap_or_else(|_| format!("{}/api/lipaclash/b2c/result", base_url)),
+        );
+        let mpesa_b2c_queue_timeout_url = with_token(
+            env::var("MPESA_B2C_QUEUE_TIMEOUT_URL")
+                .unwrap_or_else(|_| format!("{}/api/lipaclash/b2c/timeout", base_url)),
+        );
+        let mpesa_status_result_url = with_token(
+            env::var("MPESA_STATUS_RESULT_URL")
+                .unwrap_or_else(|_| format!("{}/api/lipaclash/b2c/status-result", base_url)),
+        );
+        let mpesa_status_timeout_url = with_token(
+            env::var("MPESA_STATUS_TIMEOUT_URL")
+                .unwrap_or_else(|_| format!("{}/api/lipaclash/b2c/status-timeout", base_url)),
+        );
+
+        // ── Match data poller ─────────────────────────────────────────────────
+        let poller_webhook_secret = env::var("POLLER_WEBHOOK_SECRET").unwrap_or_else(|_| {
+            tracing::warn!("⚠️  POLLER_WEBHOOK_SECRET not set — poller webhooks will be rejected");
+            String::new()
+        });
+
+        // ── Commission ────────────────────────────────────────────────────────
+        let commission = CommissionConfig::from_env();
 
         if !mpesa_consumer_key.is_empty() {
             tracing::info!(
@@ -101,9 +287,14 @@ impl AppConfig {
 
         AppConfig {
             jwt_secret,
+            admin_phones,
+            redis_url,
+            broadcast_backend,
+            trusted_proxies,
             sms_api_key,
             sms_username,
             sms_from,
+            sms_log_only,
             mpesa_environment,
             mpesa_consumer_key,
             mpesa_consumer_secret,
@@ -115,6 +306,12 @@ impl AppConfig {
             mpesa_validation_url,
             mpesa_b2c_result_url,
             mpesa_b2c_queue_timeout_url,
+            mpesa_status_result_url,
+            mpesa_status_timeout_url,
+            mpesa_callback_token,
+            mpesa_callback_ips,
+            poller_webhook_secret,
+            commission,
         }
     }
 
@@ -145,8 +342,8 @@ impl AppConfig {
                     .to_string(),
                 // Production STK push
                 "https://api.safaricom.co.ke/mpesa/stkpush/v1/processrequest".to_string(),
-                // Production B2C
-                "https://api.safaricom.co.ke/mpesa/b2c/v1/paymentrequest".to_string(),
+                // Production B2C (v3 takes our OriginatorConversationID)
+                "https://api.safaricom.co.ke/mpesa/b2c/v3/paymentrequest".to_string(),
             )
         } else {
             (
@@ -156,8 +353,17 @@ impl AppConfig {
                 // Sandbox STK push
                 "https://sandbox.safaricom.co.ke/mpesa/stkpush/v1/processrequest".to_string(),
                 // Sandbox B2C
-                "https://sandbox.safaricom.co.ke/mpesa/b2c/v1/paymentrequest".to_string(),
+                "https://sandbox.safaricom.co.ke/mpesa/b2c/v3/paymentrequest".to_string(),
             )
         }
     }
+
+    /// The transaction status query URL for the current environment.
+    pub fn get_transaction_status_url(&self) -> String {
+        if self.is_production() {
+            "https://api.safaricom.co.ke/mpesa/transactionstatus/v1/query".to_string()
+        } else {
+            "https://sandbox.safaricom.co.ke/mpesa/transactionstatus/v1/query".to_string()
+        }
+    }
 }
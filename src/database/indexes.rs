@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use mongodb::{bson::doc, options::IndexOptions, Database, IndexModel};
+
+/// Create the indexes the money and auth subsystems rely on for correctness
+/// (uniqueness / idempotency / expiry). Safe to run on every startup.
+pub async fn ensure_indexes(db: &Database) {
+    let unique = |keys| {
+        IndexModel::builder()
+            .keys(keys)
+            .options(IndexOptions::builder().unique(true).build())
+            .build()
+    };
+
+    let indexes = [
+        ("ledger_accounts", unique(doc! { "account_id": 1 })),
+        ("ledger_entries", unique(doc! { "entry_id": 1 })),
+        (
+            "ledger_entries",
+            IndexModel::builder()
+                .keys(doc! { "postings.owner_id": 1, "created_at": -1 })
+                .build(),
+        ),
+        ("escrow_holds", unique(doc! { "hold_id": 1 })),
+        (
+            "escrow_holds",
+            IndexModel::builder()
+                .keys(doc! { "bet_id": 1, "status": 1 })
+                .build(),
+        ),
+        (
+            "withdrawals",
+            IndexModel::builder()
+                .keys(doc! { "conversation_id": 1 })
+                .build(),
+        ),
+        (
+            "withdrawals",
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "created_at": -1 })
+                .build(),
+        ),
+        ("auth_sessions", unique(doc! { "session_id": 1 })),
+        (
+            "auth_sessions",
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "device_id": 1 })
+                .build(),
+        ),
+        ("kyc_records", unique(doc! { "user_id": 1 })),
+        ("kyc_records", unique(doc! { "id_number": 1 })),
+        (
+            "kyc_records",
+            IndexModel::builder()
+                .keys(doc! { "status": 1, "submitted_at": 1 })
+                .build(),
+        ),
+        ("phone_otps", unique(doc! { "phone": 1 })),
+        (
+            "phone_otps",
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ),
+    ];
+
+    for (collection, index) in indexes {
+        match db
+            .collection::<mongodb::bson::Document>(collection)
+            .create_index(index)
+            .await
+        {
+            Ok(_) => tracing::info!("✅ Index ensured on {}", collection),
+            Err(e) => tracing::error!("❌ Failed to create index on {}: {}", collection, e),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    Collection, Database,
+};
+
+/// Money fields that used to be stored as KES floats, by collection.
+const MONEY_FIELDS: &[(&str, &[&str])] = &[
+    ("pledges", &["amount"]),
+    ("bets", &["starter_amount", "finisher_amount", "total_pot"]),
+    ("transactions", &["amount"]),
+    ("user_profiles", &["balance"]),
+    ("users", &["balance"]),
+    ("ledger_accounts", &["balance"]),
+    ("escrow_holds", &["amount"]),
+    ("withdrawals", &["amount"]),
+];
+
+/// Convert legacy KES amounts (doubles / int32) into Int64 cents, rounding
+/// half away from zero. Values that are already Int64 are left alone, so
+/// this is safe to run on every startup.
+pub async fn migrate_money_to_cents(db: &Database) {
+    for (collection, fields) in MONEY_FIELDS {
+        for field in fields.iter() {
+            match migrate_field(db, collection, field).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!(
+                    "💱 Migrated {} {}.{} values to cents",
+                    count,
+                    collection,
+                    field
+                ),
+                Err(e) => tracing::error!(
+                    "❌ Money migration failed for {}.{}: {}",
+                    collection,
+                    field,
+                    e
+                ),
+            }
+        }
+    }
+
+    match migrate_ledger_postings(db).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("💱 Migrated postings on {} ledger entries to cents", count),
+        Err(e) => tracing::error!("❌ Money migration failed for ledger_entries: {}", e),
+    }
+}
+
+fn legacy_type() -> Document {
+    doc! { "$type": ["double", "int"] }
+}
+
+fn to_cents(value: &Bson) -> Option<Bson> {
+    let kes = match value {
+        Bson::Double(kes) => *kes,
+        Bson::Int32(kes) => f64::from(*kes),
+        _ => return None,
+    };
+    kes.is_finite()
+        .then(|| Bson::Int64((kes * 100.0).round() as i64))
+}
+
+async fn migrate_field(
+    db: &Database,
+    collection: &str,
+    field: &str,
+) -> mongodb::error::Result<usize> {
+    let collection: Collection<Document> = db.collection(collection);
+    let legacy: Vec<Document> = collection
+        .find(doc! { field: legacy_type() })
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut migrated = 0;
+    for document in legacy {
+        let (Some(id), Some(cents)) = (
+            document.get("_id").cloned(),
+            document.get(field).and_then(to_cents),
+        ) else {
+            continue;
+        };
+
+        // Conditional on the type so a concurrent write in cents is kept
+        let result = collection
+            .update_one(
+                doc! { "_id": id, field: legacy_type() },
+                doc! { "$set": { field: cents } },
+            )
+            .await?;
+        migrated += result.modified_count as usize;
+    }
+    Ok(migrated)
+}
+
+async fn migrate_ledger_postings(db: &Database) -> mongodb::error::Result<usize> {
+    let entries: Collection<Document> = db.collection("ledger_entries");
+    let legacy: Vec<Document> = entries
+        .find(doc! { "$or": [
+            { "postings.amount": legacy_type() },
+            { "postings.balance_after": legacy_type() },
+        ]})
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut migrated = 0;
+    for entry in legacy {
+        let (Some(id), Ok(postings)) = (entry.get("_id").cloned(), entry.get_array("postings"))
+        else {
+            continue;
+        };
+
+        let postings: Vec<Bson> = postings
+            .iter()
+            .map(|posting| match posting {
+                Bson::Document(posting) => {
+                    let mut posting = posting.clone();
+                    for field in ["amount", "balance_after"] {
+                        if let Some(cents) = posting.get(field).and_then(to_cents) {
+                            posting.insert(field, cents);
+                        }
+                    }
+                    Bson::Document(posting)
+                }
+                other => other.clone(),
+            })
+            .collect();
+
+        let result = entries
+            .update_one(doc! { "_id": id }, doc! { "$set": { "postings": postings } })
+            .await?;
+        migrated += result.modified_count as usize;
+    }
+    Ok(migrated)
+}
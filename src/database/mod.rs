@@ -1 +1,3 @@
 pub(crate) mod connection;
+pub(crate) mod indexes;
+pub(crate) mod migrations;
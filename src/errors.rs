@@ -67,12 +67,27 @@ pub enum AppError {
     #[error("M-Pesa error: {0}")]
     MpesaError(String),
 
+    #[error("Insufficient funds")]
+    InsufficientFunds,
+
+    #[error("Pledge already matched")]
+    PledgeAlreadyMatched,
+
+    #[error("{0}")]
+    ResponsibleGambling(String),
+
+    #[error("{0}")]
+    KycRequired(String),
+
     #[error("Authentication error")]
     AuthError,
 
     #[error("Unauthorized access")]
     Unauthorized,
 
+    #[error("Invalid webhook signature: {0}")]
+    InvalidSignature(String),
+
     #[error("Validation error: {0}")]
     ValidationError(String),
 
@@ -154,11 +169,30 @@ impl IntoResponse for AppError {
             }
             AppError::DuplicateKey => (StatusCode::CONFLICT, "Duplicate entry".to_string()),
             AppError::MpesaError(_) => (StatusCode::BAD_GATEWAY, "M-Pesa error".to_string()),
+            AppError::InsufficientFunds => (
+                StatusCode::PAYMENT_REQUIRED,
+                "Insufficient funds".to_string(),
+            ),
+            AppError::PledgeAlreadyMatched => (
+                StatusCode::CONFLICT,
+                "Pledge has already been matched".to_string(),
+            ),
+            AppError::ResponsibleGambling(_) => (
+                StatusCode::FORBIDDEN,
+                "Blocked by your gambling limits".to_string(),
+            ),
+            AppError::KycRequired(_) => (
+                StatusCode::FORBIDDEN,
+                "Identity verification required".to_string(),
+            ),
             AppError::AuthError => (
                 StatusCode::UNAUTHORIZED,
                 "Authentication failed".to_string(),
             ),
             AppError::Unauthorized => (StatusCode::FORBIDDEN, "Unauthorized access".to_string()),
+            AppError::InvalidSignature(_) => {
+                (StatusCode::UNAUTHORIZED, "Invalid signature".to_string())
+            }
             AppError::ValidationError(_) => {
                 (StatusCode::BAD_REQUEST, "Validation failed".to_string())
             }
@@ -14,6 +14,7 @@ use regex::escape;
 
 use crate::{
     errors::{AppError, Result},
+    middleware::auth::AuthUser,
     models::archive::{
         ArchiveActivity, ArchiveActivityRequest, ArchiveActivityResponse, ArchiveQueryParams,
         ActivityType, UserArchiveStats, GetAllArchiveQuery, PaginatedArchiveResponse,
@@ -269,6 +270,7 @@ pub async fn search_archive_activities(
 pub async fn delete_archive_activity(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    auth: AuthUser,
 ) -> Result<StatusCode> {
     println!("🗑️ Deleting archive activity: {}", id);
 
@@ -277,6 +279,12 @@ pub async fn delete_archive_activity(
     let object_id = ObjectId::parse_str(&id)
         .map_err(|_| AppError::invalid_data("Invalid activity ID"))?;
 
+    let activity = collection
+        .find_one(doc! { "_id": object_id })
+        .await?
+        .ok_or(AppError::DocumentNotFound)?;
+    auth.ensure_owner_or_moderator(&activity.user_id)?;
+
     let result = collection.delete_one(doc! { "_id": object_id }).await?;
 
     if result.deleted_count == 0 {
@@ -631,3 +639,39 @@ pub async fn get_archives_by_username(
         },
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use crate::test_support::{assert_owner_or_moderator_only, OWNER};
+
+    async fn seed_activity(state: AppState) -> String {
+        let activity = ArchiveActivity {
+            id: None,
+            user_id: OWNER.to_string(),
+            username: OWNER.to_string(),
+            fixture_id: "fixture-1".to_string(),
+            home_team: "Home".to_string(),
+            away_team: "Away".to_string(),
+            activity_type: ActivityType::Comment,
+            selection: None,
+            is_liked: None,
+            comment: Some("Come on!".to_string()),
+            timestamp: Utc::now(),
+            created_at: Utc::now(),
+        };
+        let collection: Collection<ArchiveActivity> = state.db.collection("user_archive_activities");
+        let inserted = collection.insert_one(&activity).await.unwrap();
+        inserted.inserted_id.as_object_id().unwrap().to_hex()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn delete_archive_activity_is_owner_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_activity, |state, id, auth| async move {
+            delete_archive_activity(State(state), Path(id), auth).await.into_response()
+        })
+        .await;
+    }
+}
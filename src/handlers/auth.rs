@@ -1,21 +1,26 @@
 
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use chrono::Utc;
 use futures_util::TryStreamExt;
-use jsonwebtoken::{encode, EncodingKey, Header};
 use mongodb::{
     bson::{doc, oid::ObjectId, DateTime},
     Collection,
 };
 use serde_json::json;
 
+use crate::errors::{AppError, Result};
+use crate::middleware::auth::AuthUser;
+use crate::services::{otp, session};
 use crate::state::AppState;
-use crate::models::user::{User, CreateUserRequest, UserResponse, Claims, AuthResponse};
+use crate::models::otp::{RequestOtpRequest, VerifyOtpRequest};
+use crate::models::session::{RefreshRequest, SessionTokens};
+use crate::models::user::{User, CreateUserRequest, SetRolesRequest, UserResponse, AuthResponse};
+use crate::models::money::Money;
 
 // ========== REGISTER NEW USER (Phone + Username) ==========
 pub async fn register(
@@ -62,7 +67,9 @@ pub async fn register(
         id: None,
         username: payload.username.clone(),
         phone: payload.phone.clone(),
-        balance: 0.0,
+        balance: Money::ZERO,
+        phone_verified: false,
+        roles: Vec::new(),
         created_at: now,
         updated_at: now,
     };
@@ -78,17 +85,25 @@ pub async fn register(
                 id: inserted_id.to_hex(),
                 username: payload.username,
                 phone: payload.phone,
-                balance: 0.0,
+                balance: Money::ZERO,
+                phone_verified: false,
             };
 
-            let token = generate_token(&user_response.id, &user_response.username, &user_response.phone);
+            // No token until the phone is verified via /otp/verify
+            let otp_sent = match otp::send_code(&state, &user_response.phone).await {
+                Ok(()) => true,
+                Err(e) => {
+                    println!("❌ Failed to send OTP to {}: {}", user_response.phone, e);
+                    false
+                }
+            };
 
             (
                 StatusCode::CREATED,
                 Json(json!({
                     "success": true,
                     "user": user_response,
-                    "token": token
+                    "otp_sent": otp_sent
                 })),
             ).into_response()
         }
@@ -121,16 +136,15 @@ pub async fn get_user_by_phone(
                 username: user.username,
                 phone: user.phone,
                 balance: user.balance,
+                phone_verified: user.phone_verified,
             };
-            
-            let token = generate_token(&user_response.id, &user_response.username, &user_response.phone);
-            
+
+            // Logging in is /otp/request + /otp/verify; this only looks the user up
             (
                 StatusCode::OK,
                 Json(json!({
                     "success": true,
-                    "user": user_response,
-                    "token": token
+                    "user": user_response
                 })),
             ).into_response()
         }
@@ -172,6 +186,7 @@ pub async fn get_user_by_username(
                 username: user.username,
                 phone: user.phone,
                 balance: user.balance,
+                phone_verified: user.phone_verified,
             };
             
             (
@@ -236,6 +251,7 @@ pub async fn get_all_users(
                         username: user.username,
                         phone: user.phone,
                         balance: user.balance,
+                        phone_verified: user.phone_verified,
                     })
                 })
                 .collect();
@@ -263,17 +279,140 @@ pub async fn get_all_users(
     }
 }
 
-// ========== HELPER: Generate JWT Token ==========
-fn generate_token(user_id: &str, username: &str, phone: &str) -> String {
-    let claims = Claims {
-        sub: user_id.to_string(),
-        username: username.to_string(),
-        phone: phone.to_string(),
-        exp: (Utc::now().timestamp() + 86400) as usize, // 24 hours
+// ========== SET USER ROLES (admin) ==========
+// Applies from the user's next login or token refresh
+pub async fn set_user_roles(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<SetRolesRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let object_id = ObjectId::parse_str(&id)?;
+    println!("🛡️ Setting roles of {} to {:?}", id, payload.roles);
+
+    let roles = mongodb::bson::to_bson(&payload.roles)
+        .map_err(|e| AppError::internal_server_error(e.to_string()))?;
+    let collection: Collection<User> = state.db.collection("users");
+    let result = collection
+        .update_one(
+            doc! { "_id": object_id },
+            doc! { "$set": {
+                "roles": roles,
+                "updated_at": DateTime::from_chrono(Utc::now())
+            }},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::UserNotFound);
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "roles": payload.roles
+    })))
+}
+
+// ========== REQUEST OTP (login / re-verification) ==========
+pub async fn request_otp(
+    State(state): State<AppState>,
+    Json(payload): Json<RequestOtpRequest>,
+) -> Result<Json<serde_json::Value>> {
+    println!("📲 OTP requested for phone: {}", payload.phone);
+
+    let collection: Collection<User> = state.db.collection("users");
+    collection
+        .find_one(doc! { "phone": &payload.phone })
+        .await?
+        .ok_or(AppError::UserNotFound)?;
+
+    otp::send_code(&state, &payload.phone).await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Verification code sent"
+    })))
+}
+
+// ========== VERIFY OTP (the only place tokens are issued) ==========
+pub async fn verify_otp(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyOtpRequest>,
+) -> Result<Json<serde_json::Value>> {
+    println!("🔐 Verifying OTP for phone: {}", payload.phone);
+
+    let collection: Collection<User> = state.db.collection("users");
+    let user = collection
+        .find_one(doc! { "phone": &payload.phone })
+        .await?
+        .ok_or(AppError::UserNotFound)?;
+    let user_id = user.id.ok_or(AppError::UserNotFound)?;
+
+    otp::verify_code(&state, &payload.phone, &payload.code).await?;
+
+    if !user.phone_verified {
+        collection
+            .update_one(
+                doc! { "_id": user_id },
+                doc! { "$set": {
+                    "phone_verified": true,
+                    "updated_at": DateTime::from_chrono(Utc::now())
+                }},
+            )
+            .await?;
+    }
+
+    let tokens = session::create(&state, &user, payload.device_id.as_deref()).await?;
+    let user_response = UserResponse {
+        id: user_id.to_hex(),
+        username: user.username,
+        phone: user.phone,
+        balance: user.balance,
+        phone_verified: true,
     };
-    
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "secret".to_string());
-    
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref()))
-        .unwrap_or_else(|_| "".to_string())
-}
\ No newline at end of file
+
+    println!("✅ Phone verified for: {}", user_response.username);
+    Ok(Json(json!({
+        "success": true,
+        "user": user_response,
+        "token": tokens.token,
+        "refresh_token": tokens.refresh_token,
+        "expires_in": tokens.expires_in
+    })))
+}
+
+// ========== REFRESH ACCESS TOKEN ==========
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<SessionTokens>> {
+    let tokens = session::refresh(&state, &payload.refresh_token).await?;
+    Ok(Json(tokens))
+}
+
+// ========== LOGOUT (this device) ==========
+pub async fn logout(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>> {
+    println!("🚪 Logging out session {} of {}", auth.session_id, auth.user_id);
+
+    session::revoke(&state, &auth.session_id).await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Logged out"
+    })))
+}
+
+// ========== LOGOUT ALL DEVICES ==========
+pub async fn logout_all(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>> {
+    let sessions_revoked = session::revoke_all(&state, &auth.user_id).await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Logged out of all devices",
+        "sessions_revoked": sessions_revoked
+    })))
+}
@@ -1,35 +1,14 @@
 // handlers/b2c_handlers.rs
 use axum::{
     extract::{State, Json},
-    http::StatusCode,
     response::IntoResponse,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use tracing::{info, error};
 
+use crate::services::withdrawal;
 use crate::state::AppState;
 
-// B2C Request
-#[derive(Debug, Deserialize)]
-pub struct B2CRequest {
-    pub phone_number: String,
-    pub amount: String,
-    pub command_id: String,
-    pub remarks: String,
-    pub occasion: Option<String>,
-}
-
-// B2C Response
-#[derive(Debug, Serialize)]
-pub struct B2CResponse {
-    pub success: bool,
-    pub conversation_id: String,
-    pub originator_conversation_id: String,
-    pub response_code: String,
-    pub response_description: String,
-    pub message: String,
-}
-
 // B2C Callback
 #[derive(Debug, Deserialize)]
 pub struct B2CCallback {
@@ -53,11 +32,12 @@ pub struct B2CResult {
     #[serde(rename = "ConversationID")]
     pub conversation_id: String,
 
-    #[serde(rename = "TransactionID")]
+    #[serde(rename = "TransactionID", default)]
     pub transaction_id: String,
 
-    #[serde(rename = "ResultParameters")]
-    pub result_parameters: B2CResultParameters,
+    // Absent on most failed results
+    #[serde(rename = "ResultParameters", default)]
+    pub result_parameters: Option<B2CResultParameters>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,81 +56,61 @@ pub struct B2CResultParameter {
 }
 
 // B2C Handlers
-pub async fn send_b2c_payment(
+pub async fn b2c_result_callback(
     State(state): State<AppState>,
-    Json(request): Json<B2CRequest>,
+    Json(callback): Json<B2CCallback>,
 ) -> impl IntoResponse {
-    info!("Received B2C request: {:?}", request);
-
-    let valid_commands = ["BusinessPayment", "SalaryPayment", "PromotionPayment"];
-    if !valid_commands.contains(&request.command_id.as_str()) {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "success": false,
-                "error": format!("Invalid command_id. Must be: BusinessPayment, SalaryPayment, or PromotionPayment")
-            }))
-        );
+    info!("Received B2C result callback: {:?}", callback.Result);
+
+    let result = &callback.Result;
+    if result.result_code == 0 {
+        info!("B2C payment successful: {}", result.transaction_id);
+    } else {
+        error!("B2C payment failed: {} - {}", result.result_code, result.result_desc);
     }
 
-    let mpesa_service = match &state.mpesa_service {
-        Some(service) => service,
-        None => {
-            error!("M-Pesa service not available");
-            return (
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(serde_json::json!({
-                    "success": false,
-                    "error": "M-Pesa service is not available"
-                }))
-            );
-        }
-    };
-
-    match mpesa_service.send_b2c_payment(
-        &request.phone_number,
-        &request.amount,
-        &request.command_id,
-        &request.remarks,
-        request.occasion.as_deref(),
-    ).await {
-        Ok(response) => {
-            info!("B2C payment initiated: {}", response.conversation_id);
-
-            let api_response = B2CResponse {
-                success: true,
-                conversation_id: response.conversation_id,
-                originator_conversation_id: response.originator_conversation_id,
-                response_code: response.response_code,
-                response_description: response.response_description,
-                message: "B2C payment initiated successfully".to_string(),
-            };
-
-            (StatusCode::OK, Json(serde_json::json!(api_response)))
-        }
-        Err(e) => {
-            error!("Failed to send B2C payment: {}", e);
-
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "success": false,
-                    "error": e.to_string()
-                }))
-            )
-        }
+    if let Err(e) = withdrawal::complete_from_result(
+        &state,
+        &result.conversation_id,
+        &result.originator_conversation_id,
+        result.result_code,
+        &result.result_desc,
+        &result.transaction_id,
+    )
+    .await
+    {
+        error!("Failed to finalize withdrawal {}: {}", result.conversation_id, e);
     }
+
+    // Always acknowledge, otherwise Daraja keeps retrying
+    Json(serde_json::json!({
+        "ResultCode": 0,
+        "ResultDesc": "Success"
+    }))
 }
 
-pub async fn b2c_result_callback(
-    Json(callback): Json<B2CCallback>,
+pub async fn b2c_timeout_callback(
+    State(state): State<AppState>,
+    Json(payload): Json<serde_json::Value>,
 ) -> impl IntoResponse {
-    info!("Received B2C result callback: {:?}", callback.Result);
+    info!("Received B2C timeout callback: {:?}", payload);
 
-    if callback.Result.result_code == 0 {
-        info!("B2C payment successful: {}", callback.Result.transaction_id);
-    } else {
-        error!("B2C payment failed: {} - {}", callback.Result.result_code, callback.Result.result_desc);
+    let result = payload.get("Result").unwrap_or(&payload);
+    let conversation_id = result
+        .get("ConversationID")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    let originator_conversation_id = result
+        .get("OriginatorConversationID")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+
+    if conversation_id.is_empty() && originator_conversation_id.is_empty() {
+        error!("B2C timeout callback without a ConversationID");
+    } else if let Err(e) =
+        withdrawal::mark_timed_out(&state, conversation_id, originator_conversation_id).await
+    {
+        error!("Failed to query timed-out withdrawal {}: {}", conversation_id, e);
     }
 
     Json(serde_json::json!({
@@ -159,10 +119,52 @@ pub async fn b2c_result_callback(
     }))
 }
 
-pub async fn b2c_timeout_callback(
+impl B2CResult {
+    /// A string value from ResultParameters, e.g. "TransactionStatus".
+    fn parameter(&self, key: &str) -> Option<&str> {
+        self.result_parameters
+            .as_ref()?
+            .result_parameter
+            .iter()
+            .find(|parameter| parameter.key == key)?
+            .value
+            .as_str()
+    }
+}
+
+// Transaction status query result (sent after a B2C timeout)
+pub async fn transaction_status_result_callback(
+    State(state): State<AppState>,
+    Json(callback): Json<B2CCallback>,
+) -> impl IntoResponse {
+    info!("Received transaction status result: {:?}", callback.Result);
+
+    let result = &callback.Result;
+    if let Err(e) = withdrawal::resolve_from_status(
+        &state,
+        &result.conversation_id,
+        &result.originator_conversation_id,
+        result.result_code,
+        &result.result_desc,
+        result.parameter("TransactionStatus"),
+        result.parameter("ReceiptNo"),
+    )
+    .await
+    {
+        error!("Failed to resolve withdrawal from status {}: {}", result.conversation_id, e);
+    }
+
+    Json(serde_json::json!({
+        "ResultCode": 0,
+        "ResultDesc": "Success"
+    }))
+}
+
+// The status query timed out too; the withdrawal status job asks again
+pub async fn transaction_status_timeout_callback(
     Json(payload): Json<serde_json::Value>,
 ) -> impl IntoResponse {
-    info!("Received B2C timeout callback: {:?}", payload);
+    info!("Received transaction status timeout: {:?}", payload);
 
     Json(serde_json::json!({
         "ResultCode": 0,
@@ -13,10 +13,12 @@ use serde::Deserialize;
 use crate::{
     errors::{AppError, Result},
     models::bets::{
-        Bet, BetResponse, CreateBetRequest, PledgeId, SuccessResponse, UpdateBalanceRequest,
-        UpdateBetRequest, UpdatePledgeStatusRequest,
+        Bet, BetQuote, BetQuoteQuery, BetResponse, SuccessResponse, UpdateBetRequest,
+        UpdatePledgeStatusRequest,
     },
+    models::money::Money,
     models::pledges::Pledge,
+    services::{escrow, odds},
     state::AppState,
 };
 
@@ -77,98 +79,53 @@ pub async fn get_bets(
     Ok(Json(responses))
 }
 
-// Create a new bet
-pub async fn create_bet(
+// GET /api/bets/quote - Stake/payout breakdown at the fixture's current odds
+pub async fn get_bet_quote(
     State(state): State<AppState>,
-    Json(payload): Json<CreateBetRequest>,
-) -> Result<Json<BetResponse>> {
-    println!(
-        "🎯 Creating new bet for pledge: {}",
-        payload.pledge_id.to_string()
-    );
-
-    // Validate required fields
-    if payload.starter_id.is_empty() {
-        return Err(AppError::MissingRequiredField("starter_id".to_string()));
-    }
-    if payload.finisher_id.is_empty() {
-        return Err(AppError::MissingRequiredField("finisher_id".to_string()));
-    }
-
-    if payload.starter_amount <= 0.0 {
-        return Err(AppError::ValidationError(
-            "starter_amount must be greater than 0".to_string(),
-        ));
-    }
-
-    // Validate selections
-    let valid_selections = ["home_team", "away_team", "draw"];
-    if !valid_selections.contains(&payload.starter_selection.as_str())
-        || !valid_selections.contains(&payload.finisher_selection.as_str())
-    {
-        return Err(AppError::ValidationError(
-            "Invalid selection. Must be home_team, away_team or draw".to_string(),
-        ));
-    }
-
-    if payload.starter_selection == payload.finisher_selection {
-        return Err(AppError::ValidationError(
-            "Starter and finisher must have opposite selections".to_string(),
-        ));
-    }
-
-    let finisher_amount = payload
-        .finisher_amount
-        .unwrap_or_else(|| payload.total_pot - payload.starter_amount);
-
-    if finisher_amount <= 0.0 {
-        return Err(AppError::ValidationError(
-            "Finisher amount must be greater than 0".to_string(),
-        ));
-    }
-
-    let collection: Collection<Bet> = state.db.collection("bets");
-    let now = Utc::now();
-
-    let bet: Bet = Bet {
-        id: Some(ObjectId::new()),
-        pledge_id: payload.pledge_id.to_string(),
-        starter_id: payload.starter_id.clone(),
-        starter_username: payload.starter_username.clone(),
-        starter_selection: payload.starter_selection.clone(),
-        starter_amount: payload.starter_amount,
-        starter_team: payload.starter_team.clone(),
-        finisher_id: payload.finisher_id.clone(),
-        finisher_username: payload.finisher_username.clone(),
-        finisher_selection: payload.finisher_selection.clone(),
-        finisher_amount,
-        finisher_team: payload.finisher_team.clone(),
-        home_team: payload.home_team.clone(),
-        away_team: payload.away_team.clone(),
-        match_time: payload.match_time,
-        league: payload.league.clone(),
-        sport_type: payload.sport_type.clone(),
-        total_pot: payload.total_pot,
-        status: payload.status.clone(),
-        winner_id: payload.winner_id,
-        winner_username: payload.winner_username,
-        winning_selection: payload.winning_selection,
-        odds: payload.odds.clone(),
-        created_at: now,
-        updated_at: now,
-        completed_at: None,
+    Query(query): Query<BetQuoteQuery>,
+) -> Result<Json<BetQuote>> {
+    println!("🧮 Quoting bet: {:?}", query);
+
+    let quote = match &query.pledge_id {
+        // Accepting an existing pledge
+        Some(pledge_id) => {
+            let pledges: Collection<Pledge> = state.db.collection("pledges");
+            let pledge = pledges
+                .find_one(doc! { "_id": ObjectId::parse_str(pledge_id)? })
+                .await?
+                .ok_or(AppError::DocumentNotFound)?;
+            let (game, bet_odds) = odds::snapshot(
+                &state,
+                pledge.match_id.as_deref(),
+                &pledge.home_team,
+                &pledge.away_team,
+            )
+            .await?;
+            odds::quote(&game, &bet_odds, &pledge.selection, pledge.amount)?
+        }
+        // Pricing a pledge before it is made
+        None => {
+            let match_id = query
+                .match_id
+                .as_deref()
+                .ok_or_else(|| AppError::MissingRequiredField("match_id or pledge_id".to_string()))?;
+            let selection = query
+                .selection
+                .as_deref()
+                .ok_or_else(|| AppError::MissingRequiredField("selection".to_string()))?;
+            let amount = query
+                .amount
+                .ok_or_else(|| AppError::MissingRequiredField("amount".to_string()))?;
+            let (game, bet_odds) = odds::snapshot(&state, Some(match_id), "", "").await?;
+            odds::quote(&game, &bet_odds, selection, amount)?
+        }
     };
 
-    collection.insert_one(&bet).await?;
-
     println!(
-        "✅ Successfully created bet: {} - Total Pot: ₿{}",
-        bet.id.as_ref().map(|id| id.to_hex()).unwrap_or_default(),
-        payload.total_pot
+        "✅ Quote: ₿{} at {} needs ₿{} (pot ₿{})",
+        quote.starter_amount, quote.odds, quote.finisher_amount, quote.total_pot
     );
-
-    let response = BetResponse::from(bet);
-    Ok(Json(response))
+    Ok(Json(quote))
 }
 
 // Get bet statistics
@@ -194,7 +151,7 @@ pub async fn get_bet_stats(
     let bets: Vec<Bet> = cursor.try_collect().await?;
 
     let total_bets = bets.len() as i64;
-    let total_pot: f64 = bets.iter().map(|p| p.total_pot).sum();
+    let total_pot: Money = bets.iter().map(|p| p.total_pot).sum();
     let active_bets = bets.iter().filter(|p| p.status == "active").count() as i64;
     let completed_bets = bets.iter().filter(|p| p.status == "completed").count() as i64;
 
@@ -203,7 +160,7 @@ pub async fn get_bet_stats(
         "total_pot": total_pot,
         "active_bets": active_bets,
         "completed_bets": completed_bets,
-        "average_pot": if total_bets > 0 { total_pot / total_bets as f64 } else { 0.0 }
+        "average_pot": if total_bets > 0 { total_pot.mul_ratio(1, total_bets) } else { Money::ZERO }
     });
 
     println!("✅ Successfully fetched bet statistics");
@@ -277,7 +234,7 @@ pub async fn get_bet_by_id(
     Ok(Json(response))
 }
 
-// Update bet status
+// Update bet status (admin only, mounted under /api/admin)
 pub async fn update_bet_status(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -295,23 +252,50 @@ pub async fn update_bet_status(
     let collection: Collection<Bet> = state.db.collection("bets");
 
     let filter = doc! { "_id": ObjectId::parse_str(&id)? };
-    let update = doc! {
-        "$set": {
-            "winner_id": &payload.winner_id,
-            "winner_username": &payload.winner_username,
-            "winning_selection": &payload.winning_selection,
-            "status": &payload.status,
-            "completed_at": Utc::now(),
-            "updated_at": Utc::now()
+    let existing = collection
+        .find_one(filter.clone())
+        .await?
+        .ok_or(AppError::DocumentNotFound)?;
+
+    let mut commission = None;
+    match payload.status.as_str() {
+        "completed" => {
+            if payload.winner_id != existing.starter_id && payload.winner_id != existing.finisher_id {
+                return Err(AppError::ValidationError(
+                    "winner_id must be the starter or the finisher".to_string(),
+                ));
+            }
+            let rule = state
+                .config
+                .commission
+                .rule_for(&existing.league, &existing.sport_type);
+            let payout = escrow::settle_bet(&state, &id, &payload.winner_id, rule).await?;
+            if payout.paid.is_positive() {
+                commission = Some(payout.commission);
+            }
         }
-    };
+        "cancelled" => {
+            escrow::release_bet(&state, &id).await?;
+        }
+        _ => {}
+    }
 
-    let options = mongodb::options::FindOneAndUpdateOptions::builder()
-        .return_document(mongodb::options::ReturnDocument::After)
-        .build();
+    let mut fields = doc! {
+        "winner_id": &payload.winner_id,
+        "winner_username": &payload.winner_username,
+        "winning_selection": &payload.winning_selection,
+        "status": &payload.status,
+        "completed_at": Utc::now(),
+        "updated_at": Utc::now()
+    };
+    if let Some(commission) = commission {
+        fields.insert("commission", commission);
+    }
+    let update = doc! { "$set": fields };
 
     let bet = collection
         .find_one_and_update(filter, update)
+        .return_document(mongodb::options::ReturnDocument::After)
         .await?
         .ok_or(AppError::DocumentNotFound)?;
 
@@ -319,38 +303,7 @@ pub async fn update_bet_status(
     Ok(Json(response))
 }
 
-// Update user balance
-pub async fn update_user_balance(
-    State(state): State<AppState>,
-    Json(payload): Json<UpdateBalanceRequest>,
-) -> Result<Json<SuccessResponse>> {
-    println!("💰 Updating balance for user: {}", payload.user_id);
-
-    if payload.balance < 0.0 {
-        return Err(AppError::ValidationError(
-            "Balance cannot be negative".to_string(),
-        ));
-    }
-
-    let collection: Collection<mongodb::bson::Document> = state.db.collection("users");
-
-    let filter = doc! { "id": &payload.user_id };
-    let update = doc! {
-        "$set": { "balance": payload.balance }
-    };
-
-    collection.update_one(filter, update).await?;
-
-    let response = SuccessResponse {
-        success: true,
-        message: format!("Balance updated to ₿{}", payload.balance),
-        data: None,
-    };
-
-    Ok(Json(response))
-}
-
-// Update pledge status
+// Update pledge status (admin only, mounted under /api/admin)
 pub async fn update_pledge_status(
     State(state): State<AppState>,
     Path(pledge_id): Path<String>,
@@ -384,6 +337,10 @@ pub async fn update_pledge_status(
         return Err(AppError::DocumentNotFound);
     }
 
+    if payload.status == "cancelled" {
+        escrow::release_pledge_hold(&state, &pledge_id).await?;
+    }
+
     let response = SuccessResponse {
         success: true,
         message: format!("Pledge status updated to '{}'", payload.status),
@@ -393,7 +350,7 @@ pub async fn update_pledge_status(
     Ok(Json(response))
 }
 
-// Delete a bet
+// Delete a bet (admin only, mounted under /api/admin)
 pub async fn delete_bet(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -409,6 +366,9 @@ pub async fn delete_bet(
         return Err(AppError::DocumentNotFound);
     }
 
+    // Anything still staked on a deleted bet goes back to its owners
+    escrow::release_bet(&state, &id).await?;
+
     let response = SuccessResponse {
         success: true,
         message: "Bet deleted successfully".to_string(),
@@ -14,6 +14,7 @@ use crate::models::chat::{
     ApiResponse, ChatMessage, ChatMessageResponse, CreateChatMessage, MarkAsSeenRequest,
     PaginationQuery, UpdateChatMessage,
 };
+use crate::middleware::auth::AuthUser;
 use crate::state::AppState;
 
 // Get collection helper
@@ -97,8 +98,11 @@ pub async fn get_post_messages(
 pub async fn create_message(
     State(state): State<AppState>,
     Path(post_id): Path<String>,
-    Json(payload): Json<CreateChatMessage>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreateChatMessage>,
 ) -> impl IntoResponse {
+    payload.sender_id = auth.user_id;
+    payload.sender_name = auth.username;
     println!("📝 Creating message for post: {}", post_id);
     println!("📨 Sender: {} ({})", payload.sender_name, payload.sender_id);
     println!(
@@ -343,10 +347,29 @@ pub async fn get_message(
     }
 }
 
-// PUT /chat/messages/:message_id
+// 403 unless the caller sent the message or is a moderator; 404 if it's gone
+async fn check_message_owner(
+    collection: &Collection<ChatMessage>,
+    object_id: ObjectId,
+    auth: &AuthUser,
+) -> Result<(), (StatusCode, String)> {
+    match collection.find_one(doc! { "_id": object_id }).await {
+        Ok(Some(message)) => auth
+            .ensure_owner_or_moderator(&message.sender_id)
+            .map_err(|_| (StatusCode::FORBIDDEN, "You can only change your own messages".to_string())),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Message not found".to_string())),
+        Err(err) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load message: {}", err),
+        )),
+    }
+}
+
+// PUT /chat/messages/:message_id - user's own only (or a moderator)
 pub async fn update_message(
     State(state): State<AppState>,
     Path(message_id): Path<String>,
+    auth: AuthUser,
     Json(payload): Json<UpdateChatMessage>,
 ) -> impl IntoResponse {
     println!("📝 Updating message: {}", message_id);
@@ -355,6 +378,10 @@ pub async fn update_message(
 
     match ObjectId::parse_str(&message_id) {
         Ok(object_id) => {
+            if let Err((status, message)) = check_message_owner(&collection, object_id, &auth).await {
+                return (status, Json(ApiResponse::error(message)));
+            }
+
             let filter = doc! { "_id": object_id };
 
             let update = doc! {
@@ -399,10 +426,11 @@ pub async fn update_message(
     }
 }
 
-// DELETE /chat/messages/:message_id
+// DELETE /chat/messages/:message_id - user's own only (or a moderator)
 pub async fn delete_message(
     State(state): State<AppState>,
     Path(message_id): Path<String>,
+    auth: AuthUser,
 ) -> impl IntoResponse {
     println!("🗑️ Deleting message: {}", message_id);
 
@@ -410,6 +438,10 @@ pub async fn delete_message(
 
     match ObjectId::parse_str(&message_id) {
         Ok(object_id) => {
+            if let Err((status, message)) = check_message_owner(&collection, object_id, &auth).await {
+                return (status, Json(ApiResponse::error(message)));
+            }
+
             let filter = doc! { "_id": object_id };
 
             match collection.delete_one(filter).await {
@@ -454,8 +486,10 @@ pub async fn delete_message(
 // POST /chat/messages/mark-seen
 pub async fn mark_messages_as_seen(
     State(state): State<AppState>,
-    Json(payload): Json<MarkAsSeenRequest>,
+    auth: AuthUser,
+    Json(mut payload): Json<MarkAsSeenRequest>,
 ) -> impl IntoResponse {
+    payload.user_id = auth.user_id;
     println!("👁️ Marking messages as seen for post: {}", payload.post_id);
 
     let collection = get_chat_collection(&state.db);
@@ -623,3 +657,48 @@ pub async fn health_check() -> impl IntoResponse {
 
     (StatusCode::OK, Json(ApiResponse::success(response)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::DateTime as BsonDateTime;
+    use crate::test_support::{assert_owner_or_moderator_only, OWNER};
+
+    async fn seed_message(state: AppState) -> String {
+        let message = ChatMessage {
+            id: None,
+            post_id: "post-1".to_string(),
+            sender_id: OWNER.to_string(),
+            receiver_id: "receiver".to_string(),
+            sender_name: OWNER.to_string(),
+            receiver_name: "receiver".to_string(),
+            message: "hello".to_string(),
+            seen: false,
+            created_at: BsonDateTime::now(),
+            updated_at: None,
+        };
+        let inserted = get_chat_collection(&state.db).insert_one(&message).await.unwrap();
+        inserted.inserted_id.as_object_id().unwrap().to_hex()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn update_message_is_sender_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_message, |state, id, auth| async move {
+            let edit = Json(UpdateChatMessage {
+                message: "edited".to_string(),
+            });
+            update_message(State(state), Path(id), auth, edit).await.into_response()
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn delete_message_is_sender_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_message, |state, id, auth| async move {
+            delete_message(State(state), Path(id), auth).await.into_response()
+        })
+        .await;
+    }
+}
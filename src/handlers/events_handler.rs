@@ -93,7 +93,7 @@ pub async fn add_timeline_event(
     })))
 }
 
-// DELETE all events for a match
+// DELETE all events for a match (poller or admin, mounted under /api/admin)
 pub async fn delete_match_events(
     State(state): State<AppState>,
     Path(match_id): Path<String>,
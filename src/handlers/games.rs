@@ -18,6 +18,7 @@ use crate::models::game::{
     StatisticsData, TimelineEvent, TimelineEventData, UpdateGameScore, Voter,
 };
 use crate::models::notification::FCMToken;
+use crate::services::settlement;
 use crate::state::AppState;
 
 // ============================================================================
@@ -595,7 +596,7 @@ async fn send_goal_notification_to_voter(
     Ok(())
 }
 
-fn parse_kickoff_utc(date_iso: &str, time_str: &str) -> Option<chrono::DateTime<Utc>> {
+pub(crate) fn parse_kickoff_utc(date_iso: &str, time_str: &str) -> Option<chrono::DateTime<Utc>> {
     let date = NaiveDate::parse_from_str(date_iso, "%Y-%m-%d").ok()?;
     let time = NaiveTime::parse_from_str(time_str, "%H:%M").ok()?;
     let naive = NaiveDateTime::new(date, time);
@@ -789,6 +790,10 @@ pub async fn update_game_score(
         return Err(AppError::DocumentNotFound);
     }
 
+    if payload.status.as_deref() == Some("completed") {
+        settlement::spawn_settle_fixture(&state, &match_id);
+    }
+
     match collection.find_one(filter).await? {
         Some(game) => Ok(Json(game)),
         None => Err(AppError::DocumentNotFound),
@@ -889,17 +894,26 @@ pub async fn update_game_status(
     }
 
     let filter = doc! { "match_id": &match_id };
-    let update = doc! { "$set": {
+    let mut set_doc = doc! {
         "status": &payload.status,
         "is_live": payload.is_live,
         "scraped_at": BsonDateTime::from_chrono(Utc::now()),
-    }};
+    };
+    if payload.status == "completed" {
+        set_doc.insert("completed_at", BsonDateTime::from_chrono(Utc::now()));
+    }
 
-    let result = collection.update_one(filter.clone(), update).await?;
+    let result = collection
+        .update_one(filter.clone(), doc! { "$set": set_doc })
+        .await?;
     if result.matched_count == 0 {
         return Err(AppError::DocumentNotFound);
     }
 
+    if payload.status == "completed" {
+        settlement::spawn_settle_fixture(&state, &match_id);
+    }
+
     match collection.find_one(filter).await? {
         Some(game) => Ok(Json(game)),
         None => Err(AppError::DocumentNotFound),
@@ -1389,6 +1403,12 @@ pub async fn receive_live_update(
         }
     }
 
+    if update.event_type == "full_time" {
+        set_doc.insert("status", "completed");
+        set_doc.insert("is_live", false);
+        set_doc.insert("completed_at", BsonDateTime::from_chrono(Utc::now()));
+    }
+
     games_col
         .update_one(filter.clone(), doc! { "$set": set_doc })
         .await?;
@@ -1427,7 +1447,12 @@ pub async fn receive_live_update(
     )
     .await;
 
-    // ========== 4. SEND PUSH NOTIFICATIONS TO VOTERS ==========
+    // ========== 4. SETTLE BETS AT FULL TIME ==========
+    if update.event_type == "full_time" {
+        settlement::spawn_settle_fixture(&state, &update.fixture_id);
+    }
+
+    // ========== 5. SEND PUSH NOTIFICATIONS TO VOTERS ==========
     if update.event_type == "goal" {
         if let Some(ref fixture) = games_col.find_one(filter).await? {
             if !fixture.voters.is_empty() {
@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use axum_extra::extract::Multipart;
+use chrono::NaiveDate;
+
+use crate::{
+    errors::{AppError, Result},
+    middleware::auth::AuthUser,
+    models::kyc::{KycQuery, KycReviewItem, KycReviewRequest, KycStatusResponse},
+    services::kyc,
+    state::AppState,
+};
+
+/// Also enforced on the route as the request body limit.
+pub const MAX_DOCUMENT_BYTES: usize = 8 * 1024 * 1024;
+const ALLOWED_DOCUMENT_TYPES: [&str; 2] = ["image/jpeg", "image/png"];
+
+// GET /api/kyc - The caller's verification status
+pub async fn get_kyc_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<KycStatusResponse>> {
+    let record = kyc::get(&state, &auth.user_id).await?;
+    Ok(Json(KycStatusResponse::from(record)))
+}
+
+// POST /api/kyc - Multipart: id_number, date_of_birth (YYYY-MM-DD), document (photo)
+pub async fn submit_kyc(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    mut multipart: Multipart,
+) -> Result<Json<KycStatusResponse>> {
+    println!("🪪 KYC submission from user: {}", auth.user_id);
+
+    let mut id_number = None;
+    let mut date_of_birth = None;
+    let mut document = None;
+
+    while let Some(field) = multipart.next_field().await? {
+        match field.name().unwrap_or("") {
+            "id_number" => id_number = Some(field.text().await?),
+            "date_of_birth" => {
+                let raw = field.text().await?;
+                let parsed = NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").map_err(|_| {
+                    AppError::invalid_data("date_of_birth must be formatted YYYY-MM-DD")
+                })?;
+                date_of_birth = Some(parsed);
+            }
+            "document" => {
+                let data = field.bytes().await?;
+                if data.len() > MAX_DOCUMENT_BYTES {
+                    return Err(AppError::ImageTooLarge);
+                }
+                // Trust the bytes, not the client's content type
+                let mime = infer::get(&data).map(|kind| kind.mime_type());
+                if !mime.is_some_and(|mime| ALLOWED_DOCUMENT_TYPES.contains(&mime)) {
+                    return Err(AppError::InvalidImageFormat);
+                }
+                document = Some(data);
+            }
+            _ => continue,
+        }
+    }
+
+    let id_number = id_number.ok_or_else(|| AppError::missing_field("id_number"))?;
+    let date_of_birth = date_of_birth.ok_or_else(|| AppError::missing_field("date_of_birth"))?;
+    let document = document.ok_or(AppError::NoImageProvided)?;
+
+    let record = kyc::submit(&state, &auth.user_id, &id_number, date_of_birth, &document).await?;
+    Ok(Json(KycStatusResponse::from(Some(record))))
+}
+
+// GET /api/admin/kyc?status=pending - Review queue, documents as expiring links
+pub async fn list_kyc_submissions(
+    State(state): State<AppState>,
+    Query(query): Query<KycQuery>,
+) -> Result<Json<Vec<KycReviewItem>>> {
+    let records = kyc::list(&state, query.status, query.limit).await?;
+    println!("🪪 Listed {} KYC submissions", records.len());
+    let items = records
+        .into_iter()
+        .map(|record| kyc::review_item(&state, record))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Json(items))
+}
+
+// PUT /api/admin/kyc/:user_id/review - Approve or reject a pending submission
+pub async fn review_kyc(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+    Json(payload): Json<KycReviewRequest>,
+) -> Result<Json<KycReviewItem>> {
+    let record = kyc::review(&state, &user_id, &auth.user_id, &payload).await?;
+    Ok(Json(kyc::review_item(&state, record)?))
+}
@@ -0,0 +1,178 @@
+// src/handlers/match_stream.rs
+//
+// Server-Sent Events for clients that can't hold a WebSocket open (web
+// widgets, browsers behind proxies). `GET /api/games/:match_id/stream`
+// relays the `match.*` frames `broadcast_live_match_update` publishes on
+// the fixture's match channel. Each event's id is the frame's seq, so a client
+// reconnecting with `Last-Event-ID` gets what it missed from the replay
+// buffer, or a `resync` event when that is gone.
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use chrono::Utc;
+use futures_util::{stream, Stream};
+use mongodb::{bson::doc, Collection};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::errors::{AppError, Result};
+use crate::handlers::ws_handler::{match_channel, match_state_frames};
+use crate::models::game::Game;
+use crate::services::broadcaster::{Broadcaster, Frame, Replay};
+use crate::state::AppState;
+
+const LAST_EVENT_ID: &str = "last-event-id";
+
+struct MatchStream {
+    broadcaster: Arc<dyn Broadcaster>,
+    match_id: String,
+    channel: String,
+    rx: broadcast::Receiver<Frame>,
+    last_sent: u64,
+    pending: VecDeque<Event>,
+}
+
+impl MatchStream {
+    /// Queue the buffered match events after `after_seq`, or a resync.
+    fn queue_replay(&mut self, after_seq: u64) {
+        match self.broadcaster.replay(&self.channel, after_seq) {
+            Replay::Frames(frames) => {
+                for frame in frames {
+                    if frame.seq <= self.last_sent {
+                        continue;
+                    }
+                    self.last_sent = frame.seq;
+                    if let Some(event) = match_event(&frame) {
+                        self.pending.push_back(event);
+                    }
+                }
+            }
+            Replay::Resync { seq } => {
+                tracing::info!(
+                    "🔄 SSE gap after seq {} on fixture {} too large, asking for resync",
+                    after_seq,
+                    self.match_id
+                );
+                // The client's seq may be from before a restart; go by ours
+                self.last_sent = seq;
+                let resync = json!({
+                    "type": "resync",
+                    "fixture_id": self.match_id,
+                    "seq": seq,
+                    "timestamp": Utc::now().to_rfc3339(),
+                });
+                self.pending.push_back(
+                    Event::default()
+                        .id(seq.to_string())
+                        .event("resync")
+                        .data(resync.to_string()),
+                );
+            }
+        }
+    }
+
+    async fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            match self.rx.recv().await {
+                Ok(frame) => {
+                    // Already sent by a replay
+                    if frame.seq <= self.last_sent {
+                        continue;
+                    }
+                    self.last_sent = frame.seq;
+                    if let Some(event) = match_event(&frame) {
+                        return Some(event);
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("SSE client lagged by {} messages, replaying", n);
+                    self.queue_replay(self.last_sent);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// The SSE event for a `match.*` frame; comments, votes etc. are skipped.
+fn match_event(frame: &Frame) -> Option<Event> {
+    let value: Value = serde_json::from_str(&frame.message).ok()?;
+    let frame_type = value.get("type")?.as_str()?;
+    if !frame_type.starts_with("match.") {
+        return None;
+    }
+    Some(
+        Event::default()
+            .id(frame.seq.to_string())
+            .event(frame_type)
+            .data(&frame.message),
+    )
+}
+
+// GET live match updates as Server-Sent Events
+pub async fn stream_match_updates(
+    State(state): State<AppState>,
+    Path(match_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    let games: Collection<Game> = state.db.collection("games");
+    let game = games
+        .find_one(doc! { "match_id": &match_id })
+        .await?
+        .ok_or(AppError::DocumentNotFound)?;
+
+    let last_event_id = headers
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    // Subscribe before replaying so nothing falls between the two
+    let channel = match_channel(&match_id);
+    let (rx, connected_seq) = state.comment_broadcaster.subscribe(&channel);
+    let mut match_stream = MatchStream {
+        broadcaster: state.comment_broadcaster.clone(),
+        match_id: match_id.clone(),
+        channel,
+        rx,
+        last_sent: connected_seq,
+        pending: VecDeque::new(),
+    };
+
+    match last_event_id {
+        Some(last_event_id) => {
+            tracing::info!("⏪ SSE resuming fixture {} after seq {}", match_id, last_event_id);
+            match_stream.last_sent = last_event_id;
+            match_stream.queue_replay(last_event_id);
+        }
+        None => {
+            // Start from the current state; its id lets a reconnect resume from here
+            for frame in match_state_frames(&match_id, &game) {
+                let frame_type = frame["type"].as_str().unwrap_or_default().to_string();
+                match_stream.pending.push_back(
+                    Event::default()
+                        .id(connected_seq.to_string())
+                        .event(frame_type)
+                        .data(frame.to_string()),
+                );
+            }
+        }
+    }
+
+    tracing::info!("📡 SSE stream opened for fixture {}", match_id);
+
+    let events = stream::unfold(match_stream, |mut match_stream| async move {
+        let event = match_stream.next_event().await?;
+        Some((Ok(event), match_stream))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
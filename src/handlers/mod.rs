@@ -10,12 +10,17 @@ pub(crate) mod bets;
 pub(crate) mod chat_handlers;
 pub(crate) mod comrade_handler;
 pub(crate) mod events_handler;
+pub(crate) mod kyc;
+pub(crate) mod match_stream;
 pub(crate) mod mpesa_handlers;
 pub(crate) mod notification_handler;
 pub(crate) mod posta;
+pub(crate) mod reports;
 pub(crate) mod statistics_handler;
 pub mod sub_fixture_handler;
 pub(crate) mod user_profile;
+pub(crate) mod wallet;
+pub(crate) mod withdrawals;
 
 pub(crate) mod vote_handlers;
 pub use sub_fixture_handler::*;
@@ -12,14 +12,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tracing::{error, info, warn};
 
+use crate::middleware::auth::AuthUser;
+use crate::models::gambling::LimitKind;
+use crate::models::ledger::{EntryKind, LedgerPosting, MPESA_CLEARING_ACCOUNT};
+use crate::models::money::Money;
 use crate::models::transaction::Transaction;
+use crate::services::ledger::{self, NewEntry};
+use crate::services::mpesa_audit;
+use crate::services::responsible_gambling;
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
 pub struct StkPushRequest {
     pub phone_number: String,
     pub amount: String,
-    pub account_reference: Option<String>,
+    pub account_reference: Option<String>, // Overwritten with the caller's user id
     pub transaction_desc: Option<String>,
 }
 #[derive(Debug, Deserialize)]
@@ -95,8 +102,12 @@ pub async fn mpesa_validation(
 // ✅ HANDLER 1: Initiate STK Push
 pub async fn initiate_stk_push(
     State(state): State<AppState>,
-    Json(request): Json<StkPushRequest>,
+    auth: AuthUser,
+    Json(mut request): Json<StkPushRequest>,
 ) -> Result<AxumJson<serde_json::Value>, (StatusCode, AxumJson<serde_json::Value>)> {
+    // The deposit is credited to the caller, never to a user named in the body
+    request.account_reference = Some(auth.user_id.clone());
+
     println!("🔵 [STK] === INITIATING STK PUSH ===");
     println!("📱 Phone: {}", request.phone_number);
     println!("💰 Amount: {}", request.amount);
@@ -113,8 +124,8 @@ pub async fn initiate_stk_push(
         ));
     }
 
-    let amount: f64 = match request.amount.parse() {
-        Ok(amount) if amount > 0.0 => amount,
+    let amount = match Money::parse_kes(&request.amount) {
+        Some(amount) if amount.is_positive() => amount,
         _ => {
             println!("❌ [STK] Invalid amount: {}", request.amount);
             return Err((
@@ -127,6 +138,18 @@ pub async fn initiate_stk_push(
         }
     };
 
+    // Deposit limits, cool-offs and self-exclusion
+    if let Err(e) = responsible_gambling::check(&state, &auth.user_id, LimitKind::Deposit, amount).await {
+        println!("🛡️ [STK] Deposit blocked: {}", e);
+        return Err((
+            StatusCode::FORBIDDEN,
+            AxumJson(json!({
+                "success": false,
+                "error": e.to_string()
+            })),
+        ));
+    }
+
     let mpesa_service = match &state.mpesa_service {
         Some(service) => service,
         None => {
@@ -188,6 +211,7 @@ pub async fn initiate_stk_push(
         status: "pending".to_string(),
         result_code: None,
         result_desc: None,
+        mpesa_receipt: None,
         created_at: Utc::now().to_rfc3339(), // ✅ String
         updated_at: Utc::now().to_rfc3339(), // ✅ String
         completed_at: None,
@@ -278,22 +302,16 @@ pub async fn mpesa_confirmation(
                 }
             };
 
-            // STEP 3: Determine new status based on ResultCode
-            let status = if callback.result_code == 0 {
-                "completed"
-            } else {
-                "failed"
-            };
-
-            println!("🔄 Updating transaction status to: {}", status);
-
-            // STEP 4: Extract amount from callback metadata if available
-            let mut amount = 0.0;
+            // STEP 3: Extract amount from callback metadata if available
+            let mut amount = Money::ZERO;
             if let Some(metadata) = &callback.callback_metadata {
                 for item in &metadata.items {
                     if item.name == "Amount" {
                         if let serde_json::Value::Number(num) = &item.value {
-                            amount = num.as_f64().unwrap_or(0.0);
+                            amount = num
+                                .as_f64()
+                                .and_then(Money::from_kes_f64)
+                                .unwrap_or(Money::ZERO);
                             println!("💰 Amount from callback: Ksh {}", amount);
                         }
                         break;
@@ -301,7 +319,7 @@ pub async fn mpesa_confirmation(
                 }
             }
 
-            // STEP 5: Extract M-Pesa receipt number if available
+            // STEP 4: Extract M-Pesa receipt number if available
             let mut mpesa_receipt = String::new();
             if let Some(metadata) = &callback.callback_metadata {
                 for item in &metadata.items {
@@ -315,19 +333,54 @@ pub async fn mpesa_confirmation(
                 }
             }
 
-            // STEP 6: Update using the document's _id (guaranteed to work)
+            // STEP 5: Determine new status. A success must be for exactly
+            // the amount we asked for, otherwise nothing is credited.
+            let paid = callback.result_code == 0;
+            let amount_matches = amount == transaction.amount;
+            let status = match (paid, amount_matches) {
+                (true, true) => "completed",
+                (true, false) => "amount_mismatch",
+                (false, _) => "failed",
+            };
+            if paid && !amount_matches {
+                mpesa_audit::record(
+                    &state,
+                    "/confirmation",
+                    None,
+                    &format!(
+                        "amount mismatch on {}: requested Ksh {}, callback says Ksh {}",
+                        checkout_id, transaction.amount, amount
+                    ),
+                    &json!({
+                        "checkout_request_id": &checkout_id,
+                        "result_code": callback.result_code,
+                        "amount": amount,
+                        "receipt": &mpesa_receipt,
+                    })
+                    .to_string(),
+                )
+                .await;
+            }
+
+            println!("🔄 Updating transaction status to: {}", status);
+
+            // STEP 6: Update using the document's _id (guaranteed to work).
+            // A completed transaction is never downgraded by a late retry.
             let update = doc! {
                 "$set": {
                     "status": status,
                     "result_code": callback.result_code,
                     "result_desc": &callback.result_desc,
-                    "mpesa_receipt": mpesa_receipt,
+                    "mpesa_receipt": &mpesa_receipt,
                     "updated_at": Utc::now().to_rfc3339(),
                     "completed_at": Utc::now().to_rfc3339(),
                 }
             };
 
-            match collection.update_one(doc! { "_id": id }, update).await {
+            match collection
+                .update_one(doc! { "_id": id, "status": { "$ne": "completed" } }, update)
+                .await
+            {
                 Ok(result) => {
                     if result.matched_count > 0 {
                         println!(
@@ -338,19 +391,24 @@ pub async fn mpesa_confirmation(
                             "Updated transaction {} to {} using _id",
                             checkout_id, status
                         );
+                    } else {
+                        println!("ℹ️ Transaction {} already completed (retry)", checkout_id);
+                    }
 
-                        if callback.result_code == 0 {
-                            info!(
-                                "💰 Payment successful: Ksh {} for checkout {}",
-                                amount, checkout_id
-                            );
-                            println!("🎉 Payment completed successfully!");
-                        } else {
-                            println!("❌ Payment failed: {}", callback.result_desc);
-                        }
+                    if status == "completed" {
+                        info!(
+                            "💰 Payment successful: Ksh {} for checkout {}",
+                            amount, checkout_id
+                        );
+                        println!("🎉 Payment completed successfully!");
+
+                        // STEP 7: Credit the wallet. The ledger entry id is the
+                        // idempotency key, so Safaricom retries credit only once.
+                        credit_deposit(&state, &transaction, &mpesa_receipt).await;
+                    } else if paid {
+                        println!("🚨 Payment amount mismatch, not credited");
                     } else {
-                        println!("⚠️ No document matched the _id! This should never happen.");
-                        error!("Failed to update transaction: no document matched _id");
+                        println!("❌ Payment failed: {}", callback.result_desc);
                     }
                 }
                 Err(e) => {
@@ -372,7 +430,7 @@ pub async fn mpesa_confirmation(
         }
     }
 
-    // STEP 7: Always return success to Safaricom (200 OK)
+    // STEP 8: Always return success to Safaricom (200 OK)
     // If we don't, Safaricom will keep retrying the callback
     println!("📤 Returning success response to Safaricom");
     println!("🎯 [CALLBACK COMPLETE] =================================");
@@ -383,6 +441,69 @@ pub async fn mpesa_confirmation(
     }))
 }
 
+// Post the deposit to the ledger and notify the user. Keyed on the checkout
+// id (one STK push can only ever pay once) so duplicate callbacks are no-ops.
+async fn credit_deposit(state: &AppState, transaction: &Transaction, receipt: &str) {
+    let user_id = transaction.user_id.as_str();
+    if user_id.is_empty() || user_id == "unknown" {
+        warn!(
+            "Deposit {} has no user to credit, needs manual reconciliation",
+            transaction.checkout_request_id
+        );
+        return;
+    }
+
+    // The callback amount has been checked against this
+    let amount = transaction.amount;
+    let checkout_id = transaction.checkout_request_id.as_str();
+    let reference = if receipt.is_empty() { checkout_id } else { receipt };
+
+    let entry = NewEntry {
+        entry_id: format!("mpesa:deposit:{}", checkout_id),
+        kind: EntryKind::Deposit,
+        reference: Some(reference.to_string()),
+        description: format!("M-Pesa deposit {}", reference),
+        postings: vec![
+            LedgerPosting::system(MPESA_CLEARING_ACCOUNT, -amount),
+            LedgerPosting::user_wallet(user_id, amount),
+        ],
+    };
+
+    match ledger::post_entry(state, entry).await {
+        Ok(true) => {
+            println!("💳 Credited Ksh {} to {}", amount, user_id);
+            notify_deposit(state, user_id, amount, reference).await;
+        }
+        Ok(false) => println!("ℹ️ Deposit {} already credited, skipping", checkout_id),
+        Err(e) => error!("❌ Failed to credit deposit {}: {}", checkout_id, e),
+    }
+}
+
+async fn notify_deposit(state: &AppState, user_id: &str, amount: Money, receipt: &str) {
+    let Some(fcm_service) = state.fcm_service.as_ref() else {
+        return;
+    };
+
+    let result = fcm_service
+        .send_to_user(
+            state,
+            user_id,
+            "💰 Deposit received",
+            &format!("Ksh {} has been added to your wallet", amount),
+            json!({
+                "type": "deposit",
+                "amount": amount,
+                "receipt": receipt,
+            }),
+            "deposit",
+        )
+        .await;
+
+    if let Err(e) = result {
+        warn!("Failed to send deposit notification to {}: {}", user_id, e);
+    }
+}
+
 // ✅ HANDLER 3: Check Payment Status (POST - for frontend polling)
 pub async fn check_payment_status(
     State(state): State<AppState>,
@@ -574,7 +695,7 @@ pub async fn simulate_payment(
         id: None,
         user_id: user_id.to_string(),
         phone_number: phone.to_string(),
-        amount: amount.parse().unwrap_or(10.0),
+        amount: Money::parse_kes(amount).unwrap_or(Money::from_kes(10)),
         merchant_request_id: format!("SIM-{}", Utc::now().timestamp()),
         checkout_request_id: format!("ws_CO_SIM_{}", Utc::now().timestamp()),
         response_code: "0".to_string(),
@@ -583,6 +704,7 @@ pub async fn simulate_payment(
         status: "completed".to_string(),
         result_code: Some(0),
         result_desc: Some("Processed successfully".to_string()),
+        mpesa_receipt: None,
         created_at: Utc::now().to_rfc3339(),         // ✅ String
         updated_at: Utc::now().to_rfc3339(),         // ✅ String
         completed_at: Some(Utc::now().to_rfc3339()), // ✅ String
@@ -1,18 +1,28 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     response::Json,
 };
 use chrono::Utc;
 use futures_util::TryStreamExt;
 use mongodb::{
     bson::{doc, oid::ObjectId},
+    options::ReturnDocument,
     Collection,
 };
 use serde::Deserialize;
 
 use crate::{
     errors::{AppError, Result},
-    models::pledges::{CreatePledge, Pledge, PledgeQuery},
+    handlers::games::parse_kickoff_utc,
+    middleware::auth::AuthUser,
+    models::bets::{Bet, BetResponse},
+    models::money::Money,
+    models::vote::validate_selection,
+    models::pledges::{
+        AcceptPledgeRequest, CreatePledge, Pledge, PledgeQuery, PLEDGE_MATCHED, PLEDGE_OPEN,
+    },
+    models::gambling::LimitKind,
+    services::{escrow, kyc, odds, pledge_expiry, responsible_gambling},
     state::AppState,
 };
 
@@ -49,6 +59,27 @@ pub async fn get_pledges(
         filter.insert("away_team", away_team);
     }
 
+    if let Some(starter_id) = &query.starter_id {
+        filter.insert("starter_id", starter_id);
+    }
+
+    match query.status.as_deref() {
+        // Open = still acceptable: not matched/cancelled and not past expiry
+        Some(PLEDGE_OPEN) => {
+            filter.insert(
+                "$and",
+                vec![
+                    doc! { "$or": [{ "status": PLEDGE_OPEN }, { "status": { "$exists": false } }] },
+                    doc! { "$or": [{ "expires_at": null }, { "expires_at": { "$gt": Utc::now() } }] },
+                ],
+            );
+        }
+        Some(status) => {
+            filter.insert("status", status);
+        }
+        None => {}
+    }
+
     let cursor = collection.find(filter).await?;
     let mut pledges: Vec<Pledge> = cursor.try_collect().await?;
 
@@ -61,8 +92,14 @@ pub async fn get_pledges(
 // Create a new pledge
 pub async fn create_pledge(
     State(state): State<AppState>,
-    Json(payload): Json<CreatePledge>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreatePledge>,
 ) -> Result<Json<Pledge>> {
+    // The pledger is whoever holds the token, whatever the body says
+    payload.starter_id = auth.user_id;
+    payload.username = auth.username;
+    payload.phone = auth.phone;
+
     println!("🎯 Creating new pledge for user: {}", payload.username);
 
     // Validate required fields — each gets its own error message
@@ -75,31 +112,74 @@ pub async fn create_pledge(
     if payload.selection.is_empty() {
         return Err(AppError::MissingRequiredField("selection".to_string()));
     }
+    validate_selection(&payload.selection).map_err(AppError::ValidationError)?;
 
-    if payload.amount <= 0.0 {
+    if !payload.amount.is_positive() {
         return Err(AppError::ValidationError(
             "amount must be greater than 0".to_string(),
         ));
     }
 
+    if payload.starter_id.is_empty() {
+        return Err(AppError::MissingRequiredField("starter_id".to_string()));
+    }
+
+    // Pledges close at kickoff, or earlier if the pledger asked for it
+    let now = Utc::now();
+    let kickoff = pledge_expiry::kickoff_for(
+        &state,
+        payload.match_id.as_deref(),
+        &payload.home_team,
+        &payload.away_team,
+    )
+    .await?;
+    if kickoff.is_some_and(|kickoff| kickoff <= now) {
+        return Err(AppError::ValidationError(
+            "This match has already kicked off".to_string(),
+        ));
+    }
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err(AppError::ValidationError(
+            "expires_at must be in the future".to_string(),
+        ));
+    }
+    let expires_at = match (payload.expires_at, kickoff) {
+        (Some(chosen), Some(kickoff)) => Some(chosen.min(kickoff)),
+        (chosen, kickoff) => chosen.or(kickoff),
+    };
+
+    kyc::ensure_verified(&state, &payload.starter_id).await?;
+    responsible_gambling::check(&state, &payload.starter_id, LimitKind::Stake, payload.amount).await?;
+
     let collection: Collection<Pledge> = state.db.collection("pledges");
+    let pledge_id = ObjectId::new();
+
+    // Reserve the stake before the pledge becomes visible to other users
+    escrow::hold_for_pledge(&state, &pledge_id.to_hex(), &payload.starter_id, payload.amount).await?;
 
     let pledge = Pledge {
-        _id: Some(ObjectId::new()),
+        _id: Some(pledge_id),
         username: payload.username.clone(),
         phone: payload.phone.clone(),
         selection: payload.selection.clone(),
         amount: payload.amount,
         time: Utc::now(),
         fan: payload.fan.clone(),
+        match_id: payload.match_id.clone(),
         home_team: payload.home_team.clone(),
         away_team: payload.away_team.clone(),
         starter_id: payload.starter_id.clone(),
+        status: PLEDGE_OPEN.to_string(),
+        bet_id: None,
+        expires_at,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
 
-    collection.insert_one(&pledge).await?;
+    if let Err(e) = collection.insert_one(&pledge).await {
+        escrow::release_pledge_hold(&state, &pledge_id.to_hex()).await?;
+        return Err(e.into());
+    }
 
     println!(
         "✅ Successfully created pledge for user: {} - Amount: ₿{}",
@@ -108,6 +188,207 @@ pub async fn create_pledge(
     Ok(Json(pledge))
 }
 
+// POST /api/pledges/:id/accept - Match an open pledge and create the bet
+pub async fn accept_pledge(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<AcceptPledgeRequest>,
+) -> Result<Json<BetResponse>> {
+    println!("🤝 User {} accepting pledge {}", auth.user_id, id);
+    kyc::ensure_verified(&state, &auth.user_id).await?;
+
+    let pledge_oid = ObjectId::parse_str(&id)?;
+    let collection: Collection<Pledge> = state.db.collection("pledges");
+    let pledge = collection
+        .find_one(doc! { "_id": pledge_oid })
+        .await?
+        .ok_or(AppError::DocumentNotFound)?;
+
+    if pledge.starter_id == auth.user_id {
+        return Err(AppError::ValidationError(
+            "You cannot accept your own pledge".to_string(),
+        ));
+    }
+    if pledge.status != PLEDGE_OPEN {
+        return Err(AppError::PledgeAlreadyMatched);
+    }
+    if pledge.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(AppError::ValidationError("This pledge has expired".to_string()));
+    }
+
+    // Derive the finisher's side and stake from the pledge
+    let finisher_selection = match (pledge.selection.as_str(), payload.finisher_selection.as_deref()) {
+        (starter, Some(finisher)) if finisher == starter => {
+            return Err(AppError::ValidationError(
+                "Finisher must pick a different selection than the starter".to_string(),
+            ));
+        }
+        (_, Some(finisher)) if ["home_team", "away_team", "draw"].contains(&finisher) => {
+            finisher.to_string()
+        }
+        (_, Some(_)) => {
+            return Err(AppError::ValidationError(
+                "Invalid selection. Must be home_team, away_team or draw".to_string(),
+            ));
+        }
+        ("home_team", None) => "away_team".to_string(),
+        ("away_team", None) => "home_team".to_string(),
+        _ => return Err(AppError::MissingRequiredField("finisher_selection".to_string())),
+    };
+
+    // Price the bet at the fixture's current odds; the finisher covers what
+    // the starter stands to win
+    let (game, bet_odds) = odds::snapshot(
+        &state,
+        pledge.match_id.as_deref(),
+        &pledge.home_team,
+        &pledge.away_team,
+    )
+    .await?;
+    let quote = odds::quote(&game, &bet_odds, &pledge.selection, pledge.amount)?;
+    let finisher_amount = quote.finisher_amount;
+    responsible_gambling::check(&state, &auth.user_id, LimitKind::Stake, finisher_amount).await?;
+
+    let bet_id = ObjectId::new();
+    let pledge = claim_pledge(&state, pledge_oid, &auth.user_id, &bet_id.to_hex()).await?;
+
+    if let Err(e) = escrow::lock_for_bet(
+        &state,
+        &id,
+        &bet_id.to_hex(),
+        &pledge.starter_id,
+        pledge.amount,
+        &auth.user_id,
+        finisher_amount,
+    )
+    .await
+    {
+        reopen_pledge(&state, pledge_oid, &bet_id.to_hex()).await?;
+        return Err(e);
+    }
+
+    let now = Utc::now();
+    let bet = Bet {
+        id: Some(bet_id),
+        pledge_id: id.clone(),
+        starter_id: pledge.starter_id.clone(),
+        starter_username: pledge.username.clone(),
+        starter_selection: pledge.selection.clone(),
+        starter_amount: pledge.amount,
+        starter_team: selection_team(&pledge, &pledge.selection),
+        finisher_id: auth.user_id.clone(),
+        finisher_username: auth.username.clone(),
+        finisher_team: selection_team(&pledge, &finisher_selection),
+        finisher_selection,
+        finisher_amount,
+        match_id: Some(game.match_id.clone()),
+        home_team: pledge.home_team.clone(),
+        away_team: pledge.away_team.clone(),
+        match_time: payload
+            .match_time
+            .or_else(|| parse_kickoff_utc(&game.date_iso, &game.time)),
+        league: game.league.clone(),
+        sport_type: game.sport_type.clone(),
+        total_pot: quote.total_pot,
+        status: "active".to_string(),
+        commission: None,
+        winner_id: None,
+        winner_username: None,
+        winning_selection: None,
+        odds: bet_odds,
+        created_at: now,
+        updated_at: now,
+        completed_at: None,
+    };
+
+    let bets: Collection<Bet> = state.db.collection("bets");
+    if let Err(e) = bets.insert_one(&bet).await {
+        escrow::unlock_for_bet(&state, &id, &bet_id.to_hex()).await?;
+        reopen_pledge(&state, pledge_oid, &bet_id.to_hex()).await?;
+        return Err(e.into());
+    }
+
+    println!(
+        "✅ Pledge {} matched - bet {} at {} (Total Pot: ₿{})",
+        id,
+        bet_id.to_hex(),
+        quote.odds,
+        bet.total_pot
+    );
+    Ok(Json(BetResponse::from(bet)))
+}
+
+/// Atomically move an open pledge to matched. Only one caller can win this;
+/// everyone else gets `PledgeAlreadyMatched`.
+async fn claim_pledge(
+    state: &AppState,
+    pledge_id: ObjectId,
+    finisher_id: &str,
+    bet_id: &str,
+) -> Result<Pledge> {
+    let collection: Collection<Pledge> = state.db.collection("pledges");
+
+    let claimed = collection
+        .find_one_and_update(
+            doc! {
+                "_id": pledge_id,
+                "$and": [
+                    // Pledges created before statuses existed count as open
+                    { "$or": [{ "status": PLEDGE_OPEN }, { "status": { "$exists": false } }] },
+                    { "$or": [{ "expires_at": null }, { "expires_at": { "$gt": Utc::now() } }] },
+                ],
+                "starter_id": { "$ne": finisher_id },
+            },
+            doc! { "$set": {
+                "status": PLEDGE_MATCHED,
+                "bet_id": bet_id,
+                "updated_at": Utc::now(),
+            }},
+        )
+        .return_document(ReturnDocument::After)
+        .await?;
+
+    if let Some(pledge) = claimed {
+        return Ok(pledge);
+    }
+
+    // Work out why, so the client gets a useful error
+    match collection.find_one(doc! { "_id": pledge_id }).await? {
+        None => Err(AppError::DocumentNotFound),
+        Some(pledge) if pledge.starter_id == finisher_id => Err(AppError::ValidationError(
+            "You cannot accept your own pledge".to_string(),
+        )),
+        Some(pledge) if pledge.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) => {
+            Err(AppError::ValidationError("This pledge has expired".to_string()))
+        }
+        Some(_) => Err(AppError::PledgeAlreadyMatched),
+    }
+}
+
+/// Undo `claim_pledge` when the bet could not be created.
+async fn reopen_pledge(state: &AppState, pledge_id: ObjectId, bet_id: &str) -> Result<()> {
+    let collection: Collection<Pledge> = state.db.collection("pledges");
+    collection
+        .update_one(
+            doc! { "_id": pledge_id, "status": PLEDGE_MATCHED, "bet_id": bet_id },
+            doc! {
+                "$set": { "status": PLEDGE_OPEN, "updated_at": Utc::now() },
+                "$unset": { "bet_id": "" },
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+fn selection_team(pledge: &Pledge, selection: &str) -> String {
+    match selection {
+        "home_team" => pledge.home_team.clone(),
+        "away_team" => pledge.away_team.clone(),
+        _ => "Draw".to_string(),
+    }
+}
+
 // Get pledge statistics for a specific match
 pub async fn get_pledge_stats(
     State(state): State<AppState>,
@@ -136,7 +417,7 @@ pub async fn get_pledge_stats(
     let pledges: Vec<Pledge> = cursor.try_collect().await?;
 
     let total_pledges = pledges.len() as i64;
-    let total_amount: f64 = pledges.iter().map(|p| p.amount).sum();
+    let total_amount: Money = pledges.iter().map(|p| p.amount).sum();
 
     let home_pledges = pledges
         .iter()
@@ -14,6 +14,7 @@ use serde_json::{json, Value as JsonValue};
 use uuid::Uuid;
 
 use crate::errors::{AppError, Result};
+use crate::middleware::auth::AuthUser;
 use crate::models::notification::FCMToken;
 use crate::models::posta::{
     Comment, CommentResponse, CreateCommentRequest, LikeRequest, Post, PostResponse, PostType,
@@ -734,6 +735,7 @@ pub async fn get_posts_by_user(
 pub async fn update_post_caption(
     State(state): State<AppState>,
     Path(post_id): Path<String>,
+    auth: AuthUser,
     axum::extract::Json(payload): axum::extract::Json<UpdateCaptionRequest>,
 ) -> Result<Json<serde_json::Value>> {
     let request_id = uuid::Uuid::new_v4();
@@ -751,6 +753,12 @@ pub async fn update_post_caption(
     };
 
     let filter = doc! { "_id": object_id };
+    let post = collection
+        .find_one(filter.clone())
+        .await?
+        .ok_or(AppError::PostNotFound)?;
+    auth.ensure_owner_or_moderator(&post.user_id)?;
+
     let update = doc! {
         "$set": {
             "caption": Some(payload.caption.clone()),
@@ -775,6 +783,7 @@ pub async fn update_post_caption(
 pub async fn delete_post(
     State(state): State<AppState>,
     Path(post_id): Path<String>,
+    auth: AuthUser,
 ) -> Result<Json<serde_json::Value>> {
     let request_id = uuid::Uuid::new_v4();
     log_info!(
@@ -796,6 +805,7 @@ pub async fn delete_post(
         Some(post) => post,
         None => return Err(AppError::PostNotFound),
     };
+    auth.ensure_owner_or_moderator(&post.user_id)?;
 
     if post.has_image() {
         let cloudinary_service = &state.cloudinary;
@@ -820,7 +830,10 @@ pub async fn delete_post(
 pub async fn delete_posts_by_user(
     State(state): State<AppState>,
     Path(user_id): Path<String>,
+    auth: AuthUser,
 ) -> Result<Json<serde_json::Value>> {
+    auth.ensure_owner_or_moderator(&user_id)?;
+
     let request_id = uuid::Uuid::new_v4();
     log_info!(
         "[{}] Starting delete_posts_by_user handler. User ID: {}",
@@ -1759,3 +1772,48 @@ pub async fn unlike_comment(
         None => Err(AppError::invalid_data("Comment not found after update")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use crate::test_support::{assert_owner_or_moderator_only, OWNER};
+
+    async fn seed_post(state: AppState) -> String {
+        let post = Post::new_text_post(OWNER.to_string(), OWNER.to_string(), "Match day".to_string());
+        let id = post._id.unwrap().to_hex();
+        let collection: Collection<Post> = state.db.collection("posts");
+        collection.insert_one(&post).await.unwrap();
+        id
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn update_post_caption_is_owner_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_post, |state, id, auth| async move {
+            let caption = axum::extract::Json(UpdateCaptionRequest {
+                caption: "Edited".to_string(),
+            });
+            update_post_caption(State(state), Path(id), auth, caption).await.into_response()
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn delete_post_is_owner_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_post, |state, id, auth| async move {
+            delete_post(State(state), Path(id), auth).await.into_response()
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn delete_posts_by_user_is_owner_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_post, |state, _, auth| async move {
+            delete_posts_by_user(State(state), Path(OWNER.to_string()), auth).await.into_response()
+        })
+        .await;
+    }
+}
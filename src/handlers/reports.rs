@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use chrono::{Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    Collection,
+};
+
+use crate::{
+    errors::{AppError, Result},
+    models::ledger::{CommissionDay, CommissionReport, CommissionReportQuery, HOUSE_ACCOUNT},
+    models::money::Money,
+    state::AppState,
+};
+
+const DEFAULT_REPORT_DAYS: i64 = 30;
+const MAX_REPORT_DAYS: i64 = 366;
+const EAT_OFFSET_SECS: i32 = 3 * 3600;
+
+// GET /api/reports/commission?from=YYYY-MM-DD&to=YYYY-MM-DD - House commission per day
+pub async fn get_commission_report(
+    State(state): State<AppState>,
+    Query(query): Query<CommissionReportQuery>,
+) -> Result<Json<CommissionReport>> {
+    println!("📊 Building commission report: {:?}", query);
+
+    let eat = FixedOffset::east_opt(EAT_OFFSET_SECS)
+        .ok_or_else(|| AppError::internal_server_error("Invalid EAT offset"))?;
+    let today = Utc::now().with_timezone(&eat).date_naive();
+    let to = query.to.unwrap_or(today);
+    let from = query.from.unwrap_or(to - Duration::days(DEFAULT_REPORT_DAYS - 1));
+    if from > to {
+        return Err(AppError::invalid_data("from must not be after to"));
+    }
+    if (to - from).num_days() >= MAX_REPORT_DAYS {
+        return Err(AppError::invalid_data(format!(
+            "A report can cover at most {} days",
+            MAX_REPORT_DAYS
+        )));
+    }
+
+    let start_of = |day: NaiveDate| {
+        day.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| eat.from_local_datetime(&midnight).single())
+            .map(|start| start.with_timezone(&Utc))
+            .ok_or_else(|| AppError::invalid_data(format!("Invalid date {}", day)))
+    };
+    let start = start_of(from)?;
+    let end = start_of(to + Duration::days(1))?;
+
+    // Fees live on the house account's legs of posted payout entries
+    let pipeline = vec![
+        doc! {
+            "$match": {
+                "status": "posted",
+                "postings.account_id": HOUSE_ACCOUNT,
+                "created_at": { "$gte": start, "$lt": end },
+            }
+        },
+        doc! { "$unwind": "$postings" },
+        doc! { "$match": { "postings.account_id": HOUSE_ACCOUNT } },
+        doc! {
+            "$group": {
+                "_id": {
+                    "$dateToString": {
+                        "format": "%Y-%m-%d",
+                        "date": "$created_at",
+                        "timezone": "+03:00",
+                    }
+                },
+                "commission": { "$sum": "$postings.amount" },
+                "bets": { "$sum": 1 },
+            }
+        },
+        doc! { "$sort": { "_id": 1 } },
+    ];
+
+    let collection: Collection<Document> = state.db.collection("ledger_entries");
+    let cursor = collection.aggregate(pipeline).await?;
+    let rows: Vec<Document> = cursor.try_collect().await?;
+
+    let days: Vec<CommissionDay> = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(CommissionDay {
+                date: row.get_str("_id").ok()?.to_string(),
+                commission: Money::from_cents(as_i64(row.get("commission"))?),
+                bets: as_i64(row.get("bets"))?,
+            })
+        })
+        .collect();
+    let total: Money = days.iter().map(|day| day.commission).sum();
+
+    println!("✅ Commission {} over {} days with fees", total, days.len());
+    Ok(Json(CommissionReport { from, to, total, days }))
+}
+
+fn as_i64(value: Option<&Bson>) -> Option<i64> {
+    match value? {
+        Bson::Int64(n) => Some(*n),
+        Bson::Int32(n) => Some(i64::from(*n)),
+        _ => None,
+    }
+}
@@ -12,6 +12,7 @@ use uuid::Uuid;
 
 use crate::{
     errors::{AppError, Result},
+    middleware::auth::AuthUser,
     models::sub_fixture::{
         BulkStatsRequest, CreateSubFixtureRequest, CreateSubFixtureVoteRequest, SubFixture,
         SubFixtureQuery, SubFixtureStats, SubFixtureVote, SubFixtureVoteResponse,
@@ -63,8 +64,12 @@ pub async fn get_sub_fixture_by_id(
 // ========== SUBMIT SUB-FIXTURE VOTE (WITH AUTO-CREATE) ==========
 pub async fn submit_sub_fixture_vote(
     State(state): State<AppState>,
-    Json(req): Json<CreateSubFixtureVoteRequest>,
+    auth: AuthUser,
+    Json(mut req): Json<CreateSubFixtureVoteRequest>,
 ) -> Result<Json<SubFixtureVoteResponse>> {
+    req.voter_id = auth.user_id;
+    req.username = auth.username;
+
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("📝 POST /api/votes/sub-fixture - Creating vote");
     println!(
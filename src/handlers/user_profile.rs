@@ -10,18 +10,21 @@ use futures_util::TryStreamExt;
 use validator::Validate;
 
 use crate::state::AppState;
-use crate::models::user_profile::{UserProfile, CreateUserProfile, UpdateBalanceRequest, UserQuery};
+use crate::models::user_profile::{UserProfile, CreateUserProfile, UserQuery};
+use crate::models::money::Money;
 use crate::errors::{AppError, Result};
+use crate::middleware::auth::AuthUser;
+use crate::services::ledger;
 
 #[derive(Debug, Deserialize)]
 pub struct SaveProfileRequest {
-    pub user_id: String,
+    #[serde(default)]
+    pub user_id: String, // Set from the access token
     pub username: String,
     pub phone: String,
     pub nickname: String,
     pub club_fan: String,
     pub country_fan: String,
-    pub balance: f64,
     pub number_of_bets: i32,
 }
 
@@ -120,8 +123,10 @@ pub async fn get_user_profile_by_phone(
 // Create or update user profile (UPSERT)
 pub async fn save_user_profile(
     State(state): State<AppState>,
-    Json(payload): Json<SaveProfileRequest>,
+    auth: AuthUser,
+    Json(mut payload): Json<SaveProfileRequest>,
 ) -> Result<Json<UserProfile>> {
+    payload.user_id = auth.user_id;
     println!("🎯 Saving user profile for: {}", payload.username);
 
     // Validate required fields
@@ -143,6 +148,9 @@ pub async fn save_user_profile(
     let now = Utc::now();
     let bson_now = BsonDateTime::from_chrono(now);
 
+    // Balance is a read-only projection of the wallet ledger
+    let balance = ledger::wallet_balance(&state, &payload.user_id).await?;
+
     let user_profile = UserProfile {
         id: existing_user.as_ref().and_then(|u| u.id.clone()).or(Some(ObjectId::new())),
         user_id: payload.user_id.clone(),
@@ -151,8 +159,16 @@ pub async fn save_user_profile(
         nickname: payload.nickname,
         club_fan: payload.club_fan,
         country_fan: payload.country_fan,
-        balance: payload.balance,
+        balance,
         number_of_bets: payload.number_of_bets,
+        gambling_limits: existing_user
+            .as_ref()
+            .map(|u| u.gambling_limits.clone())
+            .unwrap_or_default(),
+        kyc_status: existing_user
+            .as_ref()
+            .map(|u| u.kyc_status)
+            .unwrap_or_default(),
         created_at: existing_user.as_ref()
             .map(|u| u.created_at)
             .unwrap_or(bson_now),
@@ -182,48 +198,6 @@ pub async fn save_user_profile(
     Ok(Json(user_profile))
 }
 
-// Update user balance
-pub async fn update_user_balance(
-    State(state): State<AppState>,
-    Json(payload): Json<UpdateBalanceRequest>,
-) -> Result<Json<UserProfile>> {
-    println!("💰 Updating balance for user: {}", payload.user_id);
-
-    if payload.user_id.is_empty() {
-        return Err(AppError::invalid_data("User ID is required"));
-    }
-
-    let collection: Collection<UserProfile> = state.db.collection("user_profiles");
-
-    let filter = doc! { "user_id": &payload.user_id };
-
-    let update = doc! {
-        "$set": {
-            "balance": payload.balance,
-            "updated_at": BsonDateTime::from_chrono(Utc::now())
-        }
-    };
-
-    let update_result = collection.update_one(filter.clone(), update).await?;
-
-    if update_result.matched_count == 0 {
-        println!("❌ User not found: {}", payload.user_id);
-        return Err(AppError::DocumentNotFound);
-    }
-
-    // Fetch and return updated user
-    match collection.find_one(filter).await? {
-        Some(user) => {
-            println!("✅ Updated balance for {} to: {}", user.username, user.balance);
-            Ok(Json(user))
-        }
-        None => {
-            println!("❌ User not found after update: {}", payload.user_id);
-            Err(AppError::DocumentNotFound)
-        }
-    }
-}
-
 // Get user statistics
 pub async fn get_user_stats(
     State(state): State<AppState>,
@@ -238,12 +212,12 @@ pub async fn get_user_stats(
 
     // Calculate statistics
     let total_users = users.len() as i64;
-    let total_balance: f64 = users.iter().map(|u| u.balance).sum();
+    let total_balance: Money = users.iter().map(|u| u.balance).sum();
     let total_bets: i64 = users.iter().map(|u| u.number_of_bets as i64).sum();
 
     // Find top users by balance
     let mut sorted_users = users.clone();
-    sorted_users.sort_by(|a, b| b.balance.partial_cmp(&a.balance).unwrap());
+    sorted_users.sort_by_key(|u| std::cmp::Reverse(u.balance));
 
     let top_users: Vec<_> = sorted_users.iter()
         .take(10)
@@ -276,7 +250,7 @@ pub async fn get_user_stats(
         "total_users": total_users,
         "total_balance": total_balance,
         "total_bets": total_bets,
-        "average_balance": if total_users > 0 { total_balance / total_users as f64 } else { 0.0 },
+        "average_balance": if total_users > 0 { total_balance.mul_ratio(1, total_users) } else { Money::ZERO },
         "average_bets": if total_users > 0 { total_bets as f64 / total_users as f64 } else { 0.0 },
         "top_users": top_users,
         "by_club": club_stats,
@@ -320,8 +294,10 @@ pub async fn get_recent_users(
 // Create a new user profile
 pub async fn create_user_profile(
     State(state): State<AppState>,
-    Json(payload): Json<CreateUserProfile>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreateUserProfile>,
 ) -> Result<Json<UserProfile>> {
+    payload.user_id = auth.user_id;
     println!("🎯 Creating new user profile: {}", payload.username);
 
     // Validate the request
@@ -344,6 +320,9 @@ pub async fn create_user_profile(
         return Err(AppError::invalid_data("User with this ID or phone already exists"));
     }
 
+    // Balance is a read-only projection of the wallet ledger
+    let balance = ledger::wallet_balance(&state, &payload.user_id).await?;
+
     let now = Utc::now();
     let user_profile = UserProfile {
         id: Some(ObjectId::new()),
@@ -353,8 +332,10 @@ pub async fn create_user_profile(
         nickname: payload.nickname,
         club_fan: payload.club_fan,
         country_fan: payload.country_fan,
-        balance: payload.balance,
+        balance,
         number_of_bets: payload.number_of_bets,
+        gambling_limits: Default::default(),
+        kyc_status: Default::default(),
         created_at: BsonDateTime::from_chrono(now),
         updated_at: BsonDateTime::from_chrono(now),
     };
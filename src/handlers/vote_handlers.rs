@@ -14,14 +14,16 @@ use validator::Validate;
 
 use crate::{
     errors::{AppError, Result},
+    handlers::ws_handler::fixture_channel,
+    middleware::auth::AuthUser,
     models::game::Game,
     models::notification::FCMToken,
     models::vote::{
         parse_iso_timestamp_or_now, validate_selection, BulkVoteRequest, BulkVoteResponse, Comment,
         CommentQuery, CommentResponse, CommentStats, CreateComment, CreateLike, CreateVote,
-        FixtureCountsResponse, FixtureStats, Like, LikeResponse, LikeStats,
+        FixtureCountsResponse, FixturePresence, FixtureStats, Like, LikeResponse, LikeStats,
         MarkCommentsSeenRequest, ReplyData, TotalCountsResponse, UserVoteStatus, Vote, VoteQuery,
-        VoteResponse, VoteStats,
+        PresenceSample, VoteResponse, VoteStats,
     },
     state::AppState,
 };
@@ -171,8 +173,11 @@ pub async fn get_comments(
 
 pub async fn mark_comments_seen(
     State(state): State<AppState>,
-    Json(payload): Json<MarkCommentsSeenRequest>,
+    auth: AuthUser,
+    Json(mut payload): Json<MarkCommentsSeenRequest>,
 ) -> Result<Json<serde_json::Value>> {
+    payload.user_id = auth.user_id;
+
     println!(
         "👁️ Marking comments as seen for user: {} in fixture: {}",
         payload.user_id, payload.fixture_id
@@ -235,8 +240,12 @@ pub async fn mark_comments_seen(
 
 pub async fn create_vote(
     State(state): State<AppState>,
-    Json(payload): Json<CreateVote>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreateVote>,
 ) -> Result<Json<VoteResponse>> {
+    payload.voter_id = auth.user_id;
+    payload.username = auth.username;
+
     println!(
         "🗳️ Creating vote for user: {} ({})",
         payload.username, payload.voter_id
@@ -377,8 +386,6 @@ pub async fn create_vote(
     });
 
     // WEB SOCKET BROADCAST
-    let tx = state.get_or_create_broadcaster(&payload.fixture_id);
-
     let home_votes = vote_collection
         .count_documents(doc! {
             "fixtureId": &payload.fixture_id,
@@ -418,7 +425,10 @@ pub async fn create_vote(
     });
 
     if let Ok(message_json) = serde_json::to_string(&vote_update) {
-        let _ = tx.send(message_json);
+        state
+            .comment_broadcaster
+            .publish(&fixture_channel(&payload.fixture_id), message_json)
+            .await;
         println!(
             "📡 Broadcasted vote.update for fixture: {}",
             payload.fixture_id
@@ -463,8 +473,15 @@ pub async fn get_votes(
 
 pub async fn bulk_create_votes(
     State(state): State<AppState>,
-    Json(payload): Json<BulkVoteRequest>,
+    auth: AuthUser,
+    Json(mut payload): Json<BulkVoteRequest>,
 ) -> Result<Json<BulkVoteResponse>> {
+    // Every vote in the batch is cast by the caller
+    for vote in payload.votes.iter_mut() {
+        vote.voter_id = auth.user_id.clone();
+        vote.username = auth.username.clone();
+    }
+
     println!("📦 Creating bulk votes ({} votes)", payload.votes.len());
 
     let collection: Collection<Vote> = state.db.collection("votes");
@@ -835,6 +852,7 @@ pub async fn get_vote_counts_by_selection(
 pub async fn delete_vote(
     State(state): State<AppState>,
     Path(vote_id): Path<String>,
+    auth: AuthUser,
 ) -> Result<Json<VoteResponse>> {
     println!("🗑️ Deleting vote: {}", vote_id);
 
@@ -845,6 +863,11 @@ pub async fn delete_vote(
 
     let filter = doc! { "_id": object_id };
 
+    // Only the voter may take their vote back
+    if let Some(vote) = collection.find_one(filter.clone()).await? {
+        auth.ensure_is(&vote.voter_id)?;
+    }
+
     let delete_result = collection.delete_one(filter).await?;
 
     if delete_result.deleted_count == 0 {
@@ -869,8 +892,12 @@ pub async fn delete_vote(
 
 pub async fn create_like(
     State(state): State<AppState>,
-    Json(payload): Json<CreateLike>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreateLike>,
 ) -> Result<Json<LikeResponse>> {
+    payload.voter_id = auth.user_id;
+    payload.username = auth.username;
+
     println!(
         "👍 Creating like for user: {} ({})",
         payload.username, payload.voter_id
@@ -993,8 +1020,6 @@ pub async fn create_like(
         });
 
         // WEB SOCKET BROADCAST
-        let tx = state.get_or_create_broadcaster(&payload.fixture_id);
-
         let like_update = serde_json::json!({
             "type": "like",
             "payload": {
@@ -1007,7 +1032,10 @@ pub async fn create_like(
         });
 
         if let Ok(message_json) = serde_json::to_string(&like_update) {
-            let _ = tx.send(message_json);
+            state
+                .comment_broadcaster
+                .publish(&fixture_channel(&payload.fixture_id), message_json)
+                .await;
             println!("📡 Broadcasted like for fixture: {}", payload.fixture_id);
         }
     }
@@ -1094,6 +1122,7 @@ pub async fn get_user_like_for_fixture(
 pub async fn delete_like(
     State(state): State<AppState>,
     Path(like_id): Path<String>,
+    auth: AuthUser,
 ) -> Result<Json<LikeResponse>> {
     println!("🗑️ Deleting like: {}", like_id);
 
@@ -1108,6 +1137,7 @@ pub async fn delete_like(
         .find_one(filter.clone())
         .await?
         .ok_or_else(|| AppError::DocumentNotFound)?;
+    auth.ensure_is(&like.voter_id)?;
 
     let delete_result = collection.delete_one(filter).await?;
 
@@ -1136,8 +1166,12 @@ pub async fn delete_like(
 
 pub async fn create_comment(
     State(state): State<AppState>,
-    Json(payload): Json<CreateComment>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreateComment>,
 ) -> Result<Json<CommentResponse>> {
+    payload.voter_id = auth.user_id;
+    payload.username = auth.username;
+
     println!(
         "💬 Creating comment for user: {} ({})",
         payload.username, payload.voter_id
@@ -1294,8 +1328,6 @@ pub async fn create_comment(
     });
 
     // WEB SOCKET BROADCASTS
-    let tx = state.get_or_create_broadcaster(&payload.fixture_id);
-
     let chat_message = serde_json::json!({
         "type": "chat.message",
         "payload": {
@@ -1317,7 +1349,10 @@ pub async fn create_comment(
     });
 
     if let Ok(message_json) = serde_json::to_string(&chat_message) {
-        let _ = tx.send(message_json);
+        state
+            .comment_broadcaster
+            .publish(&fixture_channel(&payload.fixture_id), message_json)
+            .await;
         println!(
             "📡 Broadcasted chat.message for fixture: {}",
             payload.fixture_id
@@ -1338,7 +1373,10 @@ pub async fn create_comment(
     });
 
     if let Ok(message_json) = serde_json::to_string(&fixture_comment) {
-        let _ = tx.send(message_json);
+        state
+            .comment_broadcaster
+            .publish(&fixture_channel(&payload.fixture_id), message_json)
+            .await;
         println!(
             "📡 Broadcasted fixture.comment for fixture: {}",
             payload.fixture_id
@@ -1362,7 +1400,10 @@ pub async fn create_comment(
     });
 
     if let Ok(message_json) = serde_json::to_string(&comment_count_update) {
-        let _ = tx.send(message_json);
+        state
+            .comment_broadcaster
+            .publish(&fixture_channel(&payload.fixture_id), message_json)
+            .await;
         println!(
             "📡 Broadcasted comment.count for fixture: {} (total: {})",
             payload.fixture_id, total_comments
@@ -1472,6 +1513,7 @@ pub async fn get_user_comments(
 pub async fn delete_comment(
     State(state): State<AppState>,
     Path(comment_id): Path<String>,
+    auth: AuthUser,
 ) -> Result<Json<CommentResponse>> {
     println!("🗑️ Deleting comment: {}", comment_id);
 
@@ -1482,6 +1524,10 @@ pub async fn delete_comment(
 
     let filter = doc! { "_id": object_id };
 
+    if let Some(comment) = collection.find_one(filter.clone()).await? {
+        auth.ensure_owner_or_moderator(&comment.voter_id)?;
+    }
+
     let delete_result = collection.delete_one(filter).await?;
 
     if delete_result.deleted_count == 0 {
@@ -1544,6 +1590,54 @@ pub async fn like_comment(
 
 // ========== STATISTICS HANDLERS ==========
 
+// ========== PRESENCE ==========
+
+/// Most recently active watchers looked up to build the sample.
+const PRESENCE_SAMPLE_SCAN: usize = 200;
+const PRESENCE_SAMPLE_PER_SELECTION: usize = 10;
+
+pub async fn get_fixture_presence(
+    State(state): State<AppState>,
+    Path(fixture_id): Path<String>,
+) -> Result<Json<FixturePresence>> {
+    let online = state.presence.online_count(&fixture_id).await;
+    let watchers = state
+        .presence
+        .online_users(&fixture_id, PRESENCE_SAMPLE_SCAN)
+        .await;
+
+    let watcher_ids: Vec<&str> = watchers.iter().map(|u| u.user_id.as_str()).collect();
+    let collection: Collection<Vote> = state.db.collection("votes");
+    let votes: Vec<Vote> = collection
+        .find(doc! { "fixtureId": &fixture_id, "voterId": { "$in": watcher_ids } })
+        .await?
+        .try_collect()
+        .await?;
+    let selections: std::collections::HashMap<String, String> = votes
+        .into_iter()
+        .map(|v| (v.voter_id, v.selection))
+        .collect();
+
+    let mut by_selection = PresenceSample::default();
+    for user in watchers {
+        let group = match selections.get(&user.user_id).map(String::as_str) {
+            Some("home_team") => &mut by_selection.home_team,
+            Some("draw") => &mut by_selection.draw,
+            Some("away_team") => &mut by_selection.away_team,
+            _ => &mut by_selection.no_vote,
+        };
+        if group.len() < PRESENCE_SAMPLE_PER_SELECTION {
+            group.push(user);
+        }
+    }
+
+    Ok(Json(FixturePresence {
+        fixture_id,
+        online,
+        by_selection,
+    }))
+}
+
 pub async fn get_vote_stats(
     State(state): State<AppState>,
     Path(fixture_id): Path<String>,
@@ -2300,3 +2394,45 @@ pub async fn get_fixture_engagement_summary(
     );
     Ok(Json(response))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use crate::test_support::{assert_owner_or_moderator_only, OWNER};
+
+    async fn seed_comment(state: AppState) -> String {
+        let comment = Comment {
+            id: None,
+            message_id: None,
+            voter_id: OWNER.to_string(),
+            username: OWNER.to_string(),
+            fixture_id: "fixture-1".to_string(),
+            selection: "home_team".to_string(),
+            comment: "Come on!".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            comment_timestamp: BsonDateTime::now(),
+            created_at: Some(BsonDateTime::now()),
+            likes: None,
+            replies: None,
+            seen_by: vec![],
+            image_url: None,
+            video_url: None,
+            is_image: false,
+            is_video: false,
+            reply_to: None,
+        };
+        let collection: Collection<Comment> = state.db.collection("room");
+        let inserted = collection.insert_one(&comment).await.unwrap();
+        inserted.inserted_id.as_object_id().unwrap().to_hex()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn delete_comment_is_owner_or_moderator_only() {
+        assert_owner_or_moderator_only(seed_comment, |state, id, auth| async move {
+            delete_comment(State(state), Path(id), auth).await.into_response()
+        })
+        .await;
+    }
+}
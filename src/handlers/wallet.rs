@@ -0,0 +1,128 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use futures_util::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions, Collection};
+
+use crate::{
+    errors::Result,
+    middleware::auth::AuthUser,
+    models::gambling::{
+        CoolOffRequest, GamblingLimits, LimitsOverview, SelfExclusionRequest, SetLimitRequest,
+    },
+    models::ledger::{LedgerAccount, LedgerEntry, LedgerQuery, LedgerStatement, StatementLine},
+    services::{ledger, responsible_gambling},
+    state::AppState,
+};
+
+const DEFAULT_STATEMENT_SIZE: i64 = 50;
+const MAX_STATEMENT_SIZE: i64 = 200;
+
+// GET /api/wallet/:user_id/ledger - Wallet statement (newest first)
+pub async fn get_wallet_ledger(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<LedgerStatement>> {
+    println!("📒 Getting ledger statement for user: {}", user_id);
+
+    // Statements are private to their owner
+    auth.ensure_is(&user_id)?;
+
+    let account_id = LedgerAccount::wallet_id(&user_id);
+    let collection: Collection<LedgerEntry> = state.db.collection("ledger_entries");
+
+    let mut filter = doc! {
+        "postings.account_id": &account_id,
+        "status": "posted",
+    };
+    if let Some(kind) = &query.kind {
+        filter.insert("kind", kind);
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_STATEMENT_SIZE)
+        .clamp(1, MAX_STATEMENT_SIZE);
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .skip(query.skip.unwrap_or(0))
+        .limit(limit)
+        .build();
+
+    let cursor = collection.find(filter).with_options(options).await?;
+    let entries: Vec<LedgerEntry> = cursor.try_collect().await?;
+
+    let lines: Vec<StatementLine> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let leg = entry
+                .postings
+                .iter()
+                .find(|p| p.account_id == account_id)?
+                .clone();
+            Some(StatementLine {
+                entry_id: entry.entry_id,
+                kind: entry.kind,
+                reference: entry.reference,
+                description: entry.description,
+                amount: leg.amount,
+                balance_after: leg.balance_after,
+                created_at: entry.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    let balance = ledger::wallet_balance(&state, &user_id).await?;
+
+    println!("✅ Fetched {} ledger lines for {}", lines.len(), user_id);
+    Ok(Json(LedgerStatement {
+        user_id,
+        account_id,
+        balance,
+        count: lines.len(),
+        entries: lines,
+    }))
+}
+
+// GET /api/wallet/limits - Usage against the caller's gambling limits
+pub async fn get_limits(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<LimitsOverview>> {
+    println!("🛡️ Getting gambling limits for user: {}", auth.user_id);
+    let overview = responsible_gambling::overview(&state, &auth.user_id).await?;
+    Ok(Json(overview))
+}
+
+// PUT /api/wallet/limits - Set, raise or remove one limit
+pub async fn set_limit(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<SetLimitRequest>,
+) -> Result<Json<GamblingLimits>> {
+    let limits = responsible_gambling::set_limit(&state, &auth.user_id, &payload).await?;
+    Ok(Json(limits))
+}
+
+// POST /api/wallet/cool-off - Pause deposits and betting for a number of hours
+pub async fn start_cool_off(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<CoolOffRequest>,
+) -> Result<Json<GamblingLimits>> {
+    let limits = responsible_gambling::cool_off(&state, &auth.user_id, payload.hours).await?;
+    Ok(Json(limits))
+}
+
+// POST /api/wallet/self-exclusion - Exclude oneself for a number of months
+pub async fn self_exclude(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<SelfExclusionRequest>,
+) -> Result<Json<GamblingLimits>> {
+    let limits = responsible_gambling::self_exclude(&state, &auth.user_id, payload.months).await?;
+    Ok(Json(limits))
+}
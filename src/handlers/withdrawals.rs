@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Collection,
+};
+
+use crate::{
+    errors::{AppError, Result},
+    middleware::auth::AuthUser,
+    models::withdrawal::{CreateWithdrawalRequest, Withdrawal, WithdrawalQuery, WithdrawalResponse},
+    services::{kyc, withdrawal},
+    state::AppState,
+};
+
+// POST /api/wallet/withdrawals - Debit the wallet and send the money via B2C
+pub async fn create_withdrawal(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(mut payload): Json<CreateWithdrawalRequest>,
+) -> Result<Json<WithdrawalResponse>> {
+    // Only ever paid out to the account's own verified phone
+    payload.user_id = auth.user_id;
+    payload.phone_number = auth.phone;
+    println!(
+        "💸 Withdrawal requested by {}: Ksh {} to {}",
+        payload.user_id, payload.amount, payload.phone_number
+    );
+
+    kyc::ensure_verified(&state, &payload.user_id).await?;
+    let withdrawal = withdrawal::request_withdrawal(&state, payload).await?;
+
+    println!("✅ Withdrawal {:?} is {}", withdrawal.id, withdrawal.status);
+    Ok(Json(WithdrawalResponse::from(withdrawal)))
+}
+
+// GET /api/wallet/withdrawals?status=... - The caller's withdrawal history
+pub async fn get_withdrawals(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<WithdrawalQuery>,
+) -> Result<Json<Vec<WithdrawalResponse>>> {
+    println!("🔍 Getting withdrawals...");
+
+    let user_id = auth.user_id;
+
+    let collection: Collection<Withdrawal> = state.db.collection("withdrawals");
+
+    let mut filter = doc! { "user_id": &user_id };
+    if let Some(status) = &query.status {
+        filter.insert("status", status);
+    }
+
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+    let cursor = collection.find(filter).with_options(options).await?;
+    let withdrawals: Vec<Withdrawal> = cursor.try_collect().await?;
+
+    let responses: Vec<WithdrawalResponse> = withdrawals
+        .into_iter()
+        .map(WithdrawalResponse::from)
+        .collect();
+
+    println!("✅ Fetched {} withdrawals for {}", responses.len(), user_id);
+    Ok(Json(responses))
+}
+
+// GET /api/wallet/withdrawals/:id - Single withdrawal with its status history
+pub async fn get_withdrawal_by_id(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<WithdrawalResponse>> {
+    println!("🔍 Getting withdrawal by ID: {}", id);
+
+    let collection: Collection<Withdrawal> = state.db.collection("withdrawals");
+
+    let withdrawal = collection
+        .find_one(doc! { "_id": ObjectId::parse_str(&id)? })
+        .await?
+        .ok_or(AppError::DocumentNotFound)?;
+    auth.ensure_is(&withdrawal.user_id)?;
+
+    Ok(Json(WithdrawalResponse::from(withdrawal)))
+}
@@ -1,4 +1,5 @@
 use crate::models::game::Game;
+use crate::models::money::Money;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
@@ -11,24 +12,32 @@ use chrono::Utc;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc, Mutex,
+};
+use tokio::task::JoinHandle;
 use tracing;
+use uuid::Uuid;
 
+use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
 use crate::models::vote::{Comment, ReplyData};
+use crate::services::broadcaster::{Broadcaster, Frame, Replay};
+use crate::services::rate_limit::COMMENTS;
 use crate::state::AppState;
 
+type WsSender = Arc<Mutex<futures_util::stream::SplitSink<WebSocket, Message>>>;
+
 // ========== QUERY PARAMS ==========
 #[derive(Debug, Deserialize)]
 pub struct WsQuery {
     #[serde(rename = "fixtureId")]
     pub fixture_id: String,
-
-    #[serde(rename = "userId")]
-    pub user_id: String,
-
-    #[serde(rename = "username")]
-    pub username: Option<String>,
+    // Who is connecting comes from the access token; old clients' userId
+    // and username params are ignored
 }
 
 // ========== WEB SOCKET MESSAGE TYPES ==========
@@ -65,6 +74,11 @@ pub enum WSMessage {
         payload: PresencePayload,
         timestamp: String,
     },
+    #[serde(rename = "presence.count")]
+    PresenceCount {
+        payload: PresenceCountPayload,
+        timestamp: String,
+    },
     #[serde(rename = "vote.update")]
     VoteUpdate {
         payload: VoteUpdatePayload,
@@ -111,11 +125,37 @@ pub enum WSMessage {
         payload: StatusPayload,
         timestamp: String,
     },
+    #[serde(rename = "bet.settled")]
+    BetSettled {
+        payload: BetSettledPayload,
+        timestamp: String,
+    },
     #[serde(rename = "pong")]
     Pong { timestamp: String },
     #[serde(rename = "connected")]
     Connected {
         fixture_id: String,
+        seq: u64, // Last frame published before this socket subscribed
+        timestamp: String,
+    },
+    /// Missed frames are no longer buffered: reload state, then carry on from `seq`
+    #[serde(rename = "resync")]
+    Resync {
+        fixture_id: String,
+        seq: u64,
+        timestamp: String,
+    },
+    /// Acknowledges an `unsubscribe` on a multiplexed socket
+    #[serde(rename = "unsubscribed")]
+    Unsubscribed {
+        fixture_id: String,
+        timestamp: String,
+    },
+    /// A client message on a multiplexed socket that was refused
+    #[serde(rename = "error")]
+    Error {
+        fixture_id: Option<String>,
+        message: String,
         timestamp: String,
     },
 }
@@ -207,6 +247,12 @@ pub struct PresencePayload {
     pub fixture_id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PresenceCountPayload {
+    pub fixture_id: String,
+    pub online: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VoteUpdatePayload {
     pub fixture_id: String,
@@ -275,15 +321,35 @@ pub struct StatusPayload {
     pub time_elapsed: i32,
 }
 
+// ========== BET PAYLOADS ==========
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BetSettledPayload {
+    pub fixture_id: String,
+    pub bet_id: String,
+    pub pledge_id: String,
+    pub status: String,            // "completed" or "void"
+    pub winning_selection: String, // "home_win", "away_win", "draw"
+    pub winner_id: Option<String>,
+    pub winner_username: Option<String>,
+    pub payout: Money,
+    pub commission: Money,
+    pub home_score: i32,
+    pub away_score: i32,
+}
+
 // ========== UPGRADE HANDLER ==========
 pub async fn ws_comments_handler(
     ws: WebSocketUpgrade,
+    auth: AuthUser,
     Query(params): Query<WsQuery>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let fixture_id = params.fixture_id.clone();
-    let user_id = params.user_id.clone();
-    let username = params.username.unwrap_or_else(|| "Anonymous".to_string());
+) -> Result<impl IntoResponse, AppError> {
+    let fixture_id = params.fixture_id;
+    let user_id = auth.user_id;
+    let username = auth.username;
+    if !is_valid_fixture_id(&fixture_id) {
+        return Err(AppError::invalid_data("Invalid fixtureId"));
+    }
 
     tracing::info!(
         "🔌 WS upgrade request for fixture: {}, user: {}",
@@ -291,7 +357,90 @@ pub async fn ws_comments_handler(
         user_id
     );
 
-    ws.on_upgrade(move |socket| handle_socket(socket, fixture_id, user_id, username, state))
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, fixture_id, user_id, username, state)))
+}
+
+// ========== MULTIPLEXED UPGRADE HANDLER ==========
+/// One authenticated socket for many fixtures. The client sends
+/// `{"type": "subscribe", "fixtureId": "..."}` (or `fixtureIds`, and an
+/// optional `lastSeq` to resume) and `unsubscribe` to leave; frames carry
+/// the `fixture_id` they belong to. The caller's personal channel is
+/// subscribed from the start and its frames carry `"channel": "user"`.
+pub async fn ws_multiplex_handler(
+    ws: WebSocketUpgrade,
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    tracing::info!("🔌 Multiplexed WS upgrade request for user: {}", auth.user_id);
+
+    ws.on_upgrade(move |socket| handle_multiplex_socket(socket, auth, state))
+}
+
+// ========== CHANNELS ==========
+/// Fixtures a single multiplexed socket may follow at once.
+const MAX_SUBSCRIPTIONS: usize = 50;
+
+/// Broadcaster channel for a fixture's frames.
+pub fn fixture_channel(fixture_id: &str) -> String {
+    format!("fixture:{}", fixture_id)
+}
+
+/// Broadcaster channel carrying only a fixture's `match.*` frames, so the
+/// SSE stream's replay buffer isn't pushed out by chat.
+pub fn match_channel(fixture_id: &str) -> String {
+    format!("match:{}", fixture_id)
+}
+
+/// Broadcaster channel for frames meant for one user, whatever the fixture.
+pub fn user_channel(user_id: &str) -> String {
+    format!("user:{}", user_id)
+}
+
+/// Fixture ids from clients can't contain the channel separator.
+fn is_valid_fixture_id(fixture_id: &str) -> bool {
+    !fixture_id.is_empty() && !fixture_id.contains(':')
+}
+
+/// A broadcaster channel a socket listens on.
+#[derive(Debug, Clone)]
+enum Subscription {
+    Fixture(String),
+    User(String),
+}
+
+impl Subscription {
+    fn channel(&self) -> String {
+        match self {
+            Subscription::Fixture(fixture_id) => fixture_channel(fixture_id),
+            Subscription::User(user_id) => user_channel(user_id),
+        }
+    }
+
+    /// Put the channel on the frame's envelope so a client following
+    /// several can route it.
+    fn label(&self, message: String) -> String {
+        let Ok(Value::Object(mut object)) = serde_json::from_str::<Value>(&message) else {
+            return message;
+        };
+        match self {
+            Subscription::Fixture(fixture_id) => {
+                object
+                    .entry("fixture_id")
+                    .or_insert_with(|| Value::from(fixture_id.as_str()));
+            }
+            Subscription::User(_) => {
+                object.insert("channel".to_string(), Value::from("user"));
+            }
+        }
+        Value::Object(object).to_string()
+    }
+}
+
+/// A subscription on a multiplexed socket: its forwarding task and where
+/// to send `resume` requests for it.
+struct ActiveSubscription {
+    resume: mpsc::Sender<u64>,
+    task: JoinHandle<()>,
 }
 
 // ========== PER-CONNECTION LOGIC ==========
@@ -302,8 +451,8 @@ async fn handle_socket(
     username: String,
     state: AppState,
 ) {
-    let tx = state.get_or_create_broadcaster(&fixture_id);
-    let mut rx = tx.subscribe();
+    let subscription = Subscription::Fixture(fixture_id.clone());
+    let (rx, connected_seq) = state.comment_broadcaster.subscribe(&subscription.channel());
 
     let (sender, mut receiver) = socket.split();
     let sender = Arc::new(Mutex::new(sender));
@@ -313,6 +462,7 @@ async fn handle_socket(
     let welcome = serde_json::json!({
         "type": "connected",
         "fixture_id": fixture_id,
+        "seq": connected_seq,
         "timestamp": Utc::now().to_rfc3339(),
     });
 
@@ -330,20 +480,11 @@ async fn handle_socket(
     // Send current match state
     send_current_match_state(&state, &fixture_id, &sender).await;
 
-    // Broadcast user online presence
-    let presence = serde_json::json!({
-        "type": "presence",
-        "payload": {
-            "user_id": user_id,
-            "username": username,
-            "status": "online",
-            "fixture_id": fixture_id,
-        },
-        "timestamp": Utc::now().to_rfc3339(),
-    });
-
-    if let Ok(presence_json) = serde_json::to_string(&presence) {
-        let _ = tx.send(presence_json);
+    // Broadcast user online presence, once per user however many sockets
+    let connection_id = Uuid::new_v4().to_string();
+    state.presence.connect(&connection_id, &user_id, &username);
+    if state.presence.join(&connection_id, &fixture_id).await {
+        publish_presence(&state, &fixture_id, &user_id, &username, "online").await;
     }
 
     tracing::info!(
@@ -352,46 +493,47 @@ async fn handle_socket(
         fixture_id
     );
 
-    let fixture_id_for_send = fixture_id.clone();
     let fixture_id_for_recv = fixture_id.clone();
     let user_id_for_recv = user_id.clone();
     let username_for_recv = username.clone();
+    let connection_id_for_recv = connection_id.clone();
+
+    // `resume` requests from the client, by the last seq it has
+    let (resume_tx, resume_rx) = mpsc::channel::<u64>(8);
 
     // Task 1: Forward broadcast messages to this client
-    let mut send_task = tokio::spawn(async move {
-        loop {
-            match rx.recv().await {
-                Ok(msg) => {
-                    let mut sender_guard = sender.lock().await;
-                    if sender_guard.send(Message::Text(msg)).await.is_err() {
-                        break;
-                    }
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!("WS client lagged, skipped {} messages", n);
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
-            }
-        }
-    });
+    let mut send_task = tokio::spawn(forward_channel(
+        state.comment_broadcaster.clone(),
+        subscription,
+        rx,
+        connected_seq,
+        resume_rx,
+        sender,
+    ));
 
     // Task 2: Handle incoming messages
     let state_clone = state.clone();
-    let tx_clone = tx.clone();
 
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
+            for fixture_id in state_clone.presence.heartbeat(&connection_id_for_recv).await {
+                publish_presence(&state_clone, &fixture_id, &user_id_for_recv, &username_for_recv, "online")
+                    .await;
+            }
             match msg {
                 Message::Text(text) => {
-                    handle_incoming_message(
+                    let reply = handle_incoming_message(
                         text,
                         &state_clone,
                         &fixture_id_for_recv,
                         &user_id_for_recv,
                         &username_for_recv,
-                        &tx_clone,
+                        &resume_tx,
                     )
                     .await;
+                    if let Some(reply) = reply {
+                        send_text(&sender_clone, reply).await;
+                    }
                 }
                 Message::Close(_) => break,
                 Message::Ping(_) => {
@@ -414,60 +556,345 @@ async fn handle_socket(
         _ = &mut recv_task => send_task.abort(),
     }
 
-    // Broadcast user offline presence
-    let offline_presence = serde_json::json!({
-        "type": "presence",
-        "payload": {
-            "user_id": user_id,
-            "username": username,
-            "status": "offline",
-            "fixture_id": fixture_id_for_send,
-        },
+    // Broadcast user offline presence if this was their last socket
+    for fixture_id in state.presence.disconnect(&connection_id).await {
+        publish_presence(&state, &fixture_id, &user_id, &username, "offline").await;
+    }
+
+    tracing::info!("🔌 WS disconnected for fixture: {}", fixture_id);
+}
+
+// ========== MULTIPLEXED CONNECTION LOGIC ==========
+async fn handle_multiplex_socket(socket: WebSocket, auth: AuthUser, state: AppState) {
+    let (sender, mut receiver) = socket.split();
+    let sender: WsSender = Arc::new(Mutex::new(sender));
+
+    let Some(personal) =
+        start_subscription(&state, Subscription::User(auth.user_id.clone()), &sender, None).await
+    else {
+        return;
+    };
+    let mut fixtures: HashMap<String, ActiveSubscription> = HashMap::new();
+
+    let connection_id = Uuid::new_v4().to_string();
+    state
+        .presence
+        .connect(&connection_id, &auth.user_id, &auth.username);
+
+    tracing::info!("✅ Multiplexed WS connected: user {}", auth.user_id);
+
+    while let Some(Ok(msg)) = receiver.next().await {
+        for fixture_id in state.presence.heartbeat(&connection_id).await {
+            publish_presence(&state, &fixture_id, &auth.user_id, &auth.username, "online").await;
+        }
+        let text = match msg {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            Message::Ping(_) => {
+                send_pong(&sender).await;
+                continue;
+            }
+            _ => continue,
+        };
+        let Ok(json_msg) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+        let message_type = json_msg.get("type").and_then(|t| t.as_str());
+        let fixture_ids = requested_fixture_ids(&json_msg);
+        let last_seq = json_msg.get("lastSeq").and_then(|v| v.as_u64());
+
+        match message_type {
+            // ========== SUBSCRIBE ==========
+            Some("subscribe") => {
+                for fixture_id in fixture_ids {
+                    if let Some(active) = fixtures.get(&fixture_id) {
+                        // Already following it: treat a seq as a resume
+                        if let Some(last_seq) = last_seq {
+                            let _ = active.resume.send(last_seq).await;
+                        }
+                        continue;
+                    }
+                    if !is_valid_fixture_id(&fixture_id) {
+                        send_error(&sender, Some(&fixture_id), "Invalid fixtureId").await;
+                        continue;
+                    }
+                    if fixtures.len() >= MAX_SUBSCRIPTIONS {
+                        let message =
+                            format!("At most {} fixtures per connection", MAX_SUBSCRIPTIONS);
+                        send_error(&sender, Some(&fixture_id), &message).await;
+                        continue;
+                    }
+
+                    let subscription = Subscription::Fixture(fixture_id.clone());
+                    let Some(active) =
+                        start_subscription(&state, subscription, &sender, last_seq).await
+                    else {
+                        break;
+                    };
+                    fixtures.insert(fixture_id.clone(), active);
+                    if state.presence.join(&connection_id, &fixture_id).await {
+                        publish_presence(&state, &fixture_id, &auth.user_id, &auth.username, "online")
+                            .await;
+                    }
+                    tracing::info!("➕ User {} subscribed to fixture {}", auth.user_id, fixture_id);
+                }
+            }
+
+            // ========== UNSUBSCRIBE ==========
+            Some("unsubscribe") => {
+                for fixture_id in fixture_ids {
+                    let Some(active) = fixtures.remove(&fixture_id) else {
+                        continue;
+                    };
+                    active.task.abort();
+                    if state.presence.leave(&connection_id, &fixture_id).await {
+                        publish_presence(&state, &fixture_id, &auth.user_id, &auth.username, "offline")
+                            .await;
+                    }
+
+                    let unsubscribed = serde_json::json!({
+                        "type": "unsubscribed",
+                        "fixture_id": fixture_id,
+                        "timestamp": Utc::now().to_rfc3339(),
+                    });
+                    send_text(&sender, unsubscribed.to_string()).await;
+                    tracing::info!(
+                        "➖ User {} unsubscribed from fixture {}",
+                        auth.user_id,
+                        fixture_id
+                    );
+                }
+            }
+
+            // ========== PING ==========
+            // Only this socket wants the pong
+            Some("ping") => send_pong(&sender).await,
+
+            // ========== HEARTBEAT ==========
+            // Already counted above
+            Some("heartbeat") => {}
+
+            // ========== RESUME PERSONAL CHANNEL ==========
+            Some("resume") if json_msg.get("channel").and_then(|c| c.as_str()) == Some("user") => {
+                let _ = personal.resume.send(last_seq.unwrap_or(0)).await;
+            }
+
+            // ========== FIXTURE MESSAGES ==========
+            // Same messages as the single-fixture socket, routed by fixtureId
+            _ => {
+                let Some(fixture_id) = fixture_ids.into_iter().next() else {
+                    tracing::debug!("Multiplexed message without fixtureId: {:?}", message_type);
+                    continue;
+                };
+                let Some(active) = fixtures.get(&fixture_id) else {
+                    send_error(&sender, Some(&fixture_id), "Not subscribed to this fixture").await;
+                    continue;
+                };
+                let reply = handle_incoming_message(
+                    text,
+                    &state,
+                    &fixture_id,
+                    &auth.user_id,
+                    &auth.username,
+                    &active.resume,
+                )
+                .await;
+                if let Some(reply) = reply {
+                    send_text(&sender, reply).await;
+                }
+            }
+        }
+    }
+
+    personal.task.abort();
+    for active in fixtures.into_values() {
+        active.task.abort();
+    }
+    for fixture_id in state.presence.disconnect(&connection_id).await {
+        publish_presence(&state, &fixture_id, &auth.user_id, &auth.username, "offline").await;
+    }
+
+    tracing::info!("🔌 Multiplexed WS disconnected for user: {}", auth.user_id);
+}
+
+/// Subscribe, greet with the channel's current seq (and match state for a
+/// fixture), then start forwarding. None if the socket is already gone.
+async fn start_subscription(
+    state: &AppState,
+    subscription: Subscription,
+    sender: &WsSender,
+    last_seq: Option<u64>,
+) -> Option<ActiveSubscription> {
+    let (rx, connected_seq) = state.comment_broadcaster.subscribe(&subscription.channel());
+
+    let welcome = serde_json::json!({
+        "type": "connected",
+        "seq": connected_seq,
         "timestamp": Utc::now().to_rfc3339(),
     });
+    if !send_text(sender, subscription.label(welcome.to_string())).await {
+        return None;
+    }
+    if let Subscription::Fixture(fixture_id) = &subscription {
+        send_current_match_state(state, fixture_id, sender).await;
+    }
 
-    if let Ok(offline_json) = serde_json::to_string(&offline_presence) {
-        let _ = tx.send(offline_json);
+    let (resume_tx, resume_rx) = mpsc::channel::<u64>(8);
+    if let Some(last_seq) = last_seq {
+        let _ = resume_tx.try_send(last_seq);
     }
+    let task = tokio::spawn(forward_channel(
+        state.comment_broadcaster.clone(),
+        subscription,
+        rx,
+        connected_seq,
+        resume_rx,
+        sender.clone(),
+    ));
+
+    Some(ActiveSubscription {
+        resume: resume_tx,
+        task,
+    })
+}
 
-    tracing::info!("🔌 WS disconnected for fixture: {}", fixture_id);
+/// `fixtureId` or `fixtureIds`, at the top level or inside `payload`.
+fn requested_fixture_ids(json_msg: &Value) -> Vec<String> {
+    for scope in [Some(json_msg), json_msg.get("payload")].into_iter().flatten() {
+        if let Some(fixture_id) = scope.get("fixtureId").and_then(|v| v.as_str()) {
+            return vec![fixture_id.to_string()];
+        }
+        if let Some(fixture_ids) = scope.get("fixtureIds").and_then(|v| v.as_array()) {
+            return fixture_ids
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect();
+        }
+    }
+    Vec::new()
 }
 
+// ========== FORWARD ONE CHANNEL ==========
+/// Forward a channel's frames to the socket in seq order until either side
+/// closes. Falling behind or a `resume` replays from the channel's buffer.
+async fn forward_channel(
+    broadcaster: Arc<dyn Broadcaster>,
+    subscription: Subscription,
+    mut rx: broadcast::Receiver<Frame>,
+    connected_seq: u64,
+    mut resume_rx: mpsc::Receiver<u64>,
+    sender: WsSender,
+) {
+    let channel = subscription.channel();
+    let mut last_sent = connected_seq;
+    loop {
+        let replay_after = tokio::select! {
+            frame = rx.recv() => match frame {
+                Ok(frame) => {
+                    // Already sent by a replay
+                    if frame.seq <= last_sent {
+                        continue;
+                    }
+                    last_sent = frame.seq;
+                    if !send_text(&sender, subscription.label(frame.message)).await {
+                        return;
+                    }
+                    continue;
+                }
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("WS client lagged by {} messages, replaying", n);
+                    last_sent
+                }
+                Err(RecvError::Closed) => return,
+            },
+            Some(last_seq) = resume_rx.recv() => last_seq,
+        };
+
+        match broadcaster.replay(&channel, replay_after) {
+            Replay::Frames(frames) => {
+                for frame in frames {
+                    // Frames this socket got live aren't sent twice
+                    if frame.seq > connected_seq && frame.seq <= last_sent {
+                        continue;
+                    }
+                    last_sent = last_sent.max(frame.seq);
+                    if !send_text(&sender, subscription.label(frame.message)).await {
+                        return;
+                    }
+                }
+            }
+            Replay::Resync { seq } => {
+                tracing::info!(
+                    "🔄 Gap after seq {} on channel {} too large, asking for resync",
+                    replay_after,
+                    channel
+                );
+                last_sent = last_sent.max(seq);
+                let resync = serde_json::json!({
+                    "type": "resync",
+                    "seq": seq,
+                    "timestamp": Utc::now().to_rfc3339(),
+                });
+                if !send_text(&sender, subscription.label(resync.to_string())).await {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Handle a client frame for `fixture_id`. Returns a frame meant for this
+/// socket alone, if any.
 async fn handle_incoming_message(
     text: String,
     state: &AppState,
     fixture_id: &str,
     user_id: &str,
     username: &str,
-    broadcaster: &tokio::sync::broadcast::Sender<String>,
-) {
+    resume: &mpsc::Sender<u64>,
+) -> Option<String> {
+    let broadcaster = &state.comment_broadcaster;
+    let channel = fixture_channel(fixture_id);
     if let Ok(json_msg) = serde_json::from_str::<Value>(&text) {
         let message_type = json_msg.get("type").and_then(|t| t.as_str());
 
         match message_type {
             // ========== SINGLE SOURCE OF TRUTH: chat.message ==========
             Some("chat.message") => {
-                if let Some(payload) = json_msg.get("payload") {
+                if let Some(Value::Object(payload)) = json_msg.get("payload") {
+                    // Sender and fixture come from the socket, not the client
+                    let mut payload = payload.clone();
+                    payload.insert("fromUserId".to_string(), Value::from(user_id));
+                    payload.insert("username".to_string(), Value::from(username));
+                    payload.insert("fixtureId".to_string(), Value::from(fixture_id));
+                    let payload = Value::Object(payload);
                     let payload_clone = payload.clone();
-                    let fixture_id_from_payload = payload
-                        .get("fixtureId")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or(fixture_id);
 
                     tracing::info!(
                         "📨 Received chat.message from user {} in fixture {}",
                         user_id,
-                        fixture_id_from_payload
+                        fixture_id
                     );
 
+                    // Same bucket as POST /comment
+                    if let Err(retry_after) = state.rate_limiter.hit_user(COMMENTS, user_id).await {
+                        tracing::warn!(
+                            "🚦 Dropped chat.message from {}, rate limited for {}s",
+                            user_id,
+                            retry_after
+                        );
+                        return None;
+                    }
+
                     // ✅ STEP 1: Save to database
-                    if let Err(e) = save_comment_to_database(state, payload).await {
+                    if let Err(e) = save_comment_to_database(state, &payload).await {
                         tracing::error!("Failed to save comment: {}", e);
-                        return;
+                        return None;
                     }
 
                     // ✅ STEP 2: Get updated comment count
-                    let total_comments = get_comment_count(state, fixture_id_from_payload).await;
+                    let total_comments = get_comment_count(state, fixture_id).await;
                     tracing::info!("📊 Total comments after save: {}", total_comments);
 
                     // ✅ STEP 3: Broadcast chat.message to ALL clients
@@ -478,7 +905,7 @@ async fn handle_incoming_message(
                     });
 
                     if let Ok(broadcast_json) = serde_json::to_string(&broadcast_msg) {
-                        let _ = broadcaster.send(broadcast_json);
+                        broadcaster.publish(&channel, broadcast_json).await;
                         tracing::info!("📡 Broadcasted chat.message");
                     }
 
@@ -486,14 +913,14 @@ async fn handle_incoming_message(
                     let comment_count_msg = serde_json::json!({
                         "type": "comment.count",
                         "payload": {
-                            "fixtureId": fixture_id_from_payload,
+                            "fixtureId": fixture_id,
                             "count": total_comments,
                         },
                         "timestamp": Utc::now().to_rfc3339(),
                     });
 
                     if let Ok(count_json) = serde_json::to_string(&comment_count_msg) {
-                        let _ = broadcaster.send(count_json);
+                        broadcaster.publish(&channel, count_json).await;
                         tracing::info!("📡 Broadcasted comment.count: {}", total_comments);
                     }
                 }
@@ -513,9 +940,9 @@ async fn handle_incoming_message(
 
                     tracing::info!("📨 Received message.delete: {}", message_id);
 
-                    if let Err(e) = delete_comment_from_database(state, message_id).await {
+                    if let Err(e) = delete_comment_from_database(state, message_id, user_id).await {
                         tracing::error!("Failed to delete: {}", e);
-                        return;
+                        return None;
                     }
 
                     let total_comments = get_comment_count(state, fixture_id_from_payload).await;
@@ -531,7 +958,7 @@ async fn handle_incoming_message(
                     });
 
                     if let Ok(delete_json) = serde_json::to_string(&delete_msg) {
-                        let _ = broadcaster.send(delete_json);
+                        broadcaster.publish(&channel, delete_json).await;
                     }
 
                     let comment_count_msg = serde_json::json!({
@@ -544,7 +971,7 @@ async fn handle_incoming_message(
                     });
 
                     if let Ok(count_json) = serde_json::to_string(&comment_count_msg) {
-                        let _ = broadcaster.send(count_json);
+                        broadcaster.publish(&channel, count_json).await;
                     }
                 }
             }
@@ -559,7 +986,7 @@ async fn handle_incoming_message(
                         "timestamp": Utc::now().to_rfc3339(),
                     });
                     if let Ok(broadcast_json) = serde_json::to_string(&broadcast_msg) {
-                        let _ = broadcaster.send(broadcast_json);
+                        broadcaster.publish(&channel, broadcast_json).await;
                     }
                 }
             }
@@ -574,7 +1001,7 @@ async fn handle_incoming_message(
                         "timestamp": Utc::now().to_rfc3339(),
                     });
                     if let Ok(broadcast_json) = serde_json::to_string(&broadcast_msg) {
-                        let _ = broadcaster.send(broadcast_json);
+                        broadcaster.publish(&channel, broadcast_json).await;
                     }
                 }
             }
@@ -589,11 +1016,28 @@ async fn handle_incoming_message(
                         "timestamp": Utc::now().to_rfc3339(),
                     });
                     if let Ok(broadcast_json) = serde_json::to_string(&broadcast_msg) {
-                        let _ = broadcaster.send(broadcast_json);
+                        broadcaster.publish(&channel, broadcast_json).await;
                     }
                 }
             }
 
+            // ========== RESUME ==========
+            // {"type": "resume", "lastSeq": 42} after reconnecting
+            Some("resume") => {
+                let last_seq = json_msg
+                    .get("lastSeq")
+                    .or_else(|| json_msg.get("payload").and_then(|p| p.get("lastSeq")))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                tracing::info!(
+                    "⏪ User {} resuming fixture {} after seq {}",
+                    user_id,
+                    fixture_id,
+                    last_seq
+                );
+                let _ = resume.send(last_seq).await;
+            }
+
             // ========== ROOM.JOIN ==========
             Some("room.join") => {
                 tracing::info!("User {} joined room for fixture {}", user_id, fixture_id);
@@ -604,22 +1048,20 @@ async fn handle_incoming_message(
                 tracing::info!("User {} left room for fixture {}", user_id, fixture_id);
             }
 
+            // ========== HEARTBEAT ==========
+            // Keeps the connection present; the socket loop already counted it
+            Some("heartbeat") => {}
+
             // ========== PING ==========
-            Some("ping") => {
-                let pong = serde_json::json!({
-                    "type": "pong",
-                    "timestamp": Utc::now().to_rfc3339(),
-                });
-                if let Ok(pong_json) = serde_json::to_string(&pong) {
-                    let _ = broadcaster.send(pong_json);
-                }
-            }
+            // Only this socket wants the pong
+            Some("ping") => return Some(pong_frame()),
 
             _ => {
                 tracing::debug!("Unknown message type: {:?}", message_type);
             }
         }
     }
+    None
 }
 
 // ========== HANDLE INCOMING MESSAGES ==========
@@ -768,7 +1210,12 @@ async fn save_comment_to_database(state: &AppState, payload: &Value) -> Result<(
 
 // ========== FULL: delete_comment_from_database ==========
 
-async fn delete_comment_from_database(state: &AppState, message_id: &str) -> Result<(), String> {
+/// Delete `user_id`'s own comment, by messageId or _id.
+async fn delete_comment_from_database(
+    state: &AppState,
+    message_id: &str,
+    user_id: &str,
+) -> Result<(), String> {
     let collection: mongodb::Collection<Comment> = state.db.collection("room");
 
     // Try by Flutter messageId field
@@ -779,6 +1226,7 @@ async fn delete_comment_from_database(state: &AppState, message_id: &str) -> Res
         .map_err(|e| format!("Database error: {}", e))?;
 
     if let Some(comment) = comment_by_msg_id {
+        ensure_comment_owner(&comment, message_id, user_id)?;
         let fixture_id = comment.fixture_id.clone();
 
         let result = collection
@@ -802,6 +1250,7 @@ async fn delete_comment_from_database(state: &AppState, message_id: &str) -> Res
             .map_err(|e| format!("Database error: {}", e))?;
 
         if let Some(comment) = comment_by_oid {
+            ensure_comment_owner(&comment, message_id, user_id)?;
             let fixture_id = comment.fixture_id.clone();
 
             let result = collection
@@ -823,6 +1272,16 @@ async fn delete_comment_from_database(state: &AppState, message_id: &str) -> Res
     Err(error_msg)
 }
 
+fn ensure_comment_owner(comment: &Comment, message_id: &str, user_id: &str) -> Result<(), String> {
+    if comment.voter_id != user_id {
+        return Err(format!(
+            "User {} may not delete comment {} by {}",
+            user_id, message_id, comment.voter_id
+        ));
+    }
+    Ok(())
+}
+
 // ========== HELPER: decrement_game_comment_count ==========
 async fn decrement_game_comment_count(state: &AppState, fixture_id: &str) {
     let games_collection = state.db.collection::<Game>("games");
@@ -842,47 +1301,106 @@ async fn decrement_game_comment_count(state: &AppState, fixture_id: &str) {
         }
     }
 }
+// ========== SEND ONE FRAME ==========
+/// False once the socket is gone.
+async fn send_text(sender: &WsSender, text: String) -> bool {
+    let mut sender_guard = sender.lock().await;
+    sender_guard.send(Message::Text(text)).await.is_ok()
+}
+
+fn pong_frame() -> String {
+    serde_json::json!({
+        "type": "pong",
+        "timestamp": Utc::now().to_rfc3339(),
+    })
+    .to_string()
+}
+
+async fn send_pong(sender: &WsSender) {
+    send_text(sender, pong_frame()).await;
+}
+
+async fn send_error(sender: &WsSender, fixture_id: Option<&str>, message: &str) {
+    let error = serde_json::json!({
+        "type": "error",
+        "fixture_id": fixture_id,
+        "message": message,
+        "timestamp": Utc::now().to_rfc3339(),
+    });
+    send_text(sender, error.to_string()).await;
+}
+
+// ========== PRESENCE ==========
+/// A user's `presence` frame; callers go through `PresenceTracker` first so
+/// it only goes out for their first socket in and last one out.
+pub(crate) async fn publish_presence(
+    state: &AppState,
+    fixture_id: &str,
+    user_id: &str,
+    username: &str,
+    status: &str,
+) {
+    let presence = serde_json::json!({
+        "type": "presence",
+        "payload": {
+            "user_id": user_id,
+            "username": username,
+            "status": status,
+            "fixture_id": fixture_id,
+        },
+        "timestamp": Utc::now().to_rfc3339(),
+    });
+
+    if let Ok(presence_json) = serde_json::to_string(&presence) {
+        state
+            .comment_broadcaster
+            .publish(&fixture_channel(fixture_id), presence_json)
+            .await;
+    }
+}
+
 // ========== SEND CURRENT MATCH STATE ==========
 async fn send_current_match_state(
     state: &AppState,
     fixture_id: &str,
-    sender: &Arc<Mutex<futures_util::stream::SplitSink<WebSocket, Message>>>,
+    sender: &WsSender,
 ) {
     let collection = state.db.collection::<crate::models::game::Game>("games");
     let filter = doc! { "match_id": fixture_id };
 
     if let Ok(Some(game)) = collection.find_one(filter).await {
-        let score_msg = serde_json::json!({
-            "type": "match.score",
-            "payload": {
-                "fixture_id": fixture_id,
-                "home_score": game.home_score.unwrap_or(0),
-                "away_score": game.away_score.unwrap_or(0),
-                "minute": game.time_elapsed,
-            },
-            "timestamp": Utc::now().to_rfc3339(),
-        });
-
-        if let Ok(score_json) = serde_json::to_string(&score_msg) {
-            let mut sender_guard = sender.lock().await;
-            let _ = sender_guard.send(Message::Text(score_json)).await;
+        for frame in match_state_frames(fixture_id, &game) {
+            send_text(sender, frame.to_string()).await;
         }
+    }
+}
 
-        let status_msg = serde_json::json!({
-            "type": "match.status",
-            "payload": {
-                "fixture_id": fixture_id,
-                "status": game.status,
-                "time_elapsed": game.time_elapsed,
-            },
-            "timestamp": Utc::now().to_rfc3339(),
-        });
+/// `match.score` and `match.status` frames describing the game as it is now.
+pub(crate) fn match_state_frames(fixture_id: &str, game: &Game) -> [Value; 2] {
+    let score_msg = serde_json::json!({
+        "type": "match.score",
+        "fixture_id": fixture_id,
+        "payload": {
+            "fixture_id": fixture_id,
+            "home_score": game.home_score.unwrap_or(0),
+            "away_score": game.away_score.unwrap_or(0),
+            "minute": game.time_elapsed,
+        },
+        "timestamp": Utc::now().to_rfc3339(),
+    });
 
-        if let Ok(status_json) = serde_json::to_string(&status_msg) {
-            let mut sender_guard = sender.lock().await;
-            let _ = sender_guard.send(Message::Text(status_json)).await;
-        }
-    }
+    let status_msg = serde_json::json!({
+        "type": "match.status",
+        "fixture_id": fixture_id,
+        "payload": {
+            "fixture_id": fixture_id,
+            "status": game.status,
+            "time_elapsed": game.time_elapsed,
+        },
+        "timestamp": Utc::now().to_rfc3339(),
+    });
+
+    [score_msg, status_msg]
 }
 
 // ========== HELPER FUNCTION TO GET COMMENT COUNT ==========
@@ -1001,13 +1519,35 @@ pub async fn broadcast_live_match_update(
                 None
             }
         }
+        "bet_settled" => {
+            if let Ok(payload) = serde_json::from_value::<BetSettledPayload>(data) {
+                Some(serde_json::json!({
+                    "type": "bet.settled",
+                    "payload": payload,
+                    "timestamp": Utc::now().to_rfc3339(),
+                }))
+            } else {
+                None
+            }
+        }
         _ => None,
     };
 
     if let Some(message) = ws_message {
         if let Ok(json) = serde_json::to_string(&message) {
-            let tx = state.get_or_create_broadcaster(fixture_id);
-            let _ = tx.send(json);
+            let is_match_event = message["type"]
+                .as_str()
+                .is_some_and(|frame_type| frame_type.starts_with("match."));
+            if is_match_event {
+                state
+                    .comment_broadcaster
+                    .publish(&match_channel(fixture_id), json.clone())
+                    .await;
+            }
+            state
+                .comment_broadcaster
+                .publish(&fixture_channel(fixture_id), json)
+                .await;
             tracing::info!(
                 "📡 Broadcasted {} event for fixture {}",
                 event_type,
@@ -1016,3 +1556,114 @@ pub async fn broadcast_live_match_update(
         }
     }
 }
+
+// ========== PUBLIC USER CHANNEL FUNCTION ==========
+/// Send a `WSMessage`-shaped frame to every multiplexed socket `user_id`
+/// has open, on whatever fixtures they follow.
+pub async fn notify_user(state: &AppState, user_id: &str, message: Value) {
+    if let Ok(json) = serde_json::to_string(&message) {
+        state
+            .comment_broadcaster
+            .publish(&user_channel(user_id), json)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{with_test_db, OWNER};
+
+    fn comment_by(voter_id: &str) -> Comment {
+        Comment {
+            id: None,
+            message_id: Some("m-1".to_string()),
+            voter_id: voter_id.to_string(),
+            username: voter_id.to_string(),
+            fixture_id: "fixture-1".to_string(),
+            selection: "home_team".to_string(),
+            comment: "Come on!".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            comment_timestamp: BsonDateTime::now(),
+            created_at: None,
+            likes: None,
+            replies: None,
+            seen_by: vec![],
+            image_url: None,
+            video_url: None,
+            is_image: false,
+            is_video: false,
+            reply_to: None,
+        }
+    }
+
+    #[test]
+    fn only_the_author_may_delete_a_comment() {
+        let comment = comment_by(OWNER);
+        assert!(ensure_comment_owner(&comment, "m-1", OWNER).is_ok());
+        assert!(ensure_comment_owner(&comment, "m-1", "stranger").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn chat_message_is_saved_as_the_socket_user() {
+        with_test_db(|state| async move {
+            let (resume, _) = mpsc::channel(1);
+            let message = serde_json::json!({
+                "type": "chat.message",
+                "payload": {
+                    "messageId": "m-1",
+                    "fromUserId": "victim",
+                    "username": "Victim",
+                    "fixtureId": "fixture-2",
+                    "message": "Come on!",
+                },
+            });
+
+            handle_incoming_message(message.to_string(), &state, "fixture-1", "sender", "Sender", &resume)
+                .await;
+
+            let collection: mongodb::Collection<Comment> = state.db.collection("room");
+            let saved = collection
+                .find_one(doc! { "messageId": "m-1" })
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(saved.voter_id, "sender");
+            assert_eq!(saved.username, "Sender");
+            assert_eq!(saved.fixture_id, "fixture-1");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn message_delete_is_owner_only() {
+        with_test_db(|state| async move {
+            let (resume, _) = mpsc::channel(1);
+            let chat = serde_json::json!({
+                "type": "chat.message",
+                "payload": { "messageId": "m-1", "message": "Come on!" },
+            });
+            handle_incoming_message(chat.to_string(), &state, "fixture-1", OWNER, OWNER, &resume)
+                .await;
+
+            let delete = serde_json::json!({
+                "type": "message.delete",
+                "payload": { "messageId": "m-1" },
+            });
+            let collection: mongodb::Collection<Comment> = state.db.collection("room");
+
+            handle_incoming_message(delete.to_string(), &state, "fixture-1", "stranger", "Stranger", &resume)
+                .await;
+            let kept = collection.find_one(doc! { "messageId": "m-1" }).await.unwrap();
+            assert!(kept.is_some());
+
+            handle_incoming_message(delete.to_string(), &state, "fixture-1", OWNER, OWNER, &resume)
+                .await;
+            let deleted = collection.find_one(doc! { "messageId": "m-1" }).await.unwrap();
+            assert!(deleted.is_none());
+        })
+        .await;
+    }
+}
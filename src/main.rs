@@ -1,5 +1,5 @@
 use axum::extract::State;
-use axum::{http::Method, response::Json, routing::get, Router};
+use axum::{http::Method, middleware::from_fn_with_state, response::Json, routing::get, Router};
 use serde_json::{json, Value};
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -11,12 +11,18 @@ mod database;
 mod dumper;
 mod errors;
 mod handlers;
+mod middleware;
 mod models;
 mod routes;
 mod services;
 mod state;
+#[cfg(test)]
+mod test_support;
 
 use database::connection::get_db_client;
+use database::indexes::ensure_indexes;
+use database::migrations::migrate_money_to_cents;
+use middleware::roles::require_admin;
 use services::fcm_service::init_fcm_service;
 use state::AppState;
 
@@ -30,7 +36,12 @@ async fn main() {
     create_directories().await;
 
     let db = get_db_client().await;
+    ensure_indexes(&db).await;
+    migrate_money_to_cents(&db).await;
     let app_state = initialize_app_state(db).await;
+    services::pledge_expiry::spawn_pledge_expiry_job(app_state.clone());
+    services::presence::spawn_presence_job(app_state.clone());
+    services::withdrawal::spawn_withdrawal_status_job(app_state.clone());
 
     let app = build_router(app_state).await;
     start_server(app).await;
@@ -46,8 +57,10 @@ async fn create_directories() {
 }
 
 async fn initialize_app_state(db: mongodb::Database) -> AppState {
-    // Initialize AppState - no more JWT secret or SMS config needed
-    let mut app_state = match AppState::new(db) {
+    let config = Arc::new(config::AppConfig::from_env());
+    tracing::info!("✅ App config loaded successfully");
+
+    let mut app_state = match AppState::new(db, config.clone()) {
         Ok(state) => {
             tracing::info!("✅ AppState initialized successfully");
             state
@@ -59,39 +72,87 @@ async fn initialize_app_state(db: mongodb::Database) -> AppState {
     };
 
     tracing::info!("🔧 Attempting to initialize M-Pesa service...");
+    tracing::info!("📱 Short code: {}", config.mpesa_short_code);
+    tracing::info!("🌐 Environment: {}", config.mpesa_environment);
 
-    // Try to load AppConfig
-    let config_result = std::panic::catch_unwind(|| config::AppConfig::from_env());
-
-    match config_result {
-        Ok(config) => {
-            tracing::info!("✅ App config loaded successfully");
-            tracing::info!("📱 Short code: {}", config.mpesa_short_code);
-            tracing::info!("🌐 Environment: {}", config.mpesa_environment);
-
-            // Create M-Pesa service
-            let mpesa_service = Arc::new(services::mpesa_service::MpesaService::new(config));
-
-            // Try to get access token to verify credentials
-            match mpesa_service.get_access_token().await {
-                Ok(token) => {
-                    tracing::info!("✅ M-Pesa access token obtained");
-                    tracing::debug!("Token (first 20 chars): {}", &token[0..20.min(token.len())]);
-                    app_state = app_state.with_mpesa(mpesa_service);
-                    tracing::info!("✅ M-Pesa service initialized and ready");
-                }
-                Err(e) => {
-                    tracing::error!("❌ Failed to get M-Pesa access token: {}", e);
-                    tracing::warn!("M-Pesa service will be disabled");
-                }
-            }
+    // Create M-Pesa service
+    let mpesa_service = Arc::new(services::mpesa_service::MpesaService::new((*config).clone()));
+
+    // Try to get access token to verify credentials
+    match mpesa_service.get_access_token().await {
+        Ok(token) => {
+            tracing::info!("✅ M-Pesa access token obtained");
+            tracing::debug!("Token (first 20 chars): {}", &token[0..20.min(token.len())]);
+            app_state = app_state.with_mpesa(mpesa_service);
+            tracing::info!("✅ M-Pesa service initialized and ready");
         }
-        Err(_) => {
-            tracing::error!("❌ Failed to load App config (panic caught)");
+        Err(e) => {
+            tracing::error!("❌ Failed to get M-Pesa access token: {}", e);
             tracing::warn!("M-Pesa service will be disabled");
         }
     }
 
+    // Share rate limit counters through Redis when configured
+    if let Some(redis_url) = config.redis_url.as_deref() {
+        match services::rate_limit::RateLimiter::with_redis(redis_url).await {
+            Ok(rate_limiter) => {
+                tracing::info!("✅ Rate limiting backed by Redis");
+                app_state = app_state.with_rate_limiter(Arc::new(rate_limiter));
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to connect to Redis: {}", e);
+                tracing::warn!("Rate limits will be counted per instance");
+            }
+        }
+    }
+
+    // Remember poller webhook signatures across replicas
+    if let Some(redis_url) = config.redis_url.as_deref() {
+        match services::replay_guard::ReplayGuard::with_redis(redis_url).await {
+            Ok(replay_guard) => {
+                tracing::info!("✅ Webhook replay protection backed by Redis");
+                app_state = app_state.with_replay_guard(Arc::new(replay_guard));
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to connect replay protection to Redis: {}", e);
+                tracing::warn!("Webhook replays will only be caught per instance");
+            }
+        }
+    }
+
+    // Count fixture presence across replicas when Redis is there
+    if let Some(redis_url) = config.redis_url.as_deref() {
+        match services::presence::PresenceTracker::with_redis(redis_url).await {
+            Ok(presence) => {
+                tracing::info!("✅ Presence tracked in Redis");
+                app_state = app_state.with_presence(Arc::new(presence));
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to connect presence tracking to Redis: {}", e);
+                tracing::warn!("Presence will only count this instance's sockets");
+            }
+        }
+    }
+
+    // Fan WebSocket broadcasts out through Redis so every replica sees them
+    if config.broadcast_backend == "redis" {
+        match config.redis_url.as_deref() {
+            Some(redis_url) => {
+                match services::broadcaster::RedisBroadcaster::connect(redis_url).await {
+                    Ok(broadcaster) => {
+                        tracing::info!("✅ WebSocket broadcasts go through Redis pub/sub");
+                        app_state = app_state.with_broadcaster(Arc::new(broadcaster));
+                    }
+                    Err(e) => {
+                        tracing::error!("❌ Failed to connect the Redis broadcaster: {}", e);
+                        tracing::warn!("WebSocket broadcasts will only reach this instance");
+                    }
+                }
+            }
+            None => tracing::warn!("BROADCAST_BACKEND=redis needs REDIS_URL; broadcasting in memory"),
+        }
+    }
+
     // Initialize FCM service
     tracing::info!("🔧 Attempting to initialize FCM service...");
     match init_fcm_service().await {
@@ -129,23 +190,31 @@ async fn build_router(app_state: AppState) -> Router {
         .route("/api/simple_health_check", get(simple_health_check))
         // User profile routes (new - Firebase auth)
         // Existing routes
-        .nest("/api/auth", routes::auth::auth_routes())
-        .nest("/api/games", routes::games::routes())
+        .nest("/api/auth", routes::auth::auth_routes(app_state.clone()))
+        .nest("/api/games", routes::games::routes(app_state.clone()))
         .nest("/api/comrades", routes::comrade_route::comrade_routes())
         .nest("/api/posts", routes::posts::routes())
         .nest("/api/bets", routes::bets::bets_routes())
-        .nest("/api/pledges", routes::pledges::routes())
-        .nest("/api/lipaclash", routes::mpesa::mpesa_routes())
-        .nest("/api/votes", routes::vote_routes::vote_routes())
+        .nest("/api/pledges", routes::pledges::routes(app_state.clone()))
+        .nest("/api/wallet", routes::wallet::wallet_routes(app_state.clone()))
+        .nest("/api/kyc", routes::kyc::kyc_routes(app_state.clone()))
+        .nest(
+            "/api/reports",
+            routes::reports::reports_routes()
+                .route_layer(from_fn_with_state(app_state.clone(), require_admin)),
+        )
+        .nest("/api/lipaclash", routes::mpesa::mpesa_routes(app_state.clone()))
+        .nest("/api/votes", routes::vote_routes::vote_routes(app_state.clone()))
         .nest("/api/archive", routes::archive::archive_routes())
-        .nest("/api/chats", routes::chat::routes())
+        .nest("/api/chats", routes::chat::routes(app_state.clone()))
         .nest("/ws", routes::vote_routes::ws_routes())
         .nest("/comments", routes::posts::comment_routes())
         .nest(
             "/api/notifications",
-            routes::vote_routes::notification_routes(),
+            routes::vote_routes::notification_routes(app_state.clone()),
         )
         .nest("/api/profile", routes::user_profile::user_profile_routes())
+        .nest("/api/admin", routes::admin::admin_routes(app_state.clone()))
         .nest("/api", routes::posts::upload_routes())
         .layer(cors)
         .with_state(app_state)
@@ -159,7 +228,13 @@ async fn start_server(app: Router) {
 
     match tokio::net::TcpListener::bind(addr).await {
         Ok(listener) => {
-            axum::serve(listener, app).await.unwrap();
+            // Peer address is needed for the M-Pesa callback allowlist and rate limits
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
         }
         Err(e) => {
             tracing::error!("Failed to bind to {}: {}", addr, e);
@@ -0,0 +1,172 @@
+// src/middleware/auth.rs
+//
+// Bearer JWT authentication. Handlers that need to know who is calling take
+// an `AuthUser` argument; the request is rejected with 401 before the
+// handler runs if the token is missing, malformed, expired or signed with
+// another secret, or if its session has been logged out. Identity must come
+// from here, never from request bodies.
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
+};
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+
+use crate::errors::AppError;
+use crate::models::user::{Claims, Role};
+use crate::services::session;
+use crate::state::AppState;
+
+/// Lifetime of an access token; clients renew it with their refresh token.
+pub const TOKEN_TTL_SECS: i64 = 15 * 60;
+
+/// The caller, as proven by their access token.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: String,
+    pub username: String,
+    pub phone: String,
+    pub session_id: String,
+    pub roles: Vec<Role>,
+}
+
+impl AuthUser {
+    /// 403 unless the caller is `user_id`.
+    pub fn ensure_is(&self, user_id: &str) -> Result<(), AppError> {
+        if self.user_id == user_id {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized)
+        }
+    }
+
+    /// Admins implicitly hold every role.
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&Role::Admin) || self.roles.contains(&role)
+    }
+
+    /// For edits and deletes: 403 unless the caller owns the resource
+    /// (`owner_id`) or is a moderator.
+    pub fn ensure_owner_or_moderator(&self, owner_id: &str) -> Result<(), AppError> {
+        if self.user_id == owner_id || self.has_role(Role::Moderator) {
+            Ok(())
+        } else {
+            tracing::warn!("🚫 {} may not modify a resource owned by {}", self.user_id, owner_id);
+            Err(AppError::Unauthorized)
+        }
+    }
+}
+
+#[cfg(test)]
+impl AuthUser {
+    /// A caller for handler tests; there is no token or session behind it.
+    pub fn for_tests(user_id: &str, roles: Vec<Role>) -> Self {
+        AuthUser {
+            user_id: user_id.to_string(),
+            username: user_id.to_string(),
+            phone: String::new(),
+            session_id: String::new(),
+            roles,
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(AppError::AuthError)?;
+
+        let claims = verify_token(&state.config.jwt_secret, token)?;
+        if claims.sid.is_empty() || !session::is_active(state, &claims.sid).await? {
+            return Err(AppError::AuthError);
+        }
+
+        Ok(AuthUser {
+            user_id: claims.sub,
+            username: claims.username,
+            phone: claims.phone,
+            session_id: claims.sid,
+            roles: claims.roles,
+        })
+    }
+}
+
+/// The token from an `Authorization: Bearer <token>` header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Sign an access token for a user's session.
+pub fn issue_token(
+    secret: &str,
+    user_id: &str,
+    username: &str,
+    phone: &str,
+    roles: &[Role],
+    session_id: &str,
+) -> Result<String, AppError> {
+    let claims = Claims {
+        sub: user_id.to_string(),
+        username: username.to_string(),
+        phone: phone.to_string(),
+        sid: session_id.to_string(),
+        roles: roles.to_vec(),
+        exp: (Utc::now().timestamp() + TOKEN_TTL_SECS) as usize,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|e| AppError::internal_server_error(format!("Token signing failed: {}", e)))
+}
+
+/// Check signature and expiry of an access token.
+pub fn verify_token(secret: &str, token: &str) -> Result<Claims, AppError> {
+    decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
+        .map(|data| data.claims)
+        .map_err(|e| {
+            tracing::warn!("🔒 Rejected access token: {}", e);
+            AppError::AuthError
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_is_only_lets_the_user_through() {
+        let user = AuthUser::for_tests("user-1", vec![]);
+        assert!(user.ensure_is("user-1").is_ok());
+        assert!(matches!(user.ensure_is("user-2"), Err(AppError::Unauthorized)));
+
+        let admin = AuthUser::for_tests("admin", vec![Role::Admin]);
+        assert!(matches!(admin.ensure_is("user-2"), Err(AppError::Unauthorized)));
+    }
+
+    #[test]
+    fn ensure_owner_or_moderator_lets_owners_and_moderators_through() {
+        let owner = AuthUser::for_tests("owner", vec![]);
+        assert!(owner.ensure_owner_or_moderator("owner").is_ok());
+
+        let moderator = AuthUser::for_tests("moderator", vec![Role::Moderator]);
+        assert!(moderator.ensure_owner_or_moderator("owner").is_ok());
+
+        let admin = AuthUser::for_tests("admin", vec![Role::Admin]);
+        assert!(admin.ensure_owner_or_moderator("owner").is_ok());
+    }
+
+    #[test]
+    fn ensure_owner_or_moderator_turns_away_everyone_else() {
+        let stranger = AuthUser::for_tests("stranger", vec![]);
+        assert!(matches!(stranger.ensure_owner_or_moderator("owner"), Err(AppError::Unauthorized)));
+
+        let poller = AuthUser::for_tests("poller", vec![Role::Poller]);
+        assert!(matches!(poller.ensure_owner_or_moderator("owner"), Err(AppError::Unauthorized)));
+    }
+}
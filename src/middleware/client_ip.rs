@@ -0,0 +1,57 @@
+// src/middleware/client_ip.rs
+use std::net::{IpAddr, SocketAddr};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::request::Parts,
+};
+
+use crate::state::AppState;
+
+/// Address of the caller: the TCP peer, unless the peer is one of the
+/// configured `trusted_proxies`. Then `X-Forwarded-For` is walked from the
+/// right past any further trusted hops; everything left of the first
+/// untrusted hop is client-supplied and ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientIp
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+
+        let state = AppState::from_ref(state);
+        let trusted = &state.config.trusted_proxies;
+        let Some(peer) = peer.filter(|peer| trusted.contains(peer)) else {
+            return Ok(ClientIp(peer));
+        };
+
+        let forwarded = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let mut client = peer;
+        for hop in forwarded.rsplit(',') {
+            let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = hop;
+            if !trusted.contains(&hop) {
+                break;
+            }
+        }
+
+        Ok(ClientIp(Some(client)))
+    }
+}
@@ -0,0 +1,66 @@
+// src/middleware/daraja.rs
+//
+// Authentication for Safaricom Daraja callbacks. Daraja cannot sign its
+// requests, so a callback is accepted only if it carries the secret path
+// token from AppConfig (`.../confirmation/<token>`) and/or comes from an
+// allowlisted address. Refused callbacks are kept in the audit collection.
+use axum::{
+    body::to_bytes,
+    extract::{RawPathParams, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::errors::AppError;
+use crate::middleware::client_ip::ClientIp;
+use crate::services::mpesa_audit;
+use crate::state::AppState;
+
+/// Largest body read back for the audit record.
+const MAX_AUDIT_BODY: usize = 64 * 1024;
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub async fn verify_daraja_callback(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    params: Option<RawPathParams>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = &state.config;
+    let token = params
+        .as_ref()
+        .and_then(|params| params.iter().find(|(key, _)| *key == "token").map(|(_, value)| value));
+
+    let reason = if !config.mpesa_callback_token.is_empty()
+        && !token.is_some_and(|token| constant_time_eq(token, &config.mpesa_callback_token))
+    {
+        Some("missing or wrong callback token")
+    } else if !config.mpesa_callback_ips.is_empty()
+        && !ip.is_some_and(|ip| config.mpesa_callback_ips.iter().any(|allowed| *allowed == ip.to_string()))
+    {
+        Some("source address not allowlisted")
+    } else {
+        None
+    };
+
+    let Some(reason) = reason else {
+        return next.run(request).await;
+    };
+
+    // Never echo the token into the audit log
+    let path = match token {
+        Some(token) => request.uri().path().replace(token, "<token>"),
+        None => request.uri().path().to_string(),
+    };
+    let body = to_bytes(request.into_body(), MAX_AUDIT_BODY)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default();
+    mpesa_audit::record(&state, &path, ip.map(|ip| ip.to_string()), reason, &body).await;
+
+    AppError::Unauthorized.into_response()
+}
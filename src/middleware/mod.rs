@@ -0,0 +1,6 @@
+pub(crate) mod auth;
+pub(crate) mod client_ip;
+pub(crate) mod daraja;
+pub(crate) mod poller_signature;
+pub(crate) mod rate_limit;
+pub(crate) mod roles;
@@ -0,0 +1,74 @@
+// src/middleware/rate_limit.rs
+//
+// Per-route rate limiting. Wrap a route with
+// `.route_layer(from_fn_with_state(RateLimit::new(&state, COMMENTS), rate_limit))`;
+// over the limit the caller gets 429 with a Retry-After header.
+use axum::{
+    extract::{FromRef, Request, State},
+    http::{header::RETRY_AFTER, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::errors::AppError;
+use crate::middleware::auth::{bearer_token, verify_token};
+use crate::middleware::client_ip::ClientIp;
+use crate::services::rate_limit::RateLimitPolicy;
+use crate::state::AppState;
+
+#[derive(Clone)]
+pub struct RateLimit {
+    state: AppState,
+    policy: RateLimitPolicy,
+}
+
+impl RateLimit {
+    pub fn new(state: &AppState, policy: RateLimitPolicy) -> Self {
+        RateLimit {
+            state: state.clone(),
+            policy,
+        }
+    }
+}
+
+// Lets extractors that need the app state (ClientIp) run under this layer
+impl FromRef<RateLimit> for AppState {
+    fn from_ref(limit: &RateLimit) -> Self {
+        limit.state.clone()
+    }
+}
+
+pub async fn rate_limit(
+    State(limit): State<RateLimit>,
+    ClientIp(ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let RateLimit { state, policy } = limit;
+
+    // Only the signature is checked here; the handler does full authentication
+    let user_id = bearer_token(request.headers())
+        .and_then(|token| verify_token(&state.config.jwt_secret, token).ok())
+        .map(|claims| claims.sub);
+
+    let buckets = [
+        ip.map(|ip| ("ip", ip.to_string(), policy.per_ip)),
+        user_id.map(|user_id| ("user", user_id, policy.per_user)),
+    ];
+    for (scope, key, limit) in buckets.into_iter().flatten() {
+        if limit == 0 {
+            continue;
+        }
+        let bucket = format!("{}:{}:{}", policy.name, scope, key);
+        if let Err(retry_after) = state.rate_limiter.hit(&bucket, limit, policy.window_secs).await {
+            tracing::warn!("🚦 Rate limited {} ({} over {})", bucket, policy.name, limit);
+            let mut response = AppError::RateLimitExceeded.into_response();
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+            return response;
+        }
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,34 @@
+// src/middleware/roles.rs
+//
+// Route guards for staff endpoints. Mount with
+// `route_layer(from_fn_with_state(state, require_admin))`; the request is
+// rejected with 401 without a valid token and 403 without the role.
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
+use crate::models::user::Role;
+
+fn require(auth: &AuthUser, role: Role) -> Result<(), AppError> {
+    if auth.has_role(role) {
+        Ok(())
+    } else {
+        tracing::warn!("🚫 {} lacks the {:?} role", auth.user_id, role);
+        Err(AppError::Unauthorized)
+    }
+}
+
+pub async fn require_admin(auth: AuthUser, request: Request, next: Next) -> Result<Response, AppError> {
+    require(&auth, Role::Admin)?;
+    Ok(next.run(request).await)
+}
+
+pub async fn require_moderator(auth: AuthUser, request: Request, next: Next) -> Result<Response, AppError> {
+    require(&auth, Role::Moderator)?;
+    Ok(next.run(request).await)
+}
+
+pub async fn require_poller(auth: AuthUser, request: Request, next: Next) -> Result<Response, AppError> {
+    require(&auth, Role::Poller)?;
+    Ok(next.run(request).await)
+}
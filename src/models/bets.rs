@@ -2,79 +2,65 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use mongodb::bson::{oid::ObjectId, serde_helpers};
 
-// Model for creating a new bet (when user accepts a pledge)
-#[derive(Debug, Deserialize)]
-pub struct CreateBetRequest {
-    // Pledge info
-    #[serde(rename = "pledge_id")]
-    pub pledge_id: PledgeId,
-
-    // Starter info (original bet creator)
-    pub starter_id: String,
-    pub starter_username: String,
-    pub starter_selection: String, // "home_team", "away_team", or "draw"
-    #[serde(deserialize_with = "deserialize_f64")]
-    pub starter_amount: f64,
-    pub starter_team: String,
-
-    // Finisher info (user accepting the bet)
-    pub finisher_id: String,
-    pub finisher_username: String,
-    pub finisher_selection: String,
-    #[serde(default)]
-    pub finisher_amount: Option<f64>, // Can be calculated if not provided
-    pub finisher_team: String,
-
-    // Match info
-    pub home_team: String,
-    pub away_team: String,
-    pub match_time: Option<DateTime<Utc>>,
-    pub league: String,
-    pub sport_type: String,
-
-    // Bet details
-    #[serde(deserialize_with = "deserialize_f64")]
-    pub total_pot: f64,
-    #[serde(default = "default_status")]
-    pub status: String, // "active", "completed", "cancelled"
-
-    // Odds
-    pub odds: BetOdds,
-
-    // Optional fields that might not be in incoming JSON
-    #[serde(default)]
-    pub winner_id: Option<String>,
-    #[serde(default)]
-    pub winner_username: Option<String>,
-    #[serde(default)]
-    pub winning_selection: Option<String>,
-}
+use crate::models::game::Game;
+use crate::models::money::{deserialize_kes_opt, Money};
+use crate::models::odds::{deserialize_unpriced, Odds};
 
-// Bet odds structure
+// Bet odds structure, snapshotted from the Game when the bet is created
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BetOdds {
-    pub home_win: String,
-    pub away_win: String,
-    pub draw: String,
+    pub home_win: Odds,
+    pub away_win: Odds,
+    #[serde(default, deserialize_with = "deserialize_unpriced")]
+    pub draw: Option<Odds>, // None when the fixture has no draw price
 }
 
-// Handle MongoDB ObjectId in JSON (both string and object formats)
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(untagged)]
-pub enum PledgeId {
-    String(String),
-    ObjectId { #[serde(rename = "$oid")] oid: String },
-}
+impl BetOdds {
+    /// Current prices of a fixture; `None` if it isn't priced yet.
+    pub fn from_game(game: &Game) -> Option<Self> {
+        Some(BetOdds {
+            home_win: Odds::from_decimal(game.home_win)?,
+            away_win: Odds::from_decimal(game.away_win)?,
+            draw: Odds::from_decimal(game.draw),
+        })
+    }
 
-impl PledgeId {
-    pub fn to_string(&self) -> String {
-        match self {
-            PledgeId::String(s) => s.clone(),
-            PledgeId::ObjectId { oid } => oid.clone(),
+    /// Odds for a pledge selection ("home_team", "away_team", "draw").
+    pub fn for_selection(&self, selection: &str) -> Option<Odds> {
+        match selection {
+            "home_team" => Some(self.home_win),
+            "away_team" => Some(self.away_win),
+            "draw" => self.draw,
+            _ => None,
         }
     }
 }
 
+// GET /api/bets/quote - either an open pledge, or a prospective pledge
+#[derive(Debug, Deserialize)]
+pub struct BetQuoteQuery {
+    pub pledge_id: Option<String>,
+    pub match_id: Option<String>,
+    pub selection: Option<String>, // "home_team", "away_team", or "draw"
+    #[serde(default, deserialize_with = "deserialize_kes_opt")]
+    pub amount: Option<Money>, // KES
+}
+
+// Stake/payout breakdown for a starter stake at the fixture's current odds
+#[derive(Debug, Serialize)]
+pub struct BetQuote {
+    pub match_id: String,
+    pub home_team: String,
+    pub away_team: String,
+    pub starter_selection: String,
+    pub odds: Odds,
+    pub starter_amount: Money,
+    pub finisher_amount: Money, // What the finisher must stake to cover the starter's winnings
+    pub total_pot: Money,
+    pub starter_payout: Money,  // Paid to the starter if their selection wins
+    pub finisher_payout: Money, // Paid to the finisher otherwise
+}
+
 // Database model for Bets collection (MongoDB)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Bet {
@@ -88,17 +74,19 @@ pub struct Bet {
     pub starter_id: String,
     pub starter_username: String,
     pub starter_selection: String,
-    pub starter_amount: f64,
+    pub starter_amount: Money,
     pub starter_team: String,
 
     // Finisher info
     pub finisher_id: String,
     pub finisher_username: String,
     pub finisher_selection: String,
-    pub finisher_amount: f64, // Required in DB, calculated if missing
+    pub finisher_amount: Money, // Required in DB, calculated if missing
     pub finisher_team: String,
 
     // Match info
+    #[serde(default)]
+    pub match_id: Option<String>,
     pub home_team: String,
     pub away_team: String,
     pub match_time: Option<DateTime<Utc>>,
@@ -106,8 +94,10 @@ pub struct Bet {
     pub sport_type: String,
 
     // Bet details
-    pub total_pot: f64,
-    pub status: String, // "active", "completed", "cancelled"
+    pub total_pot: Money,
+    pub status: String, // "active", "completed", "cancelled", "void"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commission: Option<Money>, // House cut, set at settlement
 
     // Winner info (filled when match completes)
     pub winner_id: Option<String>,
@@ -143,23 +133,25 @@ pub struct BetResponse {
     pub starter_id: String,
     pub starter_username: String,
     pub starter_selection: String,
-    pub starter_amount: f64,
+    pub starter_amount: Money,
     pub starter_team: String,
 
     pub finisher_id: String,
     pub finisher_username: String,
     pub finisher_selection: String,
-    pub finisher_amount: f64,
+    pub finisher_amount: Money,
     pub finisher_team: String,
 
+    pub match_id: Option<String>,
     pub home_team: String,
     pub away_team: String,
     pub match_time: Option<DateTime<Utc>>,
     pub league: String,
     pub sport_type: String,
 
-    pub total_pot: f64,
+    pub total_pot: Money,
     pub status: String,
+    pub commission: Option<Money>,
 
     pub winner_id: Option<String>,
     pub winner_username: Option<String>,
@@ -171,13 +163,6 @@ pub struct BetResponse {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
-// Model for user balance update
-#[derive(Debug, Deserialize)]
-pub struct UpdateBalanceRequest {
-    pub user_id: String,
-    pub balance: f64,
-}
-
 // Model for updating pledge status
 #[derive(Debug, Deserialize)]
 pub struct UpdatePledgeStatusRequest {
@@ -200,6 +185,7 @@ impl From<Bet> for BetResponse {
             finisher_selection: bet.finisher_selection,
             finisher_amount: bet.finisher_amount,
             finisher_team: bet.finisher_team,
+            match_id: bet.match_id,
             home_team: bet.home_team,
             away_team: bet.away_team,
             match_time: bet.match_time,
@@ -207,6 +193,7 @@ impl From<Bet> for BetResponse {
             sport_type: bet.sport_type,
             total_pot: bet.total_pot,
             status: bet.status,
+            commission: bet.commission,
             winner_id: bet.winner_id,
             winner_username: bet.winner_username,
             winning_selection: bet.winning_selection,
@@ -232,67 +219,3 @@ pub struct SuccessResponse {
     pub message: String,
     pub data: Option<serde_json::Value>,
 }
-
-// Helper function to deserialize numbers to f64 (accepts both integer and float)
-fn deserialize_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    use serde::Deserialize;
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum IntOrFloat {
-        Int(i64),
-        Float(f64),
-    }
-
-    match IntOrFloat::deserialize(deserializer)? {
-        IntOrFloat::Int(i) => Ok(i as f64),
-        IntOrFloat::Float(f) => Ok(f),
-    }
-}
-
-// Default status function
-fn default_status() -> String {
-    "active".to_string()
-}
-
-// Helper to convert CreateBetRequest to Bet for database insertion
-impl From<CreateBetRequest> for Bet {
-    fn from(req: CreateBetRequest) -> Self {
-        let now = Utc::now();
-
-        // Calculate finisher_amount if not provided
-        let finisher_amount = req.finisher_amount
-            .unwrap_or_else(|| req.total_pot - req.starter_amount);
-
-        Bet {
-            id: None, // Will be set by MongoDB
-            pledge_id: req.pledge_id.to_string(),
-            starter_id: req.starter_id,
-            starter_username: req.starter_username,
-            starter_selection: req.starter_selection,
-            starter_amount: req.starter_amount,
-            starter_team: req.starter_team,
-            finisher_id: req.finisher_id,
-            finisher_username: req.finisher_username,
-            finisher_selection: req.finisher_selection,
-            finisher_amount,
-            finisher_team: req.finisher_team,
-            home_team: req.home_team,
-            away_team: req.away_team,
-            match_time: req.match_time,
-            league: req.league,
-            sport_type: req.sport_type,
-            total_pot: req.total_pot,
-            status: req.status,
-            winner_id: req.winner_id,
-            winner_username: req.winner_username,
-            winning_selection: req.winning_selection,
-            odds: req.odds,
-            created_at: now,
-            updated_at: now,
-            completed_at: None,
-        }
-    }
-}
\ No newline at end of file
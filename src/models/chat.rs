@@ -48,7 +48,8 @@ pub struct ChatMessage {
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateChatMessage {
-    #[serde(rename = "sender_id")]
+    // sender_id / sender_name are set from the access token
+    #[serde(rename = "sender_id", default)]
     #[validate(length(min = 1, message = "Sender ID is required"))]
     pub sender_id: String,
 
@@ -56,7 +57,7 @@ pub struct CreateChatMessage {
     #[validate(length(min = 1, message = "Receiver ID is required"))]
     pub receiver_id: String,
 
-    #[serde(rename = "sender_name")]
+    #[serde(rename = "sender_name", default)]
     #[validate(length(min = 1, message = "Sender name is required"))]
     pub sender_name: String,
 
@@ -85,8 +86,8 @@ pub struct MarkAsSeenRequest {
     #[serde(rename = "post_id")]
     pub post_id: String,
 
-    #[serde(rename = "user_id")]
-    pub user_id: String,
+    #[serde(rename = "user_id", default)]
+    pub user_id: String, // Set from the access token
 }
 
 // ========== CHAT MESSAGE RESPONSE ==========
@@ -0,0 +1,41 @@
+use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::Money;
+
+// Hold lifecycle:
+//   pending  -> recorded, stake not yet moved out of the wallet
+//   held     -> funds reserved for an open pledge
+//   locked   -> funds staked on an accepted bet
+//   released -> funds returned to the owner's wallet (cancel / expiry)
+//   settled  -> funds paid out to the bet winner
+pub const HOLD_PENDING: &str = "pending";
+pub const HOLD_HELD: &str = "held";
+pub const HOLD_LOCKED: &str = "locked";
+pub const HOLD_RELEASED: &str = "released";
+pub const HOLD_SETTLED: &str = "settled";
+
+/// Funds moved from a user's wallet into their escrow account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowHold {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub hold_id: String,
+    pub user_id: String,
+    pub amount: Money,
+    pub pledge_id: String,
+    pub bet_id: Option<String>,
+    pub status: String,
+    pub created_at: BsonDateTime,
+    pub updated_at: BsonDateTime,
+}
+
+impl EscrowHold {
+    pub fn pledge_hold_id(pledge_id: &str) -> String {
+        format!("pledge:{}:starter", pledge_id)
+    }
+
+    pub fn finisher_hold_id(bet_id: &str) -> String {
+        format!("bet:{}:finisher", bet_id)
+    }
+}
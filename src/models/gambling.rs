@@ -0,0 +1,150 @@
+use mongodb::bson::DateTime as BsonDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::{deserialize_kes_opt, Money};
+
+// ========== LIMIT KINDS ==========
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    Deposit, // Money paid in through STK push
+    Stake,   // Money put on pledges and bets
+    Loss,    // Stakes minus payouts and refunds
+}
+
+/// Periods are rolling: "daily" is the last 24 hours, not the calendar day.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl LimitPeriod {
+    pub const ALL: [LimitPeriod; 3] = [LimitPeriod::Daily, LimitPeriod::Weekly, LimitPeriod::Monthly];
+
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            LimitPeriod::Daily => chrono::Duration::days(1),
+            LimitPeriod::Weekly => chrono::Duration::days(7),
+            LimitPeriod::Monthly => chrono::Duration::days(30),
+        }
+    }
+}
+
+// ========== STORED ON THE PROFILE ==========
+/// One limit per period; `None` means no limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LimitSet {
+    #[serde(default)]
+    pub daily: Option<Money>,
+    #[serde(default)]
+    pub weekly: Option<Money>,
+    #[serde(default)]
+    pub monthly: Option<Money>,
+}
+
+impl LimitSet {
+    pub fn get(&self, period: LimitPeriod) -> Option<Money> {
+        match period {
+            LimitPeriod::Daily => self.daily,
+            LimitPeriod::Weekly => self.weekly,
+            LimitPeriod::Monthly => self.monthly,
+        }
+    }
+
+    pub fn set(&mut self, period: LimitPeriod, amount: Option<Money>) {
+        match period {
+            LimitPeriod::Daily => self.daily = amount,
+            LimitPeriod::Weekly => self.weekly = amount,
+            LimitPeriod::Monthly => self.monthly = amount,
+        }
+    }
+}
+
+/// A raised or removed limit waiting out the cooling delay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingLimitChange {
+    pub kind: LimitKind,
+    pub period: LimitPeriod,
+    pub amount: Option<Money>, // None removes the limit
+    pub effective_at: BsonDateTime,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GamblingLimits {
+    #[serde(default)]
+    pub deposit: LimitSet,
+    #[serde(default)]
+    pub stake: LimitSet,
+    #[serde(default)]
+    pub loss: LimitSet,
+    #[serde(default)]
+    pub pending: Vec<PendingLimitChange>,
+    /// No deposits or stakes until then; chosen in hours or days.
+    #[serde(default)]
+    pub cool_off_until: Option<BsonDateTime>,
+    /// Like a cool-off but measured in months, and cannot be shortened.
+    #[serde(default)]
+    pub self_excluded_until: Option<BsonDateTime>,
+}
+
+impl GamblingLimits {
+    pub fn limits(&self, kind: LimitKind) -> &LimitSet {
+        match kind {
+            LimitKind::Deposit => &self.deposit,
+            LimitKind::Stake => &self.stake,
+            LimitKind::Loss => &self.loss,
+        }
+    }
+
+    pub fn limits_mut(&mut self, kind: LimitKind) -> &mut LimitSet {
+        match kind {
+            LimitKind::Deposit => &mut self.deposit,
+            LimitKind::Stake => &mut self.stake,
+            LimitKind::Loss => &mut self.loss,
+        }
+    }
+}
+
+// ========== REQUESTS ==========
+// PUT /api/wallet/limits
+#[derive(Debug, Deserialize)]
+pub struct SetLimitRequest {
+    pub kind: LimitKind,
+    pub period: LimitPeriod,
+    #[serde(default, deserialize_with = "deserialize_kes_opt")]
+    pub amount: Option<Money>, // KES; omit or null to remove the limit
+}
+
+// POST /api/wallet/cool-off
+#[derive(Debug, Deserialize)]
+pub struct CoolOffRequest {
+    pub hours: i64,
+}
+
+// POST /api/wallet/self-exclusion
+#[derive(Debug, Deserialize)]
+pub struct SelfExclusionRequest {
+    pub months: i64,
+}
+
+// ========== RESPONSES ==========
+#[derive(Debug, Serialize)]
+pub struct LimitUsage {
+    pub kind: LimitKind,
+    pub period: LimitPeriod,
+    pub limit: Option<Money>,
+    pub used: Money,
+    pub remaining: Option<Money>,
+}
+
+// GET /api/wallet/limits
+#[derive(Debug, Serialize)]
+pub struct LimitsOverview {
+    pub usage: Vec<LimitUsage>,
+    pub pending: Vec<PendingLimitChange>,
+    pub cool_off_until: Option<BsonDateTime>,
+    pub self_excluded_until: Option<BsonDateTime>,
+}
@@ -142,6 +142,10 @@ pub struct Game {
     #[serde(rename = "league")]
     pub league: String,
 
+    // Picks the commission rule together with the league
+    #[serde(rename = "sport_type", default = "default_sport_type")]
+    pub sport_type: String,
+
     #[serde(rename = "tournament", skip_serializing_if = "Option::is_none")]
     pub tournament: Option<String>,
 
@@ -234,6 +238,10 @@ pub struct Game {
     pub voters: Vec<Voter>,
 }
 
+fn default_sport_type() -> String {
+    "football".to_string()
+}
+
 // ========== FOR CREATING NEW GAMES ==========
 #[derive(Debug, Deserialize)]
 pub struct CreateGame {
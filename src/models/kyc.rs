@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+/// Minimum age for pledges, bets and withdrawals.
+pub const MINIMUM_AGE: u32 = 18;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KycStatus {
+    #[default]
+    Unverified, // Nothing submitted yet
+    Pending,    // Waiting for an admin to review the document
+    Verified,
+    Rejected,   // May resubmit
+}
+
+impl KycStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KycStatus::Unverified => "unverified",
+            KycStatus::Pending => "pending",
+            KycStatus::Verified => "verified",
+            KycStatus::Rejected => "rejected",
+        }
+    }
+}
+
+/// One identity submission per user (collection `kyc_records`). Kept apart
+/// from the public profile, which only mirrors `status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KycRecord {
+    pub user_id: String,
+    pub id_number: String, // National ID or passport number, unique across users
+    pub date_of_birth: NaiveDate,
+    pub document_public_id: String, // Cloudinary public id; never served publicly
+    #[serde(default = "default_document_format")]
+    pub document_format: String,
+    // "authenticated" since uploads stopped being public; older ones are "upload"
+    #[serde(default = "default_document_type")]
+    pub document_type: String,
+    pub status: KycStatus,
+    #[serde(default)]
+    pub rejection_reason: Option<String>,
+    #[serde(default)]
+    pub reviewed_by: Option<String>,
+    #[serde(default)]
+    pub reviewed_at: Option<DateTime>,
+    pub submitted_at: DateTime,
+}
+
+fn default_document_format() -> String {
+    "jpg".to_string()
+}
+
+fn default_document_type() -> String {
+    "upload".to_string()
+}
+
+/// A submission as admins see it: the document is a signed link that stops
+/// working at `document_url_expires_at` (unix seconds).
+#[derive(Debug, Serialize)]
+pub struct KycReviewItem {
+    #[serde(flatten)]
+    pub record: KycRecord,
+    pub document_url: String,
+    pub document_url_expires_at: i64,
+}
+
+// PUT /api/admin/kyc/:user_id/review
+#[derive(Debug, Deserialize)]
+pub struct KycReviewRequest {
+    pub approve: bool,
+    #[serde(default)]
+    pub reason: Option<String>, // Shown to the user on rejection
+}
+
+// GET /api/admin/kyc?status=pending
+#[derive(Debug, Deserialize)]
+pub struct KycQuery {
+    pub status: Option<KycStatus>,
+    pub limit: Option<i64>,
+}
+
+/// What a user sees of their own submission.
+#[derive(Debug, Serialize)]
+pub struct KycStatusResponse {
+    pub status: KycStatus,
+    pub id_number: Option<String>, // Masked to the last 3 characters
+    pub date_of_birth: Option<NaiveDate>,
+    pub rejection_reason: Option<String>,
+    pub submitted_at: Option<DateTime>,
+    pub reviewed_at: Option<DateTime>,
+}
+
+impl From<Option<KycRecord>> for KycStatusResponse {
+    fn from(record: Option<KycRecord>) -> Self {
+        match record {
+            Some(record) => {
+                let visible = record.id_number.len().saturating_sub(3);
+                KycStatusResponse {
+                    status: record.status,
+                    id_number: Some(format!(
+                        "{}{}",
+                        "*".repeat(visible),
+                        &record.id_number[visible..]
+                    )),
+                    date_of_birth: Some(record.date_of_birth),
+                    rejection_reason: record.rejection_reason,
+                    submitted_at: Some(record.submitted_at),
+                    reviewed_at: record.reviewed_at,
+                }
+            }
+            None => KycStatusResponse {
+                status: KycStatus::Unverified,
+                id_number: None,
+                date_of_birth: None,
+                rejection_reason: None,
+                submitted_at: None,
+                reviewed_at: None,
+            },
+        }
+    }
+}
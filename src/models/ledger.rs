@@ -0,0 +1,187 @@
+use chrono::NaiveDate;
+use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::Money;
+
+// ========== SYSTEM ACCOUNTS ==========
+/// Mirrors the real money sitting in the M-Pesa paybill. Deposits debit it,
+/// withdrawals credit it back, so it is allowed to go negative.
+pub const MPESA_CLEARING_ACCOUNT: &str = "system:mpesa";
+
+/// Platform revenue (commission, fees).
+pub const HOUSE_ACCOUNT: &str = "system:house";
+
+// ========== ENTRY KINDS ==========
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Deposit,
+    Stake,
+    Payout,
+    Refund,
+    Withdrawal,
+    Fee,
+    Adjustment,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryKind::Deposit => "deposit",
+            EntryKind::Stake => "stake",
+            EntryKind::Payout => "payout",
+            EntryKind::Refund => "refund",
+            EntryKind::Withdrawal => "withdrawal",
+            EntryKind::Fee => "fee",
+            EntryKind::Adjustment => "adjustment",
+        }
+    }
+}
+
+// ========== ACCOUNT ==========
+/// One balance-carrying account. `balance` is a cache of the sum of all
+/// posted postings for `account_id` and is only ever changed by the ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerAccount {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub account_id: String,
+    pub owner_id: Option<String>,
+    pub account_type: String, // "wallet", "escrow", "withdrawal", "system"
+    pub balance: Money,
+    pub allow_negative: bool,
+    pub created_at: BsonDateTime,
+    pub updated_at: BsonDateTime,
+}
+
+impl LedgerAccount {
+    pub fn wallet_id(user_id: &str) -> String {
+        format!("user:{}:wallet", user_id)
+    }
+
+    /// Funds reserved for open pledges and staked on active bets.
+    pub fn escrow_id(user_id: &str) -> String {
+        format!("user:{}:escrow", user_id)
+    }
+
+    /// Funds on their way to the user's phone via B2C.
+    pub fn withdrawal_id(user_id: &str) -> String {
+        format!("user:{}:withdrawal", user_id)
+    }
+}
+
+// ========== JOURNAL ==========
+/// A single leg of a journal entry. Positive amounts increase the account
+/// balance, negative amounts decrease it. The legs of an entry sum to zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerPosting {
+    pub account_id: String,
+    pub owner_id: Option<String>,
+    pub amount: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_after: Option<Money>,
+}
+
+impl LedgerPosting {
+    pub fn user_wallet(user_id: &str, amount: Money) -> Self {
+        LedgerPosting {
+            account_id: LedgerAccount::wallet_id(user_id),
+            owner_id: Some(user_id.to_string()),
+            amount,
+            balance_after: None,
+        }
+    }
+
+    pub fn user_escrow(user_id: &str, amount: Money) -> Self {
+        LedgerPosting {
+            account_id: LedgerAccount::escrow_id(user_id),
+            owner_id: Some(user_id.to_string()),
+            amount,
+            balance_after: None,
+        }
+    }
+
+    pub fn user_withdrawal(user_id: &str, amount: Money) -> Self {
+        LedgerPosting {
+            account_id: LedgerAccount::withdrawal_id(user_id),
+            owner_id: Some(user_id.to_string()),
+            amount,
+            balance_after: None,
+        }
+    }
+
+    pub fn system(account_id: &str, amount: Money) -> Self {
+        LedgerPosting {
+            account_id: account_id.to_string(),
+            owner_id: None,
+            amount,
+            balance_after: None,
+        }
+    }
+}
+
+/// Immutable journal entry. `entry_id` doubles as the idempotency key, so
+/// posting the same movement twice (e.g. a retried callback) is a no-op.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub entry_id: String,
+    pub kind: EntryKind,
+    pub status: String, // "pending", "posted"
+    pub reference: Option<String>,
+    pub description: String,
+    pub postings: Vec<LedgerPosting>,
+    pub created_at: BsonDateTime,
+}
+
+// ========== STATEMENT ==========
+#[derive(Debug, Deserialize)]
+pub struct LedgerQuery {
+    pub limit: Option<i64>,
+    pub skip: Option<u64>,
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatementLine {
+    pub entry_id: String,
+    pub kind: EntryKind,
+    pub reference: Option<String>,
+    pub description: String,
+    pub amount: Money,
+    pub balance_after: Option<Money>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerStatement {
+    pub user_id: String,
+    pub account_id: String,
+    pub balance: Money,
+    pub count: usize,
+    pub entries: Vec<StatementLine>,
+}
+
+// ========== COMMISSION REPORT ==========
+#[derive(Debug, Deserialize)]
+pub struct CommissionReportQuery {
+    pub from: Option<NaiveDate>, // YYYY-MM-DD (EAT), defaults to 30 days ago
+    pub to: Option<NaiveDate>,   // YYYY-MM-DD (EAT), inclusive, defaults to today
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommissionDay {
+    pub date: String,
+    pub commission: Money,
+    pub bets: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommissionReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total: Money,
+    pub days: Vec<CommissionDay>,
+}
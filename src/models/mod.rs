@@ -6,16 +6,24 @@ pub(crate) mod archive;
 pub(crate) mod bets;
 pub(crate) mod chat; // Now just a simple declaration
 pub(crate) mod comments; // Now just a simple declaration
+pub(crate) mod escrow;
 pub(crate) mod events;
+pub(crate) mod gambling;
+pub(crate) mod kyc;
+pub(crate) mod ledger;
+pub(crate) mod money;
+pub(crate) mod odds;
 mod livegames;
 pub(crate) mod notification;
 pub(crate) mod otp;
 mod payment;
 pub(crate) mod pledges;
 pub(crate) mod posta;
+pub(crate) mod session;
 pub mod sub_fixture; // Add this
 pub(crate) mod transaction;
 pub(crate) mod user_profile;
+pub(crate) mod withdrawal;
 pub use sub_fixture::*;
 pub(crate) mod comrade;
 pub(crate) mod line_up;
@@ -0,0 +1,212 @@
+use mongodb::bson::Bson;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// An exact KES amount stored as integer cents.
+///
+/// Serializes as a plain integer number of cents, both in MongoDB (Int64)
+/// and in API responses. Request bodies keep accepting shillings (e.g.
+/// `"amount": 150.5`) through [`deserialize_kes`], which converts to cents
+/// with the rounding rule below.
+///
+/// Rounding: anything finer than one cent is rounded half away from zero
+/// (`0.005 -> 0.01`, `-0.005 -> -0.01`).
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    /// Whole shillings, e.g. `Money::from_kes(100)` == 100.00 KES.
+    pub const fn from_kes(kes: i64) -> Self {
+        Money(kes * 100)
+    }
+
+    /// Shillings from a float (legacy data, third-party payloads).
+    pub fn from_kes_f64(kes: f64) -> Option<Self> {
+        if !kes.is_finite() {
+            return None;
+        }
+        let cents = (kes * 100.0).round();
+        if cents.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        Some(Money(cents as i64))
+    }
+
+    /// Shillings from a decimal string ("150", "150.5", "150.505").
+    /// Parsed digit by digit so no float rounding is involved.
+    pub fn parse_kes(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let (negative, digits) = match raw.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, raw.strip_prefix('+').unwrap_or(raw)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+        let mut fraction_digits = fraction.chars().map(|c| c as i64 - '0' as i64);
+        let tenths = fraction_digits.next().unwrap_or(0);
+        let hundredths = fraction_digits.next().unwrap_or(0);
+        let round_up = fraction_digits.next().unwrap_or(0) >= 5;
+
+        let cents = whole
+            .checked_mul(100)?
+            .checked_add(tenths * 10 + hundredths + i64::from(round_up))?;
+        Some(Money(if negative { -cents } else { cents }))
+    }
+
+    /// Shillings as a float, for display and push notification text only.
+    pub fn to_kes_f64(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    /// Multiply by a ratio `numerator / denominator`, rounding half away
+    /// from zero. Used for odds and percentage calculations.
+    pub fn mul_ratio(self, numerator: i64, denominator: i64) -> Self {
+        let product = self.0 as i128 * numerator as i128;
+        let denominator = denominator as i128;
+        let half = denominator.abs() / 2;
+        let rounded = if (product >= 0) == (denominator > 0) {
+            (product.abs() + half) / denominator.abs()
+        } else {
+            -((product.abs() + half) / denominator.abs())
+        };
+        Money(rounded as i64)
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(self) -> Self {
+        Money(self.0.abs())
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+impl From<Money> for Bson {
+    fn from(money: Money) -> Bson {
+        Bson::Int64(money.0)
+    }
+}
+
+// ========== REQUEST HELPERS ==========
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KesInput {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl KesInput {
+    fn into_money<E: serde::de::Error>(self) -> Result<Money, E> {
+        match self {
+            KesInput::Int(kes) => kes
+                .checked_mul(100)
+                .map(Money)
+                .ok_or_else(|| E::custom("amount is too large")),
+            KesInput::Float(kes) => {
+                Money::from_kes_f64(kes).ok_or_else(|| E::custom("amount is not a valid number"))
+            }
+            KesInput::Text(raw) => Money::parse_kes(&raw)
+                .ok_or_else(|| E::custom(format!("invalid amount: {}", raw))),
+        }
+    }
+}
+
+/// `#[serde(deserialize_with = "deserialize_kes")]` for request fields given
+/// in shillings. Accepts `100`, `100.5` and `"100.50"`.
+pub fn deserialize_kes<'de, D>(deserializer: D) -> Result<Money, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    KesInput::deserialize(deserializer)?.into_money()
+}
+
+/// Optional variant of [`deserialize_kes`]; pair with `#[serde(default)]`.
+pub fn deserialize_kes_opt<'de, D>(deserializer: D) -> Result<Option<Money>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<KesInput>::deserialize(deserializer)?
+        .map(KesInput::into_money)
+        .transpose()
+}
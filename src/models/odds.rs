@@ -0,0 +1,87 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::models::money::Money;
+
+/// Decimal odds (e.g. 2.35) held as an integer number of thousandths so
+/// stake maths never goes through floats. Always greater than 1.
+///
+/// Serializes as a decimal number; deserializes from a number or a string
+/// (`2.35` or `"2.35"`), rounding to three decimal places.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Odds(i64);
+
+const SCALE: i64 = 1000;
+
+impl Odds {
+    pub fn from_decimal(odds: f64) -> Option<Self> {
+        if !odds.is_finite() {
+            return None;
+        }
+        Self::from_milli((odds * SCALE as f64).round() as i64)
+    }
+
+    pub fn from_milli(milli: i64) -> Option<Self> {
+        (milli > SCALE).then_some(Odds(milli))
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        Self::from_decimal(raw.trim().parse().ok()?)
+    }
+
+    pub fn to_decimal(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    /// What a winning `stake` earns on top of itself; this is the amount the
+    /// other side of a P2P bet has to put up.
+    pub fn winnings(self, stake: Money) -> Money {
+        stake.mul_ratio(self.0 - SCALE, SCALE)
+    }
+}
+
+impl fmt::Display for Odds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:03}", self.0 / SCALE, self.0 % SCALE)
+    }
+}
+
+impl Serialize for Odds {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_decimal())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OddsInput {
+    Number(f64),
+    Text(String),
+}
+
+impl OddsInput {
+    fn into_odds(self) -> Option<Odds> {
+        match self {
+            OddsInput::Number(odds) => Odds::from_decimal(odds),
+            OddsInput::Text(raw) => Odds::parse(&raw),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Odds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        OddsInput::deserialize(deserializer)?
+            .into_odds()
+            .ok_or_else(|| serde::de::Error::custom("odds must be a number greater than 1"))
+    }
+}
+
+/// For prices that are legitimately missing, like the draw in sports that
+/// cannot end level: `null`, `""`, `0` and anything else not above 1 become
+/// `None`. Pair with `#[serde(default)]`.
+pub fn deserialize_unpriced<'de, D>(deserializer: D) -> Result<Option<Odds>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<OddsInput>::deserialize(deserializer)?.and_then(OddsInput::into_odds))
+}
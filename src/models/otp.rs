@@ -9,3 +9,26 @@ pub struct ResetOTP {
     pub expires_at: DateTime, // When OTP expires
     pub created_at: DateTime, // When OTP was created
 }
+
+/// A pending phone verification code, one per phone (collection `phone_otps`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PhoneOtp {
+    pub phone: String,
+    pub code: String,         // 6-digit OTP
+    pub attempts: i32,        // Verification attempts so far
+    pub expires_at: DateTime, // When OTP expires
+    pub created_at: DateTime, // When OTP was sent
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestOtpRequest {
+    pub phone: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyOtpRequest {
+    pub phone: String,
+    pub code: String,
+    #[serde(default)]
+    pub device_id: Option<String>, // One session per device; logging in again replaces it
+}
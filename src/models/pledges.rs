@@ -3,6 +3,14 @@ use mongodb::bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
 use mongodb::bson;
 
+use crate::models::money::Money;
+
+// Pledge lifecycle: "open" -> "matched" (accepted into a bet) -> "completed",
+// or "open" -> "cancelled" / "expired" (unmatched at kickoff)
+pub const PLEDGE_OPEN: &str = "open";
+pub const PLEDGE_MATCHED: &str = "matched";
+pub const PLEDGE_EXPIRED: &str = "expired";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pledge {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -10,12 +18,24 @@ pub struct Pledge {
     pub username: String,
     pub phone: String,
     pub selection: String, // "home_team", "away_team", or "draw"
-    pub amount: f64,
+    pub amount: Money,
     pub time: DateTime<Utc>,
     pub fan: String,
+    #[serde(default)]
+    pub match_id: Option<String>, // Game.match_id
     pub home_team: String,
     pub away_team: String,
     pub starter_id: String, // Added field
+    #[serde(default = "default_pledge_status")]
+    pub status: String,
+    #[serde(default)]
+    pub bet_id: Option<String>, // Set once the pledge is matched
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<DateTime<Utc>>, // Kickoff, or earlier if the pledger chose so
 
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub created_at: DateTime<Utc>,  // Changed from Option<DateTime<Utc>>
@@ -24,16 +44,25 @@ pub struct Pledge {
     pub updated_at: DateTime<Utc>,  // Changed from Option<DateTime<Utc>>
 }
 
+// starter_id / username / phone are taken from the access token
 #[derive(Debug, Deserialize)]
 pub struct CreatePledge {
+    #[serde(default)]
     pub username: String,
+    #[serde(default)]
     pub phone: String,
     pub selection: String,
-    pub amount: f64,
+    #[serde(deserialize_with = "crate::models::money::deserialize_kes")]
+    pub amount: Money, // KES in the request, cents once stored
     pub fan: String,
+    #[serde(default)]
+    pub match_id: Option<String>,
     pub home_team: String,
     pub away_team: String,
-    pub starter_id: String, // Added field
+    #[serde(default)]
+    pub starter_id: String,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>, // Optional, capped at kickoff
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,4 +72,19 @@ pub struct PledgeQuery {
     pub home_team: Option<String>,
     pub away_team: Option<String>,
     pub starter_id: Option<String>, // Added field
-}
\ No newline at end of file
+    pub status: Option<String>,     // "open" only returns pledges that can still be accepted
+}
+
+// Accepting a pledge: the finisher is the caller, everything else is
+// derived from the stored pledge
+#[derive(Debug, Deserialize)]
+pub struct AcceptPledgeRequest {
+    #[serde(default)]
+    pub finisher_selection: Option<String>, // Required when the pledge backs a draw
+    #[serde(default)]
+    pub match_time: Option<DateTime<Utc>>,
+}
+
+fn default_pledge_status() -> String {
+    PLEDGE_OPEN.to_string()
+}
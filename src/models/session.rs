@@ -0,0 +1,30 @@
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+/// One logged-in device (collection `auth_sessions`). Access tokens carry
+/// the `session_id`, so revoking the session revokes them too.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Session {
+    pub session_id: String,
+    pub user_id: String,
+    pub device_id: String,
+    pub refresh_hash: String, // bcrypt of the current refresh token's secret
+    pub expires_at: DateTime, // Refresh token expiry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub last_used_at: DateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Returned on login and on every refresh.
+#[derive(Debug, Serialize)]
+pub struct SessionTokens {
+    pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64, // Access token lifetime in seconds
+}
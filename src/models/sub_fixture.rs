@@ -64,7 +64,9 @@ impl SubFixtureVote {
 // ========== REQUEST MODELS ==========
 #[derive(Debug, Deserialize)]
 pub struct CreateSubFixtureVoteRequest {
-    pub voter_id: String,
+    #[serde(default)]
+    pub voter_id: String, // Set from the access token
+    #[serde(default)]
     pub username: String,
     pub sub_fixture_id: String,
     pub parent_fixture_id: String,
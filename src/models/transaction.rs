@@ -1,14 +1,16 @@
 // src/models/transaction.rs
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use serde::{Deserialize, Serialize};
 
+use crate::models::money::Money;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub user_id: String,
     pub phone_number: String,
-    pub amount: f64,
+    pub amount: Money,
     pub merchant_request_id: String,
     pub checkout_request_id: String,
     pub response_code: String,
@@ -17,6 +19,8 @@ pub struct Transaction {
     pub status: String,
     pub result_code: Option<i32>,
     pub result_desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mpesa_receipt: Option<String>,
     pub created_at: String,
     pub updated_at: String,
     pub completed_at: Option<String>,
@@ -91,3 +95,14 @@ pub struct MetadataItem {
     #[allow(non_snake_case)]
     pub Value: serde_json::Value,
 }
+
+/// A Daraja callback that was refused or didn't add up (collection
+/// `mpesa_callback_audit`), kept for reconciliation and fraud review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackAudit {
+    pub path: String,
+    pub source_ip: Option<String>,
+    pub reason: String,
+    pub payload: String, // Raw body, truncated
+    pub received_at: DateTime,
+}
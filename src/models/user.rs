@@ -1,17 +1,38 @@
 use mongodb::bson::{oid::ObjectId, DateTime};
 use serde::{Deserialize, Serialize};
 
+use crate::models::money::Money;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub username: String,
     pub phone: String,
-    pub balance: f64,
+    /// Read-only projection of the user's ledger wallet (see services::ledger)
+    pub balance: Money,
+    #[serde(default)]
+    pub phone_verified: bool, // Set once an OTP sent to the phone is verified
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<Role>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
+/// Staff roles. Regular fans have none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Moderator,
+    Poller, // The live-score poller service account
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRolesRequest {
+    pub roles: Vec<Role>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
     pub username: String,
@@ -23,7 +44,8 @@ pub struct UserResponse {
     pub id: String,
     pub username: String,
     pub phone: String,
-    pub balance: f64,
+    pub balance: Money,
+    pub phone_verified: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -37,5 +59,9 @@ pub struct Claims {
     pub sub: String,
     pub username: String,
     pub phone: String,
+    #[serde(default)]
+    pub sid: String, // Session the token belongs to (services::session)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<Role>,
     pub exp: usize,
 }
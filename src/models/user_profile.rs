@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
 use validator::Validate;
 
+use crate::models::gambling::GamblingLimits;
+use crate::models::kyc::KycStatus;
+use crate::models::money::Money;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserProfile {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -13,8 +17,15 @@ pub struct UserProfile {
     pub nickname: String,
     pub club_fan: String,
     pub country_fan: String,
-    pub balance: f64,
+    /// Read-only projection of the user's ledger wallet (see services::ledger)
+    pub balance: Money,
     pub number_of_bets: i32,
+    /// Responsible gambling settings; only changed through services::responsible_gambling
+    #[serde(default)]
+    pub gambling_limits: GamblingLimits,
+    /// Mirror of the user's KYC record (see services::kyc)
+    #[serde(default)]
+    pub kyc_status: KycStatus,
 
     pub created_at: BsonDateTime,
     pub updated_at: BsonDateTime,
@@ -22,8 +33,9 @@ pub struct UserProfile {
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateUserProfile {
+    #[serde(default)]
     #[validate(length(min = 1))]
-    pub user_id: String,
+    pub user_id: String, // Set from the access token
 
     pub username: String,
 
@@ -34,22 +46,10 @@ pub struct CreateUserProfile {
     pub club_fan: String,
     pub country_fan: String,
 
-    #[validate(range(min = 0.0))]
-    pub balance: f64,
-
     #[validate(range(min = 0))]
     pub number_of_bets: i32,
 }
 
-#[derive(Debug, Deserialize, Validate)]
-pub struct UpdateBalanceRequest {
-    #[validate(length(min = 1))]
-    pub user_id: String,
-
-    #[validate(range(min = 0.0))]
-    pub balance: f64,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct UserQuery {
     pub user_id: Option<String>,
@@ -66,11 +66,11 @@ pub struct Vote {
 // For creating new votes (from Flutter app)
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateVote {
-    #[serde(rename = "voterId")]
+    #[serde(rename = "voterId", default)] // Set from the access token
     #[validate(length(min = 1, message = "Voter ID is required"))]
     pub voter_id: String,
 
-    #[serde(rename = "username")]
+    #[serde(rename = "username", default)] // Set from the access token
     #[validate(length(min = 1, message = "Username is required"))]
     pub username: String,
 
@@ -127,11 +127,11 @@ pub struct Like {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateLike {
-    #[serde(rename = "voterId")]
+    #[serde(rename = "voterId", default)] // Set from the access token
     #[validate(length(min = 1, message = "Voter ID is required"))]
     pub voter_id: String,
 
-    #[serde(rename = "username")]
+    #[serde(rename = "username", default)] // Set from the access token
     #[validate(length(min = 1, message = "Username is required"))]
     pub username: String,
 
@@ -214,11 +214,11 @@ pub struct Comment {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateComment {
-    #[serde(rename = "voterId")]
+    #[serde(rename = "voterId", default)] // Set from the access token
     #[validate(length(min = 1, message = "Voter ID is required"))]
     pub voter_id: String,
 
-    #[serde(rename = "username")]
+    #[serde(rename = "username", default)] // Set from the access token
     #[validate(length(min = 1, message = "Username is required"))]
     pub username: String,
 
@@ -261,7 +261,7 @@ pub struct CreateComment {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct MarkCommentsSeenRequest {
-    #[serde(rename = "userId")]
+    #[serde(rename = "userId", default)] // Set from the access token
     pub user_id: String,
 
     #[serde(rename = "fixtureId")]
@@ -303,6 +303,39 @@ pub struct UnreadCountsResponse {
     pub unread_counts: std::collections::HashMap<String, i64>,
 }
 
+// ========== PRESENCE MODELS ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnlineUser {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    pub username: String,
+}
+
+// Sampled watchers grouped by their vote on the fixture
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PresenceSample {
+    #[serde(rename = "homeTeam")]
+    pub home_team: Vec<OnlineUser>,
+    pub draw: Vec<OnlineUser>,
+    #[serde(rename = "awayTeam")]
+    pub away_team: Vec<OnlineUser>,
+    #[serde(rename = "noVote")]
+    pub no_vote: Vec<OnlineUser>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FixturePresence {
+    #[serde(rename = "fixtureId")]
+    pub fixture_id: String,
+
+    // Distinct users connected to the fixture right now
+    pub online: usize,
+
+    #[serde(rename = "bySelection")]
+    pub by_selection: PresenceSample,
+}
+
 // ========== STATISTICS MODELS ==========
 
 #[derive(Debug, Serialize, Deserialize)]
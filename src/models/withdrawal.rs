@@ -0,0 +1,119 @@
+use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::Money;
+
+// Withdrawal lifecycle:
+//   pending    -> funds moved out of the wallet, B2C request not yet accepted
+//   processing -> Daraja accepted the B2C request, waiting for the result
+//   unknown    -> the B2C request timed out in Daraja's queue; the payment
+//                 may still have gone through, so funds stay held until a
+//                 transaction status query (or a late result) settles it
+//   completed  -> money delivered to the user's phone
+//   failed     -> B2C rejected, funds refunded to the wallet
+//   review     -> paid out after the refund and the wallet can't cover the
+//                 reversal; needs manual follow-up
+pub const WITHDRAWAL_PENDING: &str = "pending";
+pub const WITHDRAWAL_PROCESSING: &str = "processing";
+pub const WITHDRAWAL_UNKNOWN: &str = "unknown";
+pub const WITHDRAWAL_COMPLETED: &str = "completed";
+pub const WITHDRAWAL_FAILED: &str = "failed";
+pub const WITHDRAWAL_REVIEW: &str = "review";
+
+/// Smallest amount Daraja will pay out via B2C.
+pub const MIN_WITHDRAWAL: Money = Money::from_kes(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalStatusChange {
+    pub status: String,
+    pub note: Option<String>,
+    pub at: BsonDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Withdrawal {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub phone_number: String,
+    pub amount: Money,
+    pub status: String,
+    pub conversation_id: Option<String>,
+    pub originator_conversation_id: Option<String>,
+    pub mpesa_transaction_id: Option<String>,
+    pub result_code: Option<i32>,
+    pub result_desc: Option<String>,
+    // IDs of the last transaction status query sent for this withdrawal
+    #[serde(default)]
+    pub status_query_conversation_id: Option<String>,
+    #[serde(default)]
+    pub status_query_originator_id: Option<String>,
+    #[serde(default)]
+    pub review_reason: Option<String>,
+    pub status_history: Vec<WithdrawalStatusChange>,
+    pub created_at: BsonDateTime,
+    pub updated_at: BsonDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWithdrawalRequest {
+    #[serde(default)]
+    pub user_id: String, // Set from the access token
+    #[serde(default)]
+    pub phone_number: String, // The verified phone from the access token
+    #[serde(deserialize_with = "crate::models::money::deserialize_kes")]
+    pub amount: Money, // KES, whole shillings only
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WithdrawalQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawalStatusLine {
+    pub status: String,
+    pub note: Option<String>,
+    pub at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawalResponse {
+    pub id: String,
+    pub user_id: String,
+    pub phone_number: String,
+    pub amount: Money,
+    pub status: String,
+    pub conversation_id: Option<String>,
+    pub mpesa_transaction_id: Option<String>,
+    pub result_desc: Option<String>,
+    pub status_history: Vec<WithdrawalStatusLine>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<Withdrawal> for WithdrawalResponse {
+    fn from(w: Withdrawal) -> Self {
+        WithdrawalResponse {
+            id: w.id.map(|id| id.to_hex()).unwrap_or_default(),
+            user_id: w.user_id,
+            phone_number: w.phone_number,
+            amount: w.amount,
+            status: w.status,
+            conversation_id: w.conversation_id,
+            mpesa_transaction_id: w.mpesa_transaction_id,
+            result_desc: w.result_desc,
+            status_history: w
+                .status_history
+                .into_iter()
+                .map(|change| WithdrawalStatusLine {
+                    status: change.status,
+                    note: change.note,
+                    at: change.at.try_to_rfc3339_string().unwrap_or_default(),
+                })
+                .collect(),
+            created_at: w.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            updated_at: w.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
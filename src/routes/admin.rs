@@ -0,0 +1,55 @@
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use crate::middleware::roles::{require_admin, require_moderator, require_poller};
+use crate::state::AppState;
+
+/// Operational endpoints, nested at /api/admin. Each group sits behind the
+/// role it needs; admins pass every guard.
+pub fn admin_routes(state: AppState) -> Router<AppState> {
+    let admin = Router::new()
+        .route("/users", get(crate::handlers::auth::get_all_users))
+        .route("/users/:id/roles", put(crate::handlers::auth::set_user_roles))
+        // KYC review queue
+        .route("/kyc", get(crate::handlers::kyc::list_kyc_submissions))
+        .route("/kyc/:user_id/review", put(crate::handlers::kyc::review_kyc))
+        // Manual bet/pledge corrections; these move escrowed money
+        .route(
+            "/bets/:id",
+            put(crate::handlers::bets::update_bet_status).delete(crate::handlers::bets::delete_bet),
+        )
+        .route(
+            "/pledges/:id/status",
+            put(crate::handlers::bets::update_pledge_status),
+        )
+        .route(
+            "/notifications/send-bulk",
+            post(crate::handlers::notification_handler::send_bulk_notifications),
+        )
+        .route_layer(from_fn_with_state(state.clone(), require_admin));
+
+    // Votes cleanup, overview stats and sub-fixture management
+    let moderation = crate::routes::vote_routes::vote_admin_routes()
+        .route_layer(from_fn_with_state(state.clone(), require_moderator));
+
+    // Score/status updates pushed by the live-score poller
+    let poller = Router::new()
+        .route(
+            "/games/:match_id/score",
+            put(crate::handlers::games::update_game_score),
+        )
+        .route(
+            "/games/:match_id/status",
+            put(crate::handlers::games::update_game_status),
+        )
+        .route(
+            "/games/:match_id/events",
+            delete(crate::handlers::events_handler::delete_match_events),
+        )
+        .route_layer(from_fn_with_state(state, require_poller));
+
+    admin.merge(moderation).merge(poller)
+}
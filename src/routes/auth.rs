@@ -1,17 +1,33 @@
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post},
     Router,
 };
 
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::OTP;
 use crate::state::AppState;
 
-pub fn auth_routes() -> Router<AppState> {
+pub fn auth_routes(state: AppState) -> Router<AppState> {
+    let otp = || from_fn_with_state(RateLimit::new(&state, OTP), rate_limit);
+
     Router::new()
         // Registration
         .route("/register", post(crate::handlers::auth::register))
-        // Get users
-        .route("/users", get(crate::handlers::auth::get_all_users))
-        // Get user by phone (login)
+        // Phone verification - the only way to get an access token
+        .route(
+            "/otp/request",
+            post(crate::handlers::auth::request_otp).route_layer(otp()),
+        )
+        .route(
+            "/otp/verify",
+            post(crate::handlers::auth::verify_otp).route_layer(otp()),
+        )
+        // Sessions
+        .route("/refresh", post(crate::handlers::auth::refresh_token))
+        .route("/logout", post(crate::handlers::auth::logout))
+        .route("/logout-all", post(crate::handlers::auth::logout_all))
+        // Get user by phone (lookup only, no token)
         .route(
             "/user/phone/:phone",
             get(crate::handlers::auth::get_user_by_phone),
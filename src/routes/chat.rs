@@ -1,24 +1,35 @@
 // src/routes/chat_routes.rs
 use axum::{
+    middleware::from_fn_with_state,
     routing::{delete, get, post, put},
     Router,
 };
 
 use crate::handlers::chat_handlers;
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::CHAT;
 use crate::state::AppState;
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: AppState) -> Router<AppState> {
+    let chat = || from_fn_with_state(RateLimit::new(&state, CHAT), rate_limit);
+
     Router::new()
         // Get messages for a specific post
         .route("/chat/:post_id/messages", get(chat_handlers::get_post_messages))
         // In your router setup (probably in main.rs or a routes file)
        // .route("/chat/users/:user_id/messages", get(chat_handlers::get_user_messages))
         // Create a new message in a post
-        .route("/chat/:post_id/messages", post(chat_handlers::create_message))
+        .route(
+            "/chat/:post_id/messages",
+            post(chat_handlers::create_message).route_layer(chat()),
+        )
         // Get a specific message by ID
         .route("/chat/messages/:message_id", get(chat_handlers::get_message))
         // Update a message (user's own only)
-        .route("/chat/messages/:message_id", put(chat_handlers::update_message))
+        .route(
+            "/chat/messages/:message_id",
+            put(chat_handlers::update_message).route_layer(chat()),
+        )
         // Delete a message (user's own only)
         .route("/chat/messages/:message_id", delete(chat_handlers::delete_message))
         // Mark multiple messages as seen
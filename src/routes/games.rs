@@ -1,15 +1,18 @@
 use axum::{
-    routing::{delete, get, post, put},
+    middleware::from_fn_with_state,
+    routing::{get, post},
     Router,
 };
 
 use crate::handlers::events_handler;
 use crate::handlers::games;
 use crate::handlers::lineup_handler;
+use crate::handlers::match_stream;
 use crate::handlers::statistics_handler;
+use crate::middleware::poller_signature::verify_poller_signature;
 use crate::state::AppState;
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: AppState) -> Router<AppState> {
     Router::new()
         // GAME ROUTES
         .route("/", get(games::get_games))
@@ -19,8 +22,6 @@ pub fn routes() -> Router<AppState> {
         .route("/recent", get(games::get_recent_games))
         .route("/:id", get(games::get_game_by_id))
         .route("/match/:match_id", get(games::get_game_by_match_id))
-        .route("/:match_id/score", put(games::update_game_score))
-        .route("/:match_id/status", put(games::update_game_status))
         // FAST COUNT ENDPOINTS
         .route(
             "/fixture/:fixture_id/votes/fast",
@@ -57,20 +58,12 @@ pub fn routes() -> Router<AppState> {
             "/:match_id/events/latest",
             get(events_handler::get_latest_event),
         )
-        .route("/events", post(events_handler::add_timeline_event))
+        // LIVE UPDATES OVER SSE
         .route(
-            "/:match_id/events",
-            delete(events_handler::delete_match_events),
+            "/:match_id/stream",
+            get(match_stream::stream_match_updates),
         )
         // STATISTICS ENDPOINTS
-        .route(
-            "/statistics",
-            post(statistics_handler::add_statistics_snapshot),
-        )
-        .route(
-            "/statistics/bulk",
-            post(statistics_handler::bulk_update_statistics),
-        )
         .route(
             "/:match_id/statistics",
             get(statistics_handler::get_match_statistics),
@@ -84,7 +77,6 @@ pub fn routes() -> Router<AppState> {
             get(statistics_handler::get_statistics_at_minute),
         )
         // LINEUPS ENDPOINTS
-        .route("/lineups", post(lineup_handler::receive_lineups_update))
         .route("/:match_id/lineups", get(lineup_handler::get_lineups))
         .route(
             "/:match_id/lineups/simplified",
@@ -94,11 +86,28 @@ pub fn routes() -> Router<AppState> {
             "/:match_id/lineups/available",
             get(lineup_handler::check_lineups_available),
         )
-        // LIVE UPDATE ENDPOINT
+        // POLLER WEBHOOKS
+        .merge(poller_routes(state))
+}
+
+/// Everything the match data poller pushes. Signed with the shared secret,
+/// see middleware::poller_signature.
+fn poller_routes(state: AppState) -> Router<AppState> {
+    Router::new()
         .route("/live-update", post(games::receive_live_update))
-        // TEST NOTIFICATION ENDPOINT
+        .route("/lineups", post(lineup_handler::receive_lineups_update))
+        .route(
+            "/statistics",
+            post(statistics_handler::add_statistics_snapshot),
+        )
+        .route(
+            "/statistics/bulk",
+            post(statistics_handler::bulk_update_statistics),
+        )
+        .route("/events", post(events_handler::add_timeline_event))
         .route(
             "/test-notification",
             post(games::send_test_notification_from_poller),
         )
+        .route_layer(from_fn_with_state(state, verify_poller_signature))
 }
@@ -0,0 +1,25 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware::from_fn_with_state,
+    routing::post,
+    Router,
+};
+
+use crate::handlers::kyc::{self, MAX_DOCUMENT_BYTES};
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::KYC;
+use crate::state::AppState;
+
+pub fn kyc_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        // GET  /api/kyc - Own verification status
+        // POST /api/kyc - Submit ID number, date of birth and document photo
+        .route(
+            "/",
+            post(kyc::submit_kyc)
+                .route_layer(from_fn_with_state(RateLimit::new(&state, KYC), rate_limit))
+                .get(kyc::get_kyc_status)
+                // Room for the document plus the text fields
+                .layer(DefaultBodyLimit::max(MAX_DOCUMENT_BYTES + 64 * 1024)),
+        )
+}
@@ -1,3 +1,4 @@
+pub(crate) mod admin;
 pub(crate) mod archive;
 //pub(crate) mod auth;
 pub mod auth;
@@ -5,11 +6,14 @@ pub(crate) mod bets;
 pub(crate) mod chat;
 pub(crate) mod comrade_route;
 pub(crate) mod games;
+pub(crate) mod kyc;
 pub(crate) mod mpesa;
 pub(crate) mod pledges;
 pub(crate) mod posts;
+pub(crate) mod reports;
 pub(crate) mod user_profile;
 pub(crate) mod vote_routes;
+pub(crate) mod wallet;
 // pub mod auth;  // Remove or comment out if not needed
 
 // Re-export all public functions
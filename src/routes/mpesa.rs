@@ -1,4 +1,5 @@
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post},
     Json, Router,
 };
@@ -7,27 +8,30 @@ use serde_json::json;
 
 use crate::handlers::b2c_handlers;
 use crate::handlers::mpesa_handlers;
+use crate::middleware::daraja::verify_daraja_callback;
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::STK_PUSH;
 use crate::state::AppState;
 
-pub fn mpesa_routes() -> Router<AppState> {
+pub fn mpesa_routes(state: AppState) -> Router<AppState> {
     Router::new()
         // Health
         .route("/health", get(mpesa_health))
         // C2B Routes
-        .route("/stk-push", post(mpesa_handlers::initiate_stk_push))
-        .route("/validation", post(mpesa_handlers::mpesa_validation))
-        .route("/confirmation", post(mpesa_handlers::mpesa_confirmation))
-        // Keep old callback for backward compatibility
-        .route("/callback", post(mpesa_handlers::mpesa_confirmation))
+        .route(
+            "/stk-push",
+            post(mpesa_handlers::initiate_stk_push).route_layer(from_fn_with_state(
+                RateLimit::new(&state, STK_PUSH),
+                rate_limit,
+            )),
+        )
         // ✅ NEW: Payment status check endpoint (POST for frontend)
         .route(
             "/check-payment-status",
             post(mpesa_handlers::check_payment_status),
         )
-        // B2C Routes
-        .route("/b2c/send", post(b2c_handlers::send_b2c_payment))
-        .route("/b2c/result", post(b2c_handlers::b2c_result_callback))
-        .route("/b2c/timeout", post(b2c_handlers::b2c_timeout_callback))
+        // Daraja callbacks
+        .merge(callback_routes(state))
         // Status (GET with query params)
         .route("/status", get(mpesa_handlers::check_transaction_status))
         .route("/transactions", get(mpesa_handlers::get_transactions))
@@ -35,6 +39,47 @@ pub fn mpesa_routes() -> Router<AppState> {
         .route("/simulate", post(mpesa_handlers::simulate_payment))
 }
 
+/// Everything Safaricom calls back into. `:token` is the secret path segment
+/// (MPESA_CALLBACK_TOKEN); the bare paths only pass when no token is set.
+fn callback_routes(state: AppState) -> Router<AppState> {
+    let mut router = Router::new();
+    for suffix in ["", "/:token"] {
+        router = router
+            .route(
+                &format!("/validation{}", suffix),
+                post(mpesa_handlers::mpesa_validation),
+            )
+            .route(
+                &format!("/confirmation{}", suffix),
+                post(mpesa_handlers::mpesa_confirmation),
+            )
+            // Keep old callback for backward compatibility
+            .route(
+                &format!("/callback{}", suffix),
+                post(mpesa_handlers::mpesa_confirmation),
+            )
+            // B2C callbacks (payouts are issued through /api/wallet/withdrawals)
+            .route(
+                &format!("/b2c/result{}", suffix),
+                post(b2c_handlers::b2c_result_callback),
+            )
+            .route(
+                &format!("/b2c/timeout{}", suffix),
+                post(b2c_handlers::b2c_timeout_callback),
+            )
+            // Transaction status query results for timed-out withdrawals
+            .route(
+                &format!("/b2c/status-result{}", suffix),
+                post(b2c_handlers::transaction_status_result_callback),
+            )
+            .route(
+                &format!("/b2c/status-timeout{}", suffix),
+                post(b2c_handlers::transaction_status_timeout_callback),
+            );
+    }
+    router.route_layer(from_fn_with_state(state, verify_daraja_callback))
+}
+
 async fn mpesa_health() -> Json<serde_json::Value> {
     Json(json!({
         "status": "ok",
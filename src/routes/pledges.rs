@@ -1,18 +1,24 @@
 use axum::{
-    routing::get,
+    middleware::from_fn_with_state,
+    routing::{get, post},
     Router,
 };
 
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::PLEDGES;
 use crate::state::AppState;
 use crate::handlers::pledges::{
-    get_pledges, create_pledge, get_pledge_stats, get_user_pledges, get_recent_pledges
+    get_pledges, create_pledge, accept_pledge, get_pledge_stats, get_user_pledges,
+    get_recent_pledges
 };
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: AppState) -> Router<AppState> {
+    let pledges = || from_fn_with_state(RateLimit::new(&state, PLEDGES), rate_limit);
+
     Router::new()
         // GET /api/pledges - Get all pledges with optional filtering
         // POST /api/pledges - Create a new pledge
-        .route("/", get(get_pledges).post(create_pledge))
+        .route("/", post(create_pledge).route_layer(pledges()).get(get_pledges))
 
         // GET /api/pledges/stats - Get pledge statistics for a specific match
         // Example: /api/pledges/stats?home_team=Manchester%20United&away_team=Liverpool
@@ -24,4 +30,7 @@ pub fn routes() -> Router<AppState> {
 
         // GET /api/pledges/recent - Get recent pledges (for social proof)
         .route("/recent", get(get_recent_pledges))
+
+        // POST /api/pledges/:id/accept - Atomically match an open pledge and create the bet
+        .route("/:id/accept", post(accept_pledge).route_layer(pledges()))
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+use axum::{routing::get, Router};
+
+use crate::handlers::reports;
+use crate::state::AppState;
+
+pub fn reports_routes() -> Router<AppState> {
+    Router::new()
+        // GET /api/reports/commission?from=...&to=... - Commission collected per day
+        .route("/commission", get(reports::get_commission_report))
+}
@@ -5,7 +5,7 @@ use axum::{
 
 use crate::handlers::user_profile::{
     get_user_profiles, get_user_profile_by_id, get_user_profile_by_phone,
-    save_user_profile, update_user_balance, get_user_stats,
+    save_user_profile, get_user_stats,
     get_recent_users, create_user_profile
 };
 use crate::state::AppState;
@@ -22,7 +22,6 @@ pub fn user_profile_routes() -> Router<AppState> {
         // POST routes
        // .route("/save-profile", post(save_user_profile))
         .route("/create_profile", post(create_user_profile))
-        .route("/update-balance", post(update_user_balance))
 
         // PUT routes (for updates)
         .route("/profiles/:id", put(save_user_profile))
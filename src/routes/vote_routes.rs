@@ -1,11 +1,17 @@
 use axum::{
+    middleware::from_fn_with_state,
     routing::{delete, get, post},
     Router,
 };
 
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::{COMMENTS, NOTIFICATIONS, VOTES};
 use crate::state::AppState;
 
-pub fn vote_routes() -> Router<AppState> {
+pub fn vote_routes(state: AppState) -> Router<AppState> {
+    let votes = || from_fn_with_state(RateLimit::new(&state, VOTES), rate_limit);
+    let comments = || from_fn_with_state(RateLimit::new(&state, COMMENTS), rate_limit);
+
     Router::new()
         // ========== 🆕 CHAT MEDIA UPLOAD ROUTE ==========
         .route(
@@ -13,11 +19,14 @@ pub fn vote_routes() -> Router<AppState> {
             post(crate::handlers::vote_handlers::upload_chat_media),
         )
         // ========== VOTE ROUTES ==========
-        .route("/vote", post(crate::handlers::vote_handlers::create_vote))
+        .route(
+            "/vote",
+            post(crate::handlers::vote_handlers::create_vote).route_layer(votes()),
+        )
         .route("/votes", get(crate::handlers::vote_handlers::get_votes))
         .route(
             "/votes/bulk",
-            post(crate::handlers::vote_handlers::bulk_create_votes),
+            post(crate::handlers::vote_handlers::bulk_create_votes).route_layer(votes()),
         )
         .route(
             "/votes/user/:voter_id",
@@ -35,6 +44,10 @@ pub fn vote_routes() -> Router<AppState> {
             "/votes/fixture/:fixture_id/selection",
             get(crate::handlers::vote_handlers::get_vote_counts_by_selection),
         )
+        .route(
+            "/fixture/:fixture_id/presence",
+            get(crate::handlers::vote_handlers::get_fixture_presence),
+        )
         .route(
             "/votes/fixture/:fixture_id/user/:voter_id",
             get(crate::handlers::vote_handlers::get_user_vote_for_fixture),
@@ -53,7 +66,10 @@ pub fn vote_routes() -> Router<AppState> {
             get(crate::handlers::vote_handlers::get_user_unread_counts),
         )
         // ========== LIKE ROUTES ==========
-        .route("/like", post(crate::handlers::vote_handlers::create_like))
+        .route(
+            "/like",
+            post(crate::handlers::vote_handlers::create_like).route_layer(votes()),
+        )
         .route(
             "/likes/fixture/:fixture_id",
             get(crate::handlers::vote_handlers::get_fixture_likes),
@@ -73,8 +89,9 @@ pub fn vote_routes() -> Router<AppState> {
         // ========== COMMENT ROUTES ==========
         .route(
             "/comment",
-            post(crate::handlers::vote_handlers::create_comment),
+            post(crate::handlers::vote_handlers::create_comment).route_layer(comments()),
         )
+        // Single-fixture socket; the caller comes from the access token
         .route(
             "/ws/comments",
             get(crate::handlers::ws_handler::ws_comments_handler),
@@ -101,7 +118,7 @@ pub fn vote_routes() -> Router<AppState> {
         )
         .route(
             "/comments/:comment_id/like",
-            post(crate::handlers::vote_handlers::like_comment),
+            post(crate::handlers::vote_handlers::like_comment).route_layer(votes()),
         )
         // ========== SUB-FIXTURE (PROP BETS) ROUTES ==========
         .route(
@@ -114,7 +131,8 @@ pub fn vote_routes() -> Router<AppState> {
         )
         .route(
             "/sub-fixture",
-            post(crate::handlers::sub_fixture_handler::submit_sub_fixture_vote),
+            post(crate::handlers::sub_fixture_handler::submit_sub_fixture_vote)
+                .route_layer(votes()),
         )
         .route(
             "/sub-fixture/:id/stats",
@@ -147,10 +165,17 @@ pub fn vote_routes() -> Router<AppState> {
 }
 
 pub fn ws_routes() -> Router<AppState> {
-    Router::new().route(
-        "/comments",
-        get(crate::handlers::ws_handler::ws_comments_handler),
-    )
+    Router::new()
+        // Single-fixture socket; the caller comes from the access token
+        .route(
+            "/comments",
+            get(crate::handlers::ws_handler::ws_comments_handler),
+        )
+        // One authenticated socket, subscribe/unsubscribe per fixture
+        .route(
+            "/live",
+            get(crate::handlers::ws_handler::ws_multiplex_handler),
+        )
 }
 
 pub fn vote_stats_routes() -> Router<AppState> {
@@ -224,26 +249,26 @@ pub fn vote_admin_routes() -> Router<AppState> {
     Router::new()
         // ========== ADMIN ROUTES ==========
         .route(
-            "/admin/votes/cleanup",
+            "/votes/cleanup",
             post(crate::handlers::vote_handlers::cleanup_old_votes),
         )
         .route(
-            "/admin/stats/overview",
+            "/stats/overview",
             get(crate::handlers::vote_handlers::get_overview_stats),
         )
         // ========== SUB-FIXTURE ADMIN ROUTES ==========
         .route(
-            "/admin/sub-fixtures",
+            "/sub-fixtures",
             post(crate::handlers::sub_fixture_handler::create_sub_fixture),
         )
         .route(
-            "/admin/sub-fixture/:id",
+            "/sub-fixture/:id",
             delete(crate::handlers::sub_fixture_handler::delete_sub_fixture),
         )
 }
 
 // ========== FCM NOTIFICATION ROUTES ==========
-pub fn notification_routes() -> Router<AppState> {
+pub fn notification_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route(
             "/register-token",
@@ -251,11 +276,9 @@ pub fn notification_routes() -> Router<AppState> {
         )
         .route(
             "/send",
-            post(crate::handlers::notification_handler::send_notification),
-        )
-        .route(
-            "/send-bulk",
-            post(crate::handlers::notification_handler::send_bulk_notifications),
+            post(crate::handlers::notification_handler::send_notification).route_layer(
+                from_fn_with_state(RateLimit::new(&state, NOTIFICATIONS), rate_limit),
+            ),
         )
         .route(
             "/user/:user_id",
@@ -0,0 +1,36 @@
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{get, post},
+    Router,
+};
+
+use crate::handlers::{wallet, withdrawals};
+use crate::middleware::rate_limit::{rate_limit, RateLimit};
+use crate::services::rate_limit::WITHDRAWALS;
+use crate::state::AppState;
+
+pub fn wallet_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        // GET /api/wallet/:user_id/ledger - Wallet statement backed by the ledger
+        .route("/:user_id/ledger", get(wallet::get_wallet_ledger))
+        // POST /api/wallet/withdrawals - Request a B2C withdrawal
+        // GET  /api/wallet/withdrawals - The caller's withdrawal history
+        .route(
+            "/withdrawals",
+            post(withdrawals::create_withdrawal)
+                .route_layer(from_fn_with_state(
+                    RateLimit::new(&state, WITHDRAWALS),
+                    rate_limit,
+                ))
+                .get(withdrawals::get_withdrawals),
+        )
+        // GET /api/wallet/withdrawals/:id - Withdrawal with status history
+        .route("/withdrawals/:id", get(withdrawals::get_withdrawal_by_id))
+        // GET /api/wallet/limits - Usage against deposit, stake and loss limits
+        // PUT /api/wallet/limits - Set one limit (raises apply after a delay)
+        .route("/limits", get(wallet::get_limits).put(wallet::set_limit))
+        // POST /api/wallet/cool-off - Take a break from deposits and betting
+        .route("/cool-off", post(wallet::start_cool_off))
+        // POST /api/wallet/self-exclusion - Self-exclude for months
+        .route("/self-exclusion", post(wallet::self_exclude))
+}
@@ -0,0 +1,380 @@
+// src/services/broadcaster.rs
+//
+// Fan-out of WebSocket frames to everyone watching a channel (one channel
+// per fixtureId). The in-memory backend only reaches sockets on this
+// instance; the Redis backend publishes every frame through Redis pub/sub
+// so sockets on all replicas receive it. Chosen with BROADCAST_BACKEND.
+//
+// Every frame is stamped with a per-channel `seq` that only ever grows, and
+// the last REPLAY_CAPACITY durable frames (comments, votes, match events)
+// are kept so a client that reconnects or falls behind can catch up. When
+// the frames it missed are no longer buffered it is told to resync instead.
+// A channel nobody has subscribed to or published on for CHANNEL_IDLE_TTL
+// is dropped along with its buffer.
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use futures_util::StreamExt;
+use redis::aio::MultiplexedConnection;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::errors::{AppError, Result};
+
+/// Frames a slow socket may fall behind by before it has to replay.
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Durable frames kept per channel for resume.
+pub const REPLAY_CAPACITY: usize = 256;
+
+/// Redis channels are `<prefix><channel>`; payloads are `<seq>:<frame>`.
+const REDIS_CHANNEL_PREFIX: &str = "fanclash:ws:";
+const REDIS_SEQ_PREFIX: &str = "fanclash:ws-seq:";
+
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// A channel without subscribers is dropped after this long without traffic.
+const CHANNEL_IDLE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How often idle channels are looked for.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Numbering and publishing in one step keeps Redis delivery in seq order.
+const PUBLISH_SCRIPT: &str = r"
+local seq = redis.call('INCR', KEYS[1])
+redis.call('PUBLISH', KEYS[2], seq .. ':' .. ARGV[1])
+return seq
+";
+
+/// A frame as delivered to sockets; `message` already carries `seq`.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub seq: u64,
+    pub message: String,
+}
+
+pub enum Replay {
+    /// Everything after the requested seq, oldest first.
+    Frames(Vec<Frame>),
+    /// The gap can't be filled; the client must reload and continue from `seq`.
+    Resync { seq: u64 },
+}
+
+#[async_trait]
+pub trait Broadcaster: Send + Sync {
+    /// Deliver `message` (a JSON object) to every subscriber of `channel`.
+    async fn publish(&self, channel: &str, message: String);
+
+    /// Receive what is published on `channel` from now on, together with
+    /// the seq of the last frame published before subscribing.
+    fn subscribe(&self, channel: &str) -> (broadcast::Receiver<Frame>, u64);
+
+    /// Durable frames published on `channel` after `after_seq`.
+    fn replay(&self, channel: &str, after_seq: u64) -> Replay;
+}
+
+/// Transient frames (typing, presence, pongs) are not worth replaying.
+fn is_durable(frame_type: &str) -> bool {
+    frame_type.starts_with("match.")
+        || matches!(
+            frame_type,
+            "comment.new"
+                | "chat.message"
+                | "fixture.comment"
+                | "comment.count"
+                | "message.delete"
+                | "vote.update"
+                | "like"
+                | "bet.settled"
+        )
+}
+
+// ========== PER-CHANNEL LOG ==========
+struct ChannelLog {
+    last_seq: u64,
+    /// Highest seq that can no longer be replayed.
+    floor: u64,
+    frames: VecDeque<Frame>,
+    /// Last subscribe, replay or frame.
+    last_active: Instant,
+}
+
+impl Default for ChannelLog {
+    fn default() -> Self {
+        ChannelLog {
+            last_seq: 0,
+            floor: 0,
+            frames: VecDeque::new(),
+            last_active: Instant::now(),
+        }
+    }
+}
+
+struct Channel {
+    tx: broadcast::Sender<Frame>,
+    log: Mutex<ChannelLog>,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Channel {
+            tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            log: Mutex::new(ChannelLog::default()),
+        }
+    }
+
+    /// Stamp, buffer and send one frame. Done under the log lock so sockets
+    /// and the buffer see frames in the same order.
+    fn record(&self, log: &mut ChannelLog, seq: u64, message: String) {
+        log.last_active = Instant::now();
+        if log.last_seq == 0 {
+            // Whatever came before this instance saw the channel is gone
+            log.floor = seq.saturating_sub(1);
+        }
+        log.last_seq = log.last_seq.max(seq);
+
+        let (message, durable) = match serde_json::from_str::<Value>(&message) {
+            Ok(Value::Object(mut object)) => {
+                let durable = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .is_some_and(is_durable);
+                object.insert("seq".to_string(), Value::from(seq));
+                (Value::Object(object).to_string(), durable)
+            }
+            _ => (message, false),
+        };
+        let frame = Frame { seq, message };
+
+        if durable {
+            log.frames.push_back(frame.clone());
+            while log.frames.len() > REPLAY_CAPACITY {
+                if let Some(evicted) = log.frames.pop_front() {
+                    log.floor = evicted.seq;
+                }
+            }
+        }
+        let _ = self.tx.send(frame);
+    }
+
+    fn is_idle(&self) -> bool {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        self.tx.receiver_count() == 0 && log.last_active.elapsed() >= CHANNEL_IDLE_TTL
+    }
+}
+
+// ========== IN MEMORY ==========
+/// Channels are only touched while their map entry is held, so a sweep
+/// can't drop one between a lookup and a subscribe or publish.
+pub struct InMemoryBroadcaster {
+    channels: DashMap<String, Channel>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl Default for InMemoryBroadcaster {
+    fn default() -> Self {
+        InMemoryBroadcaster {
+            channels: DashMap::new(),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl InMemoryBroadcaster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `f` on `channel`, creating it if needed.
+    fn with_channel<R>(&self, channel: &str, f: impl FnOnce(&Channel) -> R) -> R {
+        self.sweep_idle();
+        let entry = self
+            .channels
+            .entry(channel.to_string())
+            .or_insert_with(Channel::new);
+        f(entry.value())
+    }
+
+    /// Drop channels nobody has used for CHANNEL_IDLE_TTL, at most once per
+    /// SWEEP_INTERVAL.
+    fn sweep_idle(&self) {
+        {
+            let mut last_sweep = self.last_sweep.lock().unwrap_or_else(|e| e.into_inner());
+            if last_sweep.elapsed() < SWEEP_INTERVAL {
+                return;
+            }
+            *last_sweep = Instant::now();
+        }
+        let before = self.channels.len();
+        self.channels.retain(|_, channel| !channel.is_idle());
+        let dropped = before.saturating_sub(self.channels.len());
+        if dropped > 0 {
+            tracing::debug!("🧹 Dropped {} idle broadcast channels", dropped);
+        }
+    }
+
+    /// Record a frame numbered elsewhere (by Redis). Only channels someone
+    /// here has used recently are kept, so replicas don't buffer them all.
+    fn deliver(&self, channel: &str, seq: u64, message: String) {
+        self.sweep_idle();
+        if let Some(channel) = self.channels.get(channel) {
+            let mut log = channel.log.lock().unwrap_or_else(|e| e.into_inner());
+            channel.record(&mut log, seq, message);
+        }
+    }
+}
+
+#[async_trait]
+impl Broadcaster for InMemoryBroadcaster {
+    async fn publish(&self, channel: &str, message: String) {
+        self.with_channel(channel, |channel| {
+            let mut log = channel.log.lock().unwrap_or_else(|e| e.into_inner());
+            let seq = log.last_seq + 1;
+            channel.record(&mut log, seq, message);
+        })
+    }
+
+    fn subscribe(&self, channel: &str) -> (broadcast::Receiver<Frame>, u64) {
+        self.with_channel(channel, |channel| {
+            let mut log = channel.log.lock().unwrap_or_else(|e| e.into_inner());
+            log.last_active = Instant::now();
+            (channel.tx.subscribe(), log.last_seq)
+        })
+    }
+
+    fn replay(&self, channel: &str, after_seq: u64) -> Replay {
+        // Nothing is buffered for a channel we don't have
+        let Some(channel) = self.channels.get(channel) else {
+            return if after_seq == 0 {
+                Replay::Frames(Vec::new())
+            } else {
+                Replay::Resync { seq: 0 }
+            };
+        };
+        let mut log = channel.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.last_active = Instant::now();
+
+        // Ahead of us means our numbering restarted since the client's
+        if after_seq < log.floor || after_seq > log.last_seq {
+            return Replay::Resync { seq: log.last_seq };
+        }
+        Replay::Frames(
+            log.frames
+                .iter()
+                .filter(|frame| frame.seq > after_seq)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+// ========== REDIS PUB/SUB ==========
+/// Numbers and publishes frames through Redis and delivers what comes back,
+/// including our own frames, to local subscribers, so every replica sees
+/// the same stream with the same seqs.
+pub struct RedisBroadcaster {
+    local: Arc<InMemoryBroadcaster>,
+    publisher: MultiplexedConnection,
+    script: redis::Script,
+}
+
+impl RedisBroadcaster {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(|e| AppError::redis(e.to_string()))?;
+        let publisher = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .map_err(|e| AppError::redis(e.to_string()))?;
+
+        let local = Arc::new(InMemoryBroadcaster::new());
+        tokio::spawn(relay(client, local.clone()));
+
+        Ok(RedisBroadcaster {
+            local,
+            publisher,
+            script: redis::Script::new(PUBLISH_SCRIPT),
+        })
+    }
+}
+
+/// Forward every frame published by any replica to local subscribers,
+/// resubscribing whenever the connection drops.
+async fn relay(client: redis::Client, local: Arc<InMemoryBroadcaster>) {
+    let pattern = format!("{}*", REDIS_CHANNEL_PREFIX);
+    loop {
+        let pubsub = match client.get_async_connection().await {
+            Ok(connection) => {
+                let mut pubsub = connection.into_pubsub();
+                match pubsub.psubscribe(&pattern).await {
+                    Ok(()) => Some(pubsub),
+                    Err(e) => {
+                        tracing::error!("❌ Redis broadcast subscribe failed: {}", e);
+                        None
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!("❌ Redis broadcast connection failed: {}", e);
+                None
+            }
+        };
+
+        if let Some(mut pubsub) = pubsub {
+            tracing::info!("📡 Relaying WebSocket broadcasts from Redis");
+            let mut messages = pubsub.on_message();
+            while let Some(msg) = messages.next().await {
+                let Some(channel) = msg.get_channel_name().strip_prefix(REDIS_CHANNEL_PREFIX) else {
+                    continue;
+                };
+                let payload = match msg.get_payload::<String>() {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        tracing::warn!("⚠️ Dropping undecodable broadcast: {}", e);
+                        continue;
+                    }
+                };
+                match payload
+                    .split_once(':')
+                    .and_then(|(seq, frame)| Some((seq.parse::<u64>().ok()?, frame)))
+                {
+                    Some((seq, frame)) => local.deliver(channel, seq, frame.to_string()),
+                    None => tracing::warn!("⚠️ Dropping unsequenced broadcast on {}", channel),
+                }
+            }
+            tracing::warn!("⚠️ Redis broadcast subscription ended, reconnecting");
+        }
+
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+#[async_trait]
+impl Broadcaster for RedisBroadcaster {
+    async fn publish(&self, channel: &str, message: String) {
+        let mut connection = self.publisher.clone();
+        let result: redis::RedisResult<u64> = self
+            .script
+            .key(format!("{}{}", REDIS_SEQ_PREFIX, channel))
+            .key(format!("{}{}", REDIS_CHANNEL_PREFIX, channel))
+            .arg(&message)
+            .invoke_async(&mut connection)
+            .await;
+
+        // Numbering it locally would clash with Redis' seqs once it is back;
+        // clients pick the frame up through resume or resync instead
+        if let Err(e) = result {
+            tracing::error!("❌ Redis publish to {} failed, frame dropped: {}", channel, e);
+        }
+    }
+
+    fn subscribe(&self, channel: &str) -> (broadcast::Receiver<Frame>, u64) {
+        self.local.subscribe(channel)
+    }
+
+    fn replay(&self, channel: &str, after_seq: u64) -> Replay {
+        self.local.replay(channel, after_seq)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::errors::{AppError, Result};
 use reqwest::multipart;
 use serde_json::Value;
+use sha1::{Digest, Sha1};
 use std::env;
 
 #[derive(Clone)]
@@ -48,6 +49,18 @@ impl CloudinaryService {
         })
     }
 
+    /// Placeholder credentials for tests that never upload.
+    #[cfg(test)]
+    pub(crate) fn for_tests() -> Self {
+        Self {
+            cloud_name: "test".to_string(),
+            api_key: "test".to_string(),
+            api_secret: "test".to_string(),
+            upload_preset: "test".to_string(),
+            video_upload_preset: "test".to_string(),
+        }
+    }
+
     // ============================================================================
     // METHODS FOR CHAT MEDIA (Image & Video)
     // ============================================================================
@@ -384,6 +397,119 @@ impl CloudinaryService {
         Ok((secure_url, public_id))
     }
 
+    // ============================================================================
+    // AUTHENTICATED ASSETS (KYC documents)
+    // ============================================================================
+
+    /// API request signature: SHA-1 of the `&`-joined, sorted `key=value`
+    /// pairs followed by the API secret.
+    fn sign_params(&self, params: &[(&str, String)]) -> String {
+        let mut pairs: Vec<String> = params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        pairs.sort();
+        let mut hasher = Sha1::new();
+        hasher.update(pairs.join("&"));
+        hasher.update(&self.api_secret);
+        hex::encode(hasher.finalize())
+    }
+
+    /// Upload an image as an `authenticated` asset: it has no public URL and
+    /// can only be fetched through `private_download_url`. Returns the
+    /// public id and the stored format.
+    pub async fn upload_authenticated_image(
+        &self,
+        image_data: &[u8],
+        folder: &str,
+        public_id: &str,
+    ) -> Result<(String, String)> {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let signed = [
+            ("folder", folder.to_string()),
+            ("public_id", public_id.to_string()),
+            ("timestamp", timestamp.clone()),
+            ("type", "authenticated".to_string()),
+        ];
+        let signature = self.sign_params(&signed);
+
+        let upload_url = format!(
+            "https://api.cloudinary.com/v1_1/{}/image/upload",
+            self.cloud_name
+        );
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let mut form = multipart::Form::new()
+            .text("api_key", self.api_key.clone())
+            .text("signature", signature);
+        for (key, value) in signed {
+            form = form.text(key, value);
+        }
+        form = form.part(
+            "file",
+            multipart::Part::bytes(image_data.to_vec()).file_name("document"),
+        );
+
+        let response = client.post(&upload_url).multipart(form).send().await?;
+        let result: Value = response.json().await?;
+
+        if let Some(error) = result.get("error") {
+            let error_msg = error["message"].as_str().unwrap_or("Unknown error");
+            return Err(AppError::CloudinaryError(format!(
+                "Authenticated upload failed: {}",
+                error_msg
+            )));
+        }
+
+        let public_id = result["public_id"]
+            .as_str()
+            .ok_or_else(|| AppError::CloudinaryError("No public ID".into()))?
+            .to_string();
+        let format = result["format"]
+            .as_str()
+            .ok_or_else(|| AppError::CloudinaryError("No format".into()))?
+            .to_string();
+
+        println!("🔒 Authenticated upload successful: {}", public_id);
+        Ok((public_id, format))
+    }
+
+    /// Signed download link for a non-public image that stops working at
+    /// `expires_at` (unix seconds). `delivery_type` is how it was uploaded
+    /// ("authenticated", "private" or "upload").
+    pub fn private_download_url(
+        &self,
+        public_id: &str,
+        format: &str,
+        delivery_type: &str,
+        expires_at: i64,
+    ) -> Result<String> {
+        let params = [
+            ("expires_at", expires_at.to_string()),
+            ("format", format.to_string()),
+            ("public_id", public_id.to_string()),
+            ("timestamp", chrono::Utc::now().timestamp().to_string()),
+            ("type", delivery_type.to_string()),
+        ];
+        let signature = self.sign_params(&params);
+
+        let url = reqwest::Url::parse_with_params(
+            &format!(
+                "https://api.cloudinary.com/v1_1/{}/image/download",
+                self.cloud_name
+            ),
+            params
+                .iter()
+                .map(|(key, value)| (*key, value.as_str()))
+                .chain([("api_key", self.api_key.as_str()), ("signature", signature.as_str())]),
+        )
+        .map_err(|e| AppError::CloudinaryError(format!("Invalid download URL: {}", e)))?;
+        Ok(url.to_string())
+    }
+
     /// Delete image from Cloudinary
     pub async fn delete_image(&self, public_id: &str) -> Result<()> {
         let timestamp = chrono::Utc::now().timestamp().to_string();
@@ -0,0 +1,398 @@
+// src/services/escrow.rs
+//
+// Escrow for the P2P betting flow. A pledge reserves the starter's stake
+// (wallet -> escrow), accepting it as a bet locks that hold and the
+// finisher's stake, and the bet then either settles (both escrows -> winner
+// wallet, less the house commission) or is cancelled (each escrow -> its
+// owner's wallet). Every move
+// goes through the ledger; `escrow_holds` tracks where each stake stands.
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime as BsonDateTime},
+    Collection,
+};
+
+use crate::config::CommissionRule;
+use crate::errors::{AppError, Result};
+use crate::models::escrow::{
+    EscrowHold, HOLD_HELD, HOLD_LOCKED, HOLD_PENDING, HOLD_RELEASED, HOLD_SETTLED,
+};
+use crate::models::ledger::{EntryKind, LedgerPosting, HOUSE_ACCOUNT};
+use crate::models::money::Money;
+use crate::services::ledger::{self, NewEntry};
+use crate::state::AppState;
+
+const HOLDS: &str = "escrow_holds";
+
+/// What a settled bet paid: `paid` to the winner, `commission` to the house.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SettledPayout {
+    pub paid: Money,
+    pub commission: Money,
+}
+
+fn holds(state: &AppState) -> Collection<EscrowHold> {
+    state.db.collection(HOLDS)
+}
+
+/// Reserve the starter's stake when a pledge is created.
+pub async fn hold_for_pledge(
+    state: &AppState,
+    pledge_id: &str,
+    user_id: &str,
+    amount: Money,
+) -> Result<EscrowHold> {
+    place_hold(
+        state,
+        EscrowHold::pledge_hold_id(pledge_id),
+        user_id,
+        amount,
+        pledge_id,
+        None,
+        HOLD_HELD,
+    )
+    .await
+}
+
+/// Turn the pledge hold into a locked stake and lock the finisher's stake.
+/// Fails without side effects if either party cannot cover their stake.
+pub async fn lock_for_bet(
+    state: &AppState,
+    pledge_id: &str,
+    bet_id: &str,
+    starter_id: &str,
+    starter_amount: Money,
+    finisher_id: &str,
+    finisher_amount: Money,
+) -> Result<()> {
+    let collection = holds(state);
+    let pledge_hold_id = EscrowHold::pledge_hold_id(pledge_id);
+
+    let starter_hold = match collection
+        .find_one(doc! { "hold_id": &pledge_hold_id })
+        .await?
+    {
+        Some(hold) => {
+            if hold.user_id != starter_id {
+                return Err(AppError::invalid_data("Pledge hold belongs to another user"));
+            }
+            if hold.amount != starter_amount {
+                return Err(AppError::invalid_data(format!(
+                    "starter_amount must match the pledged amount ({})",
+                    hold.amount
+                )));
+            }
+            let claimed = collection
+                .update_one(
+                    doc! { "hold_id": &pledge_hold_id, "status": HOLD_HELD },
+                    doc! { "$set": {
+                        "status": HOLD_LOCKED,
+                        "bet_id": bet_id,
+                        "updated_at": BsonDateTime::now(),
+                    }},
+                )
+                .await?;
+            if claimed.modified_count == 0 {
+                return Err(AppError::invalid_data("Pledge stake is no longer available"));
+            }
+            hold
+        }
+        // Pledges created before escrow existed have no hold yet
+        None => {
+            place_hold(
+                state,
+                pledge_hold_id.clone(),
+                starter_id,
+                starter_amount,
+                pledge_id,
+                Some(bet_id.to_string()),
+                HOLD_LOCKED,
+            )
+            .await?
+        }
+    };
+
+    if let Err(e) = place_hold(
+        state,
+        EscrowHold::finisher_hold_id(bet_id),
+        finisher_id,
+        finisher_amount,
+        pledge_id,
+        Some(bet_id.to_string()),
+        HOLD_LOCKED,
+    )
+    .await
+    {
+        // Put the starter's stake back on the open pledge
+        unlock_pledge_hold(&collection, &starter_hold.hold_id).await?;
+        return Err(e);
+    }
+
+    tracing::info!("🔒 Stakes locked for bet {} (pledge {})", bet_id, pledge_id);
+    Ok(())
+}
+
+/// Undo `lock_for_bet` when the bet could not be stored: the finisher gets
+/// their stake back and the starter's stake returns to the open pledge.
+pub async fn unlock_for_bet(state: &AppState, pledge_id: &str, bet_id: &str) -> Result<()> {
+    release_hold(state, &EscrowHold::finisher_hold_id(bet_id), HOLD_LOCKED).await?;
+    unlock_pledge_hold(&holds(state), &EscrowHold::pledge_hold_id(pledge_id)).await
+}
+
+async fn unlock_pledge_hold(collection: &Collection<EscrowHold>, hold_id: &str) -> Result<()> {
+    collection
+        .update_one(
+            doc! { "hold_id": hold_id, "status": HOLD_LOCKED },
+            doc! {
+                "$set": { "status": HOLD_HELD, "updated_at": BsonDateTime::now() },
+                "$unset": { "bet_id": "" },
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Return an open pledge's reserved stake to the starter.
+pub async fn release_pledge_hold(state: &AppState, pledge_id: &str) -> Result<bool> {
+    release_hold(state, &EscrowHold::pledge_hold_id(pledge_id), HOLD_HELD).await
+}
+
+/// Whether any stake on the bet ever went through escrow. Bets placed
+/// before escrow existed have none.
+pub async fn bet_has_holds(state: &AppState, bet_id: &str) -> Result<bool> {
+    Ok(holds(state)
+        .find_one(doc! { "bet_id": bet_id })
+        .await?
+        .is_some())
+}
+
+/// Cancel a bet: every locked stake goes back to its owner.
+pub async fn release_bet(state: &AppState, bet_id: &str) -> Result<usize> {
+    let locked: Vec<EscrowHold> = holds(state)
+        .find(doc! { "bet_id": bet_id, "status": HOLD_LOCKED })
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut released = 0;
+    for hold in locked {
+        if release_hold(state, &hold.hold_id, HOLD_LOCKED).await? {
+            released += 1;
+        }
+    }
+    Ok(released)
+}
+
+/// Pay every locked stake on a bet to the winner, less the commission under
+/// `rule`. Returns zeroes if the bet had already been settled or cancelled.
+pub async fn settle_bet(
+    state: &AppState,
+    bet_id: &str,
+    winner_id: &str,
+    rule: CommissionRule,
+) -> Result<SettledPayout> {
+    let collection = holds(state);
+    let locked: Vec<EscrowHold> = collection
+        .find(doc! { "bet_id": bet_id, "status": HOLD_LOCKED })
+        .await?
+        .try_collect()
+        .await?;
+
+    // Claim each stake first so a concurrent cancel cannot also refund it
+    let mut claimed: Vec<EscrowHold> = Vec::new();
+    for hold in locked {
+        let result = collection
+            .update_one(
+                doc! { "hold_id": &hold.hold_id, "status": HOLD_LOCKED },
+                doc! { "$set": { "status": HOLD_SETTLED, "updated_at": BsonDateTime::now() } },
+            )
+            .await?;
+        if result.modified_count > 0 {
+            claimed.push(hold);
+        }
+    }
+
+    if claimed.is_empty() {
+        return Ok(SettledPayout::default());
+    }
+
+    let total_pot: Money = claimed.iter().map(|h| h.amount).sum();
+    let winnings: Money = claimed
+        .iter()
+        .filter(|h| h.user_id != winner_id)
+        .map(|h| h.amount)
+        .sum();
+    let commission = rule.fee_for(total_pot, winnings);
+    let paid = total_pot - commission;
+
+    let mut postings: Vec<LedgerPosting> = claimed
+        .iter()
+        .map(|h| LedgerPosting::user_escrow(&h.user_id, -h.amount))
+        .collect();
+    postings.push(LedgerPosting::user_wallet(winner_id, paid));
+    if commission.is_positive() {
+        postings.push(LedgerPosting::system(HOUSE_ACCOUNT, commission));
+    }
+
+    let posted = ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("bet:{}:payout", bet_id),
+            kind: EntryKind::Payout,
+            reference: Some(bet_id.to_string()),
+            description: format!("Bet winnings ({})", bet_id),
+            postings,
+        },
+    )
+    .await;
+
+    if let Err(e) = posted {
+        revert_status(&collection, &claimed, HOLD_SETTLED, HOLD_LOCKED).await;
+        return Err(e);
+    }
+
+    tracing::info!(
+        "🏆 Bet {} settled: {} paid to {} ({} commission)",
+        bet_id,
+        paid,
+        winner_id,
+        commission
+    );
+    Ok(SettledPayout { paid, commission })
+}
+
+async fn place_hold(
+    state: &AppState,
+    hold_id: String,
+    user_id: &str,
+    amount: Money,
+    pledge_id: &str,
+    bet_id: Option<String>,
+    status: &str,
+) -> Result<EscrowHold> {
+    if !amount.is_positive() {
+        return Err(AppError::invalid_data("Stake must be greater than 0"));
+    }
+
+    // Recorded before the stake moves, so a stake in escrow always has a
+    // hold that can release it
+    let collection = holds(state);
+    let now = BsonDateTime::now();
+    let mut hold = EscrowHold {
+        id: Some(ObjectId::new()),
+        hold_id,
+        user_id: user_id.to_string(),
+        amount,
+        pledge_id: pledge_id.to_string(),
+        bet_id,
+        status: HOLD_PENDING.to_string(),
+        created_at: now,
+        updated_at: now,
+    };
+    collection.insert_one(&hold).await?;
+
+    let posted = ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("escrow:{}:hold", hold.hold_id),
+            kind: EntryKind::Stake,
+            reference: Some(pledge_id.to_string()),
+            description: match &hold.bet_id {
+                Some(bet_id) => format!("Stake locked on bet {}", bet_id),
+                None => format!("Stake reserved for pledge {}", pledge_id),
+            },
+            postings: vec![
+                LedgerPosting::user_wallet(user_id, -amount),
+                LedgerPosting::user_escrow(user_id, amount),
+            ],
+        },
+    )
+    .await;
+
+    if let Err(e) = posted {
+        if let Err(delete_error) = collection
+            .delete_one(doc! { "hold_id": &hold.hold_id, "status": HOLD_PENDING })
+            .await
+        {
+            tracing::error!(
+                "❌ Failed to discard pending escrow hold {}: {}",
+                hold.hold_id,
+                delete_error
+            );
+        }
+        return Err(e);
+    }
+
+    let activated = collection
+        .update_one(
+            doc! { "hold_id": &hold.hold_id, "status": HOLD_PENDING },
+            doc! { "$set": { "status": status, "updated_at": BsonDateTime::now() } },
+        )
+        .await;
+
+    if let Err(e) = activated {
+        // The stake moved but the hold can't be used; hand it back
+        release_hold(state, &hold.hold_id, HOLD_PENDING).await?;
+        return Err(e.into());
+    }
+
+    hold.status = status.to_string();
+    Ok(hold)
+}
+
+/// Move a hold in `from_status` back to its owner's wallet.
+async fn release_hold(state: &AppState, hold_id: &str, from_status: &str) -> Result<bool> {
+    let collection = holds(state);
+    let hold = collection
+        .find_one_and_update(
+            doc! { "hold_id": hold_id, "status": from_status },
+            doc! { "$set": { "status": HOLD_RELEASED, "updated_at": BsonDateTime::now() } },
+        )
+        .await?;
+
+    let Some(hold) = hold else {
+        return Ok(false);
+    };
+
+    let posted = ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("escrow:{}:release", hold_id),
+            kind: EntryKind::Refund,
+            reference: Some(hold.bet_id.clone().unwrap_or_else(|| hold.pledge_id.clone())),
+            description: "Stake released".to_string(),
+            postings: vec![
+                LedgerPosting::user_escrow(&hold.user_id, -hold.amount),
+                LedgerPosting::user_wallet(&hold.user_id, hold.amount),
+            ],
+        },
+    )
+    .await;
+
+    if let Err(e) = posted {
+        revert_status(&collection, std::slice::from_ref(&hold), HOLD_RELEASED, from_status).await;
+        return Err(e);
+    }
+
+    tracing::info!("🔓 Released {} to {} ({})", hold.amount, hold.user_id, hold_id);
+    Ok(true)
+}
+
+async fn revert_status(
+    collection: &Collection<EscrowHold>,
+    holds: &[EscrowHold],
+    from: &str,
+    to: &str,
+) {
+    for hold in holds {
+        if let Err(e) = collection
+            .update_one(
+                doc! { "hold_id": &hold.hold_id, "status": from },
+                doc! { "$set": { "status": to, "updated_at": BsonDateTime::now() } },
+            )
+            .await
+        {
+            tracing::error!("❌ Failed to revert escrow hold {}: {}", hold.hold_id, e);
+        }
+    }
+}
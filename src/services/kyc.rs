@@ -0,0 +1,221 @@
+// src/services/kyc.rs
+//
+// Identity and age verification. A user submits an ID number, date of birth
+// and a photo of the document; an admin reviews it. Money features that put
+// funds at risk or move them out (pledges, bets, B2C withdrawals) require a
+// verified adult; voting and chat stay open to everyone.
+use chrono::{NaiveDate, Utc};
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, DateTime},
+    options::{FindOptions, ReturnDocument},
+    Collection,
+};
+use uuid::Uuid;
+
+use crate::errors::{AppError, Result};
+use crate::models::kyc::{KycRecord, KycReviewItem, KycReviewRequest, KycStatus, MINIMUM_AGE};
+use crate::models::user_profile::UserProfile;
+use crate::state::AppState;
+
+const MAX_REVIEW_LIST: i64 = 200;
+
+/// Cloudinary delivery type of uploaded ID documents.
+const DOCUMENT_TYPE: &str = "authenticated";
+
+/// How long an admin's link to an ID document works.
+pub const DOCUMENT_URL_TTL_SECS: i64 = 10 * 60;
+
+fn records(state: &AppState) -> Collection<KycRecord> {
+    state.db.collection("kyc_records")
+}
+
+pub fn age_on(date_of_birth: NaiveDate, today: NaiveDate) -> u32 {
+    today.years_since(date_of_birth).unwrap_or(0)
+}
+
+/// The profile carries a copy of the status so clients can show it.
+async fn mirror_status(state: &AppState, user_id: &str, status: KycStatus) -> Result<()> {
+    state
+        .db
+        .collection::<UserProfile>("user_profiles")
+        .update_one(
+            doc! { "user_id": user_id },
+            doc! { "$set": { "kyc_status": status.as_str() } },
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn get(state: &AppState, user_id: &str) -> Result<Option<KycRecord>> {
+    Ok(records(state).find_one(doc! { "user_id": user_id }).await?)
+}
+
+/// Store a submission and its document for review. Replaces a rejected one.
+pub async fn submit(
+    state: &AppState,
+    user_id: &str,
+    id_number: &str,
+    date_of_birth: NaiveDate,
+    document: &[u8],
+) -> Result<KycRecord> {
+    let id_number = id_number.trim().to_uppercase();
+    if !(5..=20).contains(&id_number.len()) || !id_number.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(AppError::invalid_data(
+            "ID number must be 5 to 20 letters or digits",
+        ));
+    }
+
+    let today = Utc::now().date_naive();
+    if date_of_birth >= today {
+        return Err(AppError::invalid_data("Date of birth must be in the past"));
+    }
+    if age_on(date_of_birth, today) < MINIMUM_AGE {
+        return Err(AppError::KycRequired(format!(
+            "You must be at least {} to use money features",
+            MINIMUM_AGE
+        )));
+    }
+
+    let collection = records(state);
+    match get(state, user_id).await?.map(|record| record.status) {
+        Some(KycStatus::Verified) => {
+            return Err(AppError::invalid_data("Your identity is already verified"))
+        }
+        Some(KycStatus::Pending) => {
+            return Err(AppError::invalid_data("Your documents are already under review"))
+        }
+        _ => {}
+    }
+
+    // One identity per account
+    if collection
+        .find_one(doc! { "id_number": &id_number, "user_id": { "$ne": user_id } })
+        .await?
+        .is_some()
+    {
+        return Err(AppError::invalid_data(
+            "This ID number is registered to another account",
+        ));
+    }
+
+    // ID photos are authenticated assets; admins get expiring links to them
+    let folder = format!("fanclash/kyc/{}", user_id);
+    let public_id = format!("kyc_{}_{}", user_id, Uuid::new_v4());
+    let (document_public_id, document_format) = state
+        .cloudinary
+        .upload_authenticated_image(document, &folder, &public_id)
+        .await?;
+
+    let record = KycRecord {
+        user_id: user_id.to_string(),
+        id_number,
+        date_of_birth,
+        document_public_id,
+        document_format,
+        document_type: DOCUMENT_TYPE.to_string(),
+        status: KycStatus::Pending,
+        rejection_reason: None,
+        reviewed_by: None,
+        reviewed_at: None,
+        submitted_at: DateTime::now(),
+    };
+    collection
+        .replace_one(doc! { "user_id": user_id }, &record)
+        .upsert(true)
+        .await?;
+    mirror_status(state, user_id, KycStatus::Pending).await?;
+
+    println!("🪪 KYC submitted by user {}", user_id);
+    Ok(record)
+}
+
+/// Submissions for admins, oldest first so the queue is worked in order.
+pub async fn list(state: &AppState, status: Option<KycStatus>, limit: Option<i64>) -> Result<Vec<KycRecord>> {
+    let filter = match status {
+        Some(status) => doc! { "status": status.as_str() },
+        None => doc! {},
+    };
+    let options = FindOptions::builder()
+        .sort(doc! { "submitted_at": 1 })
+        .limit(limit.unwrap_or(50).clamp(1, MAX_REVIEW_LIST))
+        .build();
+
+    let cursor = records(state).find(filter).with_options(options).await?;
+    Ok(cursor.try_collect().await?)
+}
+
+/// Attach a short-lived signed link to the document for an admin.
+pub fn review_item(state: &AppState, record: KycRecord) -> Result<KycReviewItem> {
+    let expires_at = Utc::now().timestamp() + DOCUMENT_URL_TTL_SECS;
+    let document_url = state.cloudinary.private_download_url(
+        &record.document_public_id,
+        &record.document_format,
+        &record.document_type,
+        expires_at,
+    )?;
+    Ok(KycReviewItem {
+        record,
+        document_url,
+        document_url_expires_at: expires_at,
+    })
+}
+
+/// Approve or reject a pending submission.
+pub async fn review(
+    state: &AppState,
+    user_id: &str,
+    reviewer_id: &str,
+    request: &KycReviewRequest,
+) -> Result<KycRecord> {
+    let reason = request
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty());
+    if !request.approve && reason.is_none() {
+        return Err(AppError::missing_field("reason"));
+    }
+
+    let status = if request.approve {
+        KycStatus::Verified
+    } else {
+        KycStatus::Rejected
+    };
+    let record = records(state)
+        .find_one_and_update(
+            doc! { "user_id": user_id, "status": KycStatus::Pending.as_str() },
+            doc! { "$set": {
+                "status": status.as_str(),
+                "rejection_reason": if request.approve { None } else { reason },
+                "reviewed_by": reviewer_id,
+                "reviewed_at": DateTime::now(),
+            }},
+        )
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(AppError::DocumentNotFound)?;
+    mirror_status(state, user_id, status).await?;
+
+    println!("🪪 KYC for user {} {} by {}", user_id, status.as_str(), reviewer_id);
+    Ok(record)
+}
+
+/// Gate for pledges, bets and withdrawals: a verified identity of a user
+/// who is an adult today.
+pub async fn ensure_verified(state: &AppState, user_id: &str) -> Result<()> {
+    let record = get(state, user_id).await?;
+    let Some(record) = record.filter(|record| record.status == KycStatus::Verified) else {
+        return Err(AppError::KycRequired(
+            "Verify your identity to use money features".to_string(),
+        ));
+    };
+    if age_on(record.date_of_birth, Utc::now().date_naive()) < MINIMUM_AGE {
+        return Err(AppError::KycRequired(format!(
+            "You must be at least {} to use money features",
+            MINIMUM_AGE
+        )));
+    }
+    Ok(())
+}
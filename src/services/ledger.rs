@@ -0,0 +1,375 @@
+// src/services/ledger.rs
+//
+// Double-entry wallet ledger. Every money movement is written as one
+// immutable `ledger_entries` document whose postings sum to zero, and the
+// per-account balances in `ledger_accounts` are only ever changed here.
+// `users.balance` / `user_profiles.balance` are read-only projections that
+// get refreshed after each posting. Amounts are integer cents (`Money`).
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime as BsonDateTime},
+    error::{ErrorKind, WriteFailure},
+    options::ReturnDocument,
+    Collection,
+};
+
+use crate::errors::{AppError, Result};
+use crate::models::ledger::{EntryKind, LedgerAccount, LedgerEntry, LedgerPosting};
+use crate::models::money::Money;
+use crate::state::AppState;
+
+const ACCOUNTS: &str = "ledger_accounts";
+const ENTRIES: &str = "ledger_entries";
+
+/// A movement to be journaled. `entry_id` is the idempotency key.
+pub struct NewEntry {
+    pub entry_id: String,
+    pub kind: EntryKind,
+    pub reference: Option<String>,
+    pub description: String,
+    pub postings: Vec<LedgerPosting>,
+}
+
+/// Posts a balanced entry.
+///
+/// Returns `Ok(true)` when the entry was posted and `Ok(false)` when an
+/// entry with the same `entry_id` already exists. Fails with
+/// `AppError::InsufficientFunds` (and leaves no trace) when a non-system
+/// account would go negative.
+///
+/// Each posting tags its account with the entry id until the entry is
+/// marked posted, so applying it twice is a no-op. An entry left "pending"
+/// by a crash is resumed by the next caller with the same `entry_id` once
+/// it is older than STALE_PENDING_SECS; a younger one is still in flight
+/// and the caller is told to retry.
+pub async fn post_entry(state: &AppState, entry: NewEntry) -> Result<bool> {
+    validate_postings(&entry.postings)?;
+
+    let entries: Collection<LedgerEntry> = state.db.collection(ENTRIES);
+
+    let entry_oid = ObjectId::new();
+    let journal = LedgerEntry {
+        id: Some(entry_oid),
+        entry_id: entry.entry_id.clone(),
+        kind: entry.kind,
+        status: "pending".to_string(),
+        reference: entry.reference,
+        description: entry.description,
+        postings: entry.postings.clone(),
+        created_at: BsonDateTime::now(),
+    };
+
+    // The unique index on entry_id is what makes retries safe.
+    match entries.insert_one(&journal).await {
+        Ok(_) => apply_entry(state, journal).await,
+        Err(e) if is_duplicate_key(&e) => resume_entry(state, &entry.entry_id).await,
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Seconds after which a "pending" entry is taken to be abandoned.
+const STALE_PENDING_SECS: i64 = 60;
+
+/// An entry with this `entry_id` exists: done, in flight, or abandoned.
+async fn resume_entry(state: &AppState, entry_id: &str) -> Result<bool> {
+    let entries: Collection<LedgerEntry> = state.db.collection(ENTRIES);
+    let stale_before = BsonDateTime::from_millis(
+        BsonDateTime::now().timestamp_millis() - STALE_PENDING_SECS * 1000,
+    );
+
+    // Claiming it keeps two retries from repairing it at once
+    let claimed = entries
+        .find_one_and_update(
+            doc! {
+                "entry_id": entry_id,
+                "status": "pending",
+                "created_at": { "$lt": stale_before },
+                "$or": [
+                    { "claimed_at": { "$exists": false } },
+                    { "claimed_at": { "$lt": stale_before } },
+                ],
+            },
+            doc! { "$set": { "claimed_at": BsonDateTime::now() } },
+        )
+        .await?;
+
+    if let Some(stuck) = claimed {
+        tracing::warn!("🩹 Resuming ledger entry {} left pending", entry_id);
+        return apply_entry(state, stuck).await;
+    }
+
+    match entries.find_one(doc! { "entry_id": entry_id }).await? {
+        Some(existing) if existing.status == "posted" => {
+            tracing::info!("📒 Ledger entry {} already posted, skipping", entry_id);
+            Ok(false)
+        }
+        // Rolled back and removed since our insert failed: start over
+        None => Err(AppError::ServiceUnavailable(format!(
+            "Ledger entry {} changed concurrently, retry",
+            entry_id
+        ))),
+        Some(_) => Err(AppError::ServiceUnavailable(format!(
+            "Ledger entry {} is still being posted, retry",
+            entry_id
+        ))),
+    }
+}
+
+/// Apply a journaled entry's postings and mark it posted.
+async fn apply_entry(state: &AppState, journal: LedgerEntry) -> Result<bool> {
+    let entries: Collection<LedgerEntry> = state.db.collection(ENTRIES);
+    let accounts: Collection<LedgerAccount> = state.db.collection(ACCOUNTS);
+    let entry_id = journal.entry_id.clone();
+    let entry_oid = journal
+        .id
+        .ok_or_else(|| AppError::internal_server_error("Ledger entry without _id"))?;
+
+    // Debits first so a failed funds check never leaves a half-applied credit.
+    let mut ordered = journal.postings;
+    ordered.sort_by_key(|p| p.amount);
+
+    let mut applied: Vec<LedgerPosting> = Vec::with_capacity(ordered.len());
+    for posting in ordered {
+        match apply_posting(&accounts, &entry_id, &posting).await {
+            Ok(Some(balance_after)) => applied.push(LedgerPosting {
+                balance_after: Some(balance_after),
+                ..posting
+            }),
+            Ok(None) => {
+                rollback(&accounts, &entry_id, &applied).await;
+                entries.delete_one(doc! { "_id": entry_oid }).await?;
+                tracing::warn!(
+                    "❌ Ledger entry {} rejected: insufficient funds on {}",
+                    entry_id,
+                    posting.account_id
+                );
+                return Err(AppError::InsufficientFunds);
+            }
+            Err(e) => {
+                rollback(&accounts, &entry_id, &applied).await;
+                entries.delete_one(doc! { "_id": entry_oid }).await?;
+                return Err(e);
+            }
+        }
+    }
+
+    let postings_bson = mongodb::bson::to_bson(&applied)
+        .map_err(|e| AppError::internal_server_error(format!("Ledger serialization: {}", e)))?;
+    entries
+        .update_one(
+            doc! { "_id": entry_oid },
+            doc! { "$set": { "postings": postings_bson, "status": "posted" } },
+        )
+        .await?;
+
+    // Only once the entry is posted may its accounts forget it
+    let account_ids: Vec<&str> = applied.iter().map(|p| p.account_id.as_str()).collect();
+    if let Err(e) = accounts
+        .update_many(
+            doc! { "account_id": { "$in": account_ids } },
+            doc! { "$pull": { "pending_entries": &entry_id } },
+        )
+        .await
+    {
+        tracing::error!("❌ Failed to clear pending marker of {}: {}", entry_id, e);
+    }
+
+    for posting in &applied {
+        if let (Some(owner_id), Some(balance)) = (&posting.owner_id, posting.balance_after) {
+            if posting.account_id == LedgerAccount::wallet_id(owner_id) {
+                sync_balance_projection(state, owner_id, balance).await;
+            }
+        }
+    }
+
+    tracing::info!(
+        "📒 Posted {} entry {} ({} legs)",
+        journal.kind.as_str(),
+        entry_id,
+        applied.len()
+    );
+    Ok(true)
+}
+
+/// True once the entry with this `entry_id` has been posted.
+pub async fn entry_posted(state: &AppState, entry_id: &str) -> Result<bool> {
+    let entries: Collection<LedgerEntry> = state.db.collection(ENTRIES);
+    Ok(entries
+        .find_one(doc! { "entry_id": entry_id, "status": "posted" })
+        .await?
+        .is_some())
+}
+
+/// Current wallet balance for a user (0 when the user has no account yet).
+pub async fn wallet_balance(state: &AppState, user_id: &str) -> Result<Money> {
+    account_balance(state, &LedgerAccount::wallet_id(user_id)).await
+}
+
+pub async fn account_balance(state: &AppState, account_id: &str) -> Result<Money> {
+    let accounts: Collection<LedgerAccount> = state.db.collection(ACCOUNTS);
+    Ok(accounts
+        .find_one(doc! { "account_id": account_id })
+        .await?
+        .map(|a| a.balance)
+        .unwrap_or(Money::ZERO))
+}
+
+/// Refresh the denormalized `balance` fields on `users` and `user_profiles`.
+/// Failures are logged only; the ledger stays the source of truth.
+pub async fn sync_balance_projection(state: &AppState, user_id: &str, balance: Money) {
+    let now = BsonDateTime::now();
+
+    let profiles: Collection<mongodb::bson::Document> = state.db.collection("user_profiles");
+    if let Err(e) = profiles
+        .update_one(
+            doc! { "user_id": user_id },
+            doc! { "$set": { "balance": balance, "updated_at": now } },
+        )
+        .await
+    {
+        tracing::error!("❌ Failed to project balance onto user_profiles: {}", e);
+    }
+
+    if let Ok(oid) = ObjectId::parse_str(user_id) {
+        let users: Collection<mongodb::bson::Document> = state.db.collection("users");
+        if let Err(e) = users
+            .update_one(
+                doc! { "_id": oid },
+                doc! { "$set": { "balance": balance, "updated_at": now } },
+            )
+            .await
+        {
+            tracing::error!("❌ Failed to project balance onto users: {}", e);
+        }
+    }
+}
+
+pub fn is_duplicate_key(err: &mongodb::error::Error) -> bool {
+    matches!(
+        err.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(we)) if we.code == 11000
+    )
+}
+
+fn validate_postings(postings: &[LedgerPosting]) -> Result<()> {
+    if postings.len() < 2 {
+        return Err(AppError::invalid_data("A ledger entry needs at least two postings"));
+    }
+    if postings.iter().any(|p| p.amount == Money::ZERO) {
+        return Err(AppError::invalid_data("Ledger postings must be non-zero amounts"));
+    }
+    let sum: Money = postings.iter().map(|p| p.amount).sum();
+    if sum != Money::ZERO {
+        return Err(AppError::invalid_data(format!(
+            "Unbalanced ledger entry (postings sum to {})",
+            sum
+        )));
+    }
+    Ok(())
+}
+
+fn account_type_of(account_id: &str) -> &str {
+    if account_id.starts_with("system:") {
+        "system"
+    } else {
+        account_id.rsplit(':').next().unwrap_or("wallet")
+    }
+}
+
+/// Applies one posting and returns the resulting balance, or `None` when a
+/// guarded debit did not have enough funds. A posting `entry_id` already
+/// applied is not applied again.
+async fn apply_posting(
+    accounts: &Collection<LedgerAccount>,
+    entry_id: &str,
+    posting: &LedgerPosting,
+) -> Result<Option<Money>> {
+    let account_type = account_type_of(&posting.account_id);
+    let allow_negative = account_type == "system";
+    let now = BsonDateTime::now();
+
+    let result = if posting.amount.is_negative() && !allow_negative {
+        accounts
+            .find_one_and_update(
+                doc! {
+                    "account_id": &posting.account_id,
+                    "pending_entries": { "$ne": entry_id },
+                    "balance": { "$gte": -posting.amount },
+                },
+                doc! {
+                    "$inc": { "balance": posting.amount },
+                    "$set": { "updated_at": now },
+                    "$addToSet": { "pending_entries": entry_id },
+                },
+            )
+            .return_document(ReturnDocument::After)
+            .await
+    } else {
+        accounts
+            .find_one_and_update(
+                doc! {
+                    "account_id": &posting.account_id,
+                    "pending_entries": { "$ne": entry_id },
+                },
+                doc! {
+                    "$inc": { "balance": posting.amount },
+                    "$set": { "updated_at": now },
+                    "$addToSet": { "pending_entries": entry_id },
+                    "$setOnInsert": {
+                        "owner_id": &posting.owner_id,
+                        "account_type": account_type,
+                        "allow_negative": allow_negative,
+                        "created_at": now,
+                    },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await
+    };
+
+    match result {
+        Ok(Some(account)) => Ok(Some(account.balance)),
+        // No match (or the upsert collided): applied before this attempt?
+        Ok(None) => already_applied(accounts, entry_id, &posting.account_id).await,
+        Err(e) if is_duplicate_key(&e) => {
+            match already_applied(accounts, entry_id, &posting.account_id).await? {
+                Some(balance) => Ok(Some(balance)),
+                None => Err(e.into()),
+            }
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn already_applied(
+    accounts: &Collection<LedgerAccount>,
+    entry_id: &str,
+    account_id: &str,
+) -> Result<Option<Money>> {
+    Ok(accounts
+        .find_one(doc! { "account_id": account_id, "pending_entries": entry_id })
+        .await?
+        .map(|a| a.balance))
+}
+
+async fn rollback(accounts: &Collection<LedgerAccount>, entry_id: &str, applied: &[LedgerPosting]) {
+    for posting in applied {
+        if let Err(e) = accounts
+            .update_one(
+                doc! { "account_id": &posting.account_id, "pending_entries": entry_id },
+                doc! {
+                    "$inc": { "balance": -posting.amount },
+                    "$pull": { "pending_entries": entry_id },
+                },
+            )
+            .await
+        {
+            tracing::error!(
+                "❌ Ledger rollback failed for {} ({}): {}",
+                posting.account_id,
+                posting.amount,
+                e
+            );
+        }
+    }
+}
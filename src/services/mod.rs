@@ -1,4 +1,20 @@
 // src/services/mod.rs
+pub mod broadcaster;
 pub mod cloudinary;
+pub mod escrow;
 pub mod fcm_service;
+pub mod kyc;
+pub mod ledger;
+pub mod mpesa_audit;
 pub mod mpesa_service;
+pub mod odds;
+pub mod otp;
+pub mod pledge_expiry;
+pub mod presence;
+pub mod rate_limit;
+pub mod replay_guard;
+pub mod responsible_gambling;
+pub mod session;
+pub mod settlement;
+pub mod sms_service;
+pub mod withdrawal;
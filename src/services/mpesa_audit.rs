@@ -0,0 +1,27 @@
+// src/services/mpesa_audit.rs
+use chrono::Utc;
+use mongodb::{bson::DateTime, Collection};
+
+use crate::models::transaction::CallbackAudit;
+use crate::state::AppState;
+
+/// Longest payload kept per audit record.
+const MAX_PAYLOAD_CHARS: usize = 8 * 1024;
+
+/// Record a suspicious M-Pesa callback. Never fails the request: a missing
+/// audit row is logged instead.
+pub async fn record(state: &AppState, path: &str, source_ip: Option<String>, reason: &str, payload: &str) {
+    tracing::warn!("🚨 M-Pesa callback {} from {:?}: {}", path, source_ip, reason);
+
+    let audit = CallbackAudit {
+        path: path.to_string(),
+        source_ip,
+        reason: reason.to_string(),
+        payload: payload.chars().take(MAX_PAYLOAD_CHARS).collect(),
+        received_at: DateTime::from_chrono(Utc::now()),
+    };
+    let collection: Collection<CallbackAudit> = state.db.collection("mpesa_callback_audit");
+    if let Err(e) = collection.insert_one(&audit).await {
+        tracing::error!("❌ Failed to record M-Pesa callback audit: {}", e);
+    }
+}
@@ -59,6 +59,8 @@ pub struct StkPushResponse {
 // B2C Structs
 #[derive(Debug, Serialize)]
 pub struct B2CRequest {
+    #[serde(rename = "OriginatorConversationID")]
+    pub originator_conversation_id: String,
     #[serde(rename = "InitiatorName")]
     pub initiator_name: String,
     #[serde(rename = "SecurityCredential")]
@@ -93,6 +95,51 @@ pub struct B2CResponse {
     pub response_description: String,
 }
 
+/// Why a B2C request didn't go through. `Rejected` means nothing will be
+/// paid; `Unknown` means Daraja may have accepted it anyway (the connection
+/// dropped, a gateway error, an unreadable response).
+#[derive(Debug)]
+pub enum B2CError {
+    Rejected(String),
+    Unknown(String),
+}
+
+impl std::fmt::Display for B2CError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            B2CError::Rejected(msg) => write!(f, "{}", msg),
+            B2CError::Unknown(msg) => write!(f, "outcome unknown: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for B2CError {}
+
+// Transaction Status Structs
+#[derive(Debug, Serialize)]
+pub struct TransactionStatusRequest {
+    #[serde(rename = "Initiator")]
+    pub initiator: String,
+    #[serde(rename = "SecurityCredential")]
+    pub security_credential: String,
+    #[serde(rename = "CommandID")]
+    pub command_id: String,
+    #[serde(rename = "OriginatorConversationID")]
+    pub originator_conversation_id: String,
+    #[serde(rename = "PartyA")]
+    pub party_a: String,
+    #[serde(rename = "IdentifierType")]
+    pub identifier_type: String,
+    #[serde(rename = "ResultURL")]
+    pub result_url: String,
+    #[serde(rename = "QueueTimeOutURL")]
+    pub queue_timeout_url: String,
+    #[serde(rename = "Remarks")]
+    pub remarks: String,
+    #[serde(rename = "Occasion")]
+    pub occasion: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MpesaService {
     config: AppConfig,
@@ -279,14 +326,17 @@ impl MpesaService {
     }
 
     // B2C: Business to Customer
+    /// Submit a B2C payment under our own `originator_conversation_id`, so
+    /// its outcome can be looked up even if the response never arrives.
     pub async fn send_b2c_payment(
         &self,
+        originator_conversation_id: &str,
         phone_number: &str,
         amount: &str,
         command_id: &str,
         remarks: &str,
         occasion: Option<&str>,
-    ) -> Result<B2CResponse, Box<dyn std::error::Error>> {
+    ) -> Result<B2CResponse, B2CError> {
         info!("B2C: Sending to {} - KSh {}", phone_number, amount);
 
         println!("==========================================");
@@ -321,21 +371,28 @@ impl MpesaService {
                     "   Got: {} chars",
                     self.config.mpesa_security_credential.len()
                 );
-                return Err("Invalid production security credential".into());
+                return Err(B2CError::Rejected(
+                    "Invalid production security credential".to_string(),
+                ));
             }
         }
 
-        let amount_parsed = amount.parse::<f64>()?;
+        let amount_parsed = amount
+            .parse::<f64>()
+            .map_err(|e| B2CError::Rejected(e.to_string()))?;
         if amount_parsed <= 0.0 {
-            return Err("Amount must be greater than 0".into());
+            return Err(B2CError::Rejected("Amount must be greater than 0".to_string()));
         }
 
         // Check minimum amount for B2C
         if amount_parsed < 10.0 {
-            return Err("Minimum B2C amount is KSh 10".into());
+            return Err(B2CError::Rejected("Minimum B2C amount is KSh 10".to_string()));
         }
 
-        let access_token = self.get_access_token().await?;
+        let access_token = self
+            .get_access_token()
+            .await
+            .map_err(|e| B2CError::Rejected(e.to_string()))?;
         let formatted_phone = self.format_phone_number(phone_number);
 
         println!("[B2C] Formatted Phone: {}", formatted_phone);
@@ -345,6 +402,7 @@ impl MpesaService {
         println!("[B2C] API URL: {}", b2c_url);
 
         let b2c_request = B2CRequest {
+            originator_conversation_id: originator_conversation_id.to_string(),
             initiator_name: self.config.mpesa_initiator_name.clone(),
             security_credential: self.config.mpesa_security_credential.clone(),
             command_id: command_id.to_string(),
@@ -373,20 +431,37 @@ impl MpesaService {
             .header(header::CONTENT_TYPE, "application/json")
             .json(&b2c_request)
             .send()
-            .await?;
+            .await
+            .map_err(|e| {
+                // Only a failed connect is sure not to have reached Daraja
+                if e.is_connect() {
+                    B2CError::Rejected(e.to_string())
+                } else {
+                    B2CError::Unknown(e.to_string())
+                }
+            })?;
 
         let status = response.status();
-        let response_text = response.text().await?;
+        let response_text = response
+            .text()
+            .await
+            .map_err(|e| B2CError::Unknown(e.to_string()))?;
 
         println!("[B2C] Response Status: {}", status);
         println!("[B2C] Response Body: {}", response_text);
 
         if !status.is_success() {
             error!("B2C failed: {} - {}", status, response_text);
-            return Err(format!("B2C failed: {} - {}", status, response_text).into());
+            let message = format!("B2C failed: {} - {}", status, response_text);
+            return Err(if status.is_server_error() {
+                B2CError::Unknown(message)
+            } else {
+                B2CError::Rejected(message)
+            });
         }
 
-        let b2c_response: B2CResponse = serde_json::from_str(&response_text)?;
+        let b2c_response: B2CResponse = serde_json::from_str(&response_text)
+            .map_err(|e| B2CError::Unknown(e.to_string()))?;
 
         println!("==========================================");
         println!("✅ B2C TRANSACTION RESPONSE");
@@ -410,12 +485,78 @@ impl MpesaService {
                 "M-Pesa rejected B2C request: {}",
                 b2c_response.response_description
             );
-            return Err(format!("M-Pesa error: {}", b2c_response.response_description).into());
+            return Err(B2CError::Rejected(format!(
+                "M-Pesa error: {}",
+                b2c_response.response_description
+            )));
         }
 
         Ok(b2c_response)
     }
 
+    /// Ask Daraja what became of a B2C payment, by the
+    /// OriginatorConversationID it was submitted under. The answer arrives
+    /// on `mpesa_status_result_url`; the acknowledgement has the same shape
+    /// as a B2C one.
+    pub async fn query_transaction_status(
+        &self,
+        originator_conversation_id: &str,
+        remarks: &str,
+    ) -> Result<B2CResponse, Box<dyn std::error::Error>> {
+        let access_token = self.get_access_token().await?;
+        let status_url = self.config.get_transaction_status_url();
+
+        let status_request = TransactionStatusRequest {
+            initiator: self.config.mpesa_initiator_name.clone(),
+            security_credential: self.config.mpesa_security_credential.clone(),
+            command_id: "TransactionStatusQuery".to_string(),
+            originator_conversation_id: originator_conversation_id.to_string(),
+            party_a: self.config.mpesa_short_code.clone(),
+            // 4 = organization shortcode
+            identifier_type: "4".to_string(),
+            result_url: self.config.mpesa_status_result_url.clone(),
+            queue_timeout_url: self.config.mpesa_status_timeout_url.clone(),
+            remarks: remarks.to_string(),
+            occasion: None,
+        };
+
+        let response = self
+            .client
+            .post(&status_url)
+            .header(header::AUTHORIZATION, format!("Bearer {}", access_token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .json(&status_request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let response_text = response.text().await?;
+
+        if !status.is_success() {
+            error!("Transaction status query failed: {} - {}", status, response_text);
+            return Err(format!(
+                "Transaction status query failed: {} - {}",
+                status, response_text
+            )
+            .into());
+        }
+
+        let ack: B2CResponse = serde_json::from_str(&response_text)?;
+        if ack.response_code != "0" {
+            error!(
+                "M-Pesa rejected transaction status query: {}",
+                ack.response_description
+            );
+            return Err(format!("M-Pesa error: {}", ack.response_description).into());
+        }
+
+        info!(
+            "Transaction status query for {} accepted: {}",
+            originator_conversation_id, ack.conversation_id
+        );
+        Ok(ack)
+    }
+
     pub async fn check_connectivity(
         &self,
     ) -> Result<ConnectivityStatus, Box<dyn std::error::Error>> {
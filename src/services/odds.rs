@@ -0,0 +1,77 @@
+// src/services/odds.rs
+//
+// Server-side pricing for P2P bets. Odds always come from the stored Game
+// at the moment a bet is created (never from the client), and the
+// finisher's stake is whatever covers the starter's winnings at those odds.
+use mongodb::{bson::doc, Collection};
+
+use crate::errors::{AppError, Result};
+use crate::models::bets::{BetOdds, BetQuote};
+use crate::models::game::Game;
+use crate::models::money::Money;
+use crate::state::AppState;
+
+/// The fixture a pledge or bet is on: by `match_id` when known, otherwise
+/// the upcoming game between the two teams.
+pub async fn find_fixture(
+    state: &AppState,
+    match_id: Option<&str>,
+    home_team: &str,
+    away_team: &str,
+) -> Result<Option<Game>> {
+    let games: Collection<Game> = state.db.collection("games");
+    let filter = match match_id {
+        Some(match_id) => doc! { "match_id": match_id },
+        None => doc! { "home_team": home_team, "away_team": away_team, "status": "upcoming" },
+    };
+    Ok(games.find_one(filter).await?)
+}
+
+/// Snapshot the fixture's current odds.
+pub async fn snapshot(
+    state: &AppState,
+    match_id: Option<&str>,
+    home_team: &str,
+    away_team: &str,
+) -> Result<(Game, BetOdds)> {
+    let game = find_fixture(state, match_id, home_team, away_team)
+        .await?
+        .ok_or_else(|| AppError::ValidationError("No fixture found for this match".to_string()))?;
+    let odds = BetOdds::from_game(&game).ok_or_else(|| {
+        AppError::ValidationError(format!("{} vs {} has no odds yet", game.home_team, game.away_team))
+    })?;
+    Ok((game, odds))
+}
+
+/// Price a starter stake on `selection`.
+pub fn quote(game: &Game, odds: &BetOdds, selection: &str, starter_amount: Money) -> Result<BetQuote> {
+    if !starter_amount.is_positive() {
+        return Err(AppError::ValidationError(
+            "amount must be greater than 0".to_string(),
+        ));
+    }
+    let selection_odds = odds.for_selection(selection).ok_or_else(|| {
+        AppError::ValidationError(format!("No odds available for selection '{}'", selection))
+    })?;
+
+    let finisher_amount = selection_odds.winnings(starter_amount);
+    if !finisher_amount.is_positive() {
+        return Err(AppError::ValidationError(
+            "Stake is too small for these odds".to_string(),
+        ));
+    }
+    let total_pot = starter_amount + finisher_amount;
+
+    Ok(BetQuote {
+        match_id: game.match_id.clone(),
+        home_team: game.home_team.clone(),
+        away_team: game.away_team.clone(),
+        starter_selection: selection.to_string(),
+        odds: selection_odds,
+        starter_amount,
+        finisher_amount,
+        total_pot,
+        starter_payout: total_pot,
+        finisher_payout: total_pot,
+    })
+}
@@ -0,0 +1,179 @@
+// src/services/otp.rs
+//
+// Phone verification codes. A code is sent by SMS, lives for OTP_TTL_SECS
+// and allows OTP_MAX_ATTEMPTS guesses; verifying it consumes it. Access
+// tokens are only ever issued after a successful verification.
+use chrono::{Duration, Utc};
+use mongodb::{
+    bson::{doc, DateTime},
+    options::ReturnDocument,
+    Collection,
+};
+use rand::Rng;
+
+use crate::errors::{AppError, Result};
+use crate::models::otp::PhoneOtp;
+use crate::state::AppState;
+
+pub const OTP_TTL_SECS: i64 = 5 * 60;
+pub const OTP_MAX_ATTEMPTS: i32 = 5;
+/// Minimum gap between two codes for the same phone.
+pub const OTP_RESEND_SECS: i64 = 60;
+
+fn otps(state: &AppState) -> Collection<PhoneOtp> {
+    state.db.collection("phone_otps")
+}
+
+fn generate_code() -> String {
+    format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+}
+
+/// Send a fresh code to `phone`, replacing any previous one.
+pub async fn send_code(state: &AppState, phone: &str) -> Result<()> {
+    let collection = otps(state);
+    let now = Utc::now();
+
+    let resend_after = DateTime::from_chrono(now - Duration::seconds(OTP_RESEND_SECS));
+    if collection
+        .find_one(doc! { "phone": phone, "created_at": { "$gt": resend_after } })
+        .await?
+        .is_some()
+    {
+        return Err(AppError::RateLimitExceeded);
+    }
+
+    let otp = PhoneOtp {
+        phone: phone.to_string(),
+        code: generate_code(),
+        attempts: 0,
+        expires_at: DateTime::from_chrono(now + Duration::seconds(OTP_TTL_SECS)),
+        created_at: DateTime::from_chrono(now),
+    };
+    collection
+        .replace_one(doc! { "phone": phone }, &otp)
+        .upsert(true)
+        .await?;
+
+    state.sms.send_otp(phone, &otp.code).await?;
+    println!("📲 OTP sent to {}", phone);
+    Ok(())
+}
+
+/// Check `code` for `phone`. Every call uses up an attempt, so a code cannot
+/// be brute-forced by racing requests; a correct code is deleted.
+pub async fn verify_code(state: &AppState, phone: &str, code: &str) -> Result<()> {
+    let collection = otps(state);
+
+    let otp = collection
+        .find_one_and_update(
+            doc! { "phone": phone, "attempts": { "$lt": OTP_MAX_ATTEMPTS } },
+            doc! { "$inc": { "attempts": 1 } },
+        )
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(AppError::InvalidOtp)?;
+
+    if otp.expires_at.to_chrono() <= Utc::now() {
+        collection.delete_one(doc! { "phone": phone }).await?;
+        return Err(AppError::OtpExpired);
+    }
+
+    if otp.code != code.trim() {
+        if otp.attempts >= OTP_MAX_ATTEMPTS {
+            collection.delete_one(doc! { "phone": phone }).await?;
+        }
+        return Err(AppError::InvalidOtp);
+    }
+
+    // Only one request can consume the code
+    let deleted = collection
+        .delete_one(doc! { "phone": phone, "code": &otp.code })
+        .await?;
+    if deleted.deleted_count == 0 {
+        return Err(AppError::InvalidOtp);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_test_db;
+
+    const PHONE: &str = "254700000001";
+
+    async fn stored(state: &AppState) -> Option<PhoneOtp> {
+        otps(state).find_one(doc! { "phone": PHONE }).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn request_stores_a_code_and_throttles_resends() {
+        with_test_db(|state| async move {
+            send_code(&state, PHONE).await.unwrap();
+            let otp = stored(&state).await.expect("code stored");
+            assert_eq!(otp.code.len(), 6);
+            assert_eq!(otp.attempts, 0);
+
+            let resend = send_code(&state, PHONE).await;
+            assert!(matches!(resend, Err(AppError::RateLimitExceeded)));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn verify_accepts_the_code_once() {
+        with_test_db(|state| async move {
+            send_code(&state, PHONE).await.unwrap();
+            let code = stored(&state).await.unwrap().code;
+
+            verify_code(&state, PHONE, &format!(" {} ", code)).await.unwrap();
+            assert!(stored(&state).await.is_none());
+
+            let replay = verify_code(&state, PHONE, &code).await;
+            assert!(matches!(replay, Err(AppError::InvalidOtp)));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn verify_rejects_an_expired_code() {
+        with_test_db(|state| async move {
+            send_code(&state, PHONE).await.unwrap();
+            let code = stored(&state).await.unwrap().code;
+
+            let expired = DateTime::from_chrono(Utc::now() - Duration::seconds(1));
+            otps(&state)
+                .update_one(doc! { "phone": PHONE }, doc! { "$set": { "expires_at": expired } })
+                .await
+                .unwrap();
+
+            let result = verify_code(&state, PHONE, &code).await;
+            assert!(matches!(result, Err(AppError::OtpExpired)));
+            assert!(stored(&state).await.is_none());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at TEST_MONGODB_URI"]
+    async fn verify_burns_the_code_after_max_attempts() {
+        with_test_db(|state| async move {
+            send_code(&state, PHONE).await.unwrap();
+            let code = stored(&state).await.unwrap().code;
+            let wrong = if code == "000000" { "111111" } else { "000000" };
+
+            for _ in 0..OTP_MAX_ATTEMPTS {
+                let result = verify_code(&state, PHONE, wrong).await;
+                assert!(matches!(result, Err(AppError::InvalidOtp)));
+            }
+            assert!(stored(&state).await.is_none());
+
+            let result = verify_code(&state, PHONE, &code).await;
+            assert!(matches!(result, Err(AppError::InvalidOtp)));
+        })
+        .await;
+    }
+}
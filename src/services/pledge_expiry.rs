@@ -0,0 +1,143 @@
+// src/services/pledge_expiry.rs
+//
+// Background job that closes unmatched pledges once they can no longer be
+// accepted: at kickoff, or earlier if the pledger picked an expiry. The
+// starter's reserved stake goes back to their wallet and they get a push.
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use mongodb::{bson::doc, Collection};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::errors::Result;
+use crate::handlers::games::parse_kickoff_utc;
+use crate::models::pledges::{Pledge, PLEDGE_EXPIRED, PLEDGE_OPEN};
+use crate::services::{escrow, odds};
+use crate::state::AppState;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Start the sweep loop. Called once from `main`.
+pub fn spawn_pledge_expiry_job(state: AppState) {
+    tokio::spawn(async move {
+        tracing::info!("⏰ Pledge expiry job started ({}s interval)", SWEEP_INTERVAL.as_secs());
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = backfill_expiry(&state).await {
+                tracing::error!("❌ Pledge expiry backfill failed: {}", e);
+            }
+            match expire_due_pledges(&state).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("⏰ Expired {} pledges", count),
+                Err(e) => tracing::error!("❌ Pledge expiry sweep failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Kickoff time of the fixture a pledge is on, if the game is known.
+pub async fn kickoff_for(
+    state: &AppState,
+    match_id: Option<&str>,
+    home_team: &str,
+    away_team: &str,
+) -> Result<Option<DateTime<Utc>>> {
+    Ok(odds::find_fixture(state, match_id, home_team, away_team)
+        .await?
+        .and_then(|game| parse_kickoff_utc(&game.date_iso, &game.time)))
+}
+
+/// Open pledges that predate `expires_at` get it filled in from the fixture.
+async fn backfill_expiry(state: &AppState) -> Result<()> {
+    let pledges: Collection<Pledge> = state.db.collection("pledges");
+    let missing: Vec<Pledge> = pledges
+        .find(doc! {
+            "$or": [{ "status": PLEDGE_OPEN }, { "status": { "$exists": false } }],
+            "expires_at": null,
+        })
+        .await?
+        .try_collect()
+        .await?;
+
+    for pledge in missing {
+        let kickoff = kickoff_for(
+            state,
+            pledge.match_id.as_deref(),
+            &pledge.home_team,
+            &pledge.away_team,
+        )
+        .await?;
+        if let (Some(id), Some(kickoff)) = (pledge._id, kickoff) {
+            pledges
+                .update_one(doc! { "_id": id }, doc! { "$set": { "expires_at": kickoff } })
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Expire every open pledge whose `expires_at` has passed.
+pub async fn expire_due_pledges(state: &AppState) -> Result<usize> {
+    let pledges: Collection<Pledge> = state.db.collection("pledges");
+    let now = Utc::now();
+
+    let due: Vec<Pledge> = pledges
+        .find(doc! {
+            "$or": [{ "status": PLEDGE_OPEN }, { "status": { "$exists": false } }],
+            "expires_at": { "$lte": now },
+        })
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut expired = 0;
+    for pledge in due {
+        let Some(id) = pledge._id else { continue };
+
+        // Conditional so a pledge accepted in the meantime is left alone
+        let result = pledges
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "$or": [{ "status": PLEDGE_OPEN }, { "status": { "$exists": false } }],
+                },
+                doc! { "$set": { "status": PLEDGE_EXPIRED, "updated_at": now } },
+            )
+            .await?;
+        if result.modified_count == 0 {
+            continue;
+        }
+
+        expired += 1;
+        let pledge_id = id.to_hex();
+        if let Err(e) = escrow::release_pledge_hold(state, &pledge_id).await {
+            tracing::error!("❌ Failed to release stake for expired pledge {}: {}", pledge_id, e);
+        }
+        notify_expired(state, &pledge, &pledge_id).await;
+    }
+
+    Ok(expired)
+}
+
+async fn notify_expired(state: &AppState, pledge: &Pledge, pledge_id: &str) {
+    let Some(fcm_service) = state.fcm_service.as_ref() else {
+        return;
+    };
+
+    let body = format!(
+        "Nobody took your ₿{} pledge on {} vs {}. Your stake is back in your wallet.",
+        pledge.amount, pledge.home_team, pledge.away_team
+    );
+    let data = json!({
+        "type": "pledge_expired",
+        "pledge_id": pledge_id,
+        "amount": pledge.amount,
+    });
+    if let Err(e) = fcm_service
+        .send_to_user(state, &pledge.starter_id, "⏰ Pledge expired", &body, data, "pledge_expired")
+        .await
+    {
+        tracing::warn!("⚠️ Pledge expiry notification failed: {}", e);
+    }
+}
@@ -0,0 +1,593 @@
+// src/services/presence.rs
+//
+// Who is watching each fixture. Every WebSocket connection registers here,
+// joins and leaves fixture rooms, and heartbeats on each message it sends;
+// connections that go quiet for PRESENCE_TTL_SECS stop counting until they
+// speak again. A user
+// with several sockets on a fixture counts once, and their online/offline
+// frames go out only when the first socket joins and the last one leaves.
+//
+// In memory by default. With REDIS_URL set, each instance also writes the
+// users it holds into a per-fixture sorted set (scored by last seen) so
+// counts cover every replica, and each connection into a set per user and
+// fixture, so "first in" and "last out" are decided across replicas. If
+// Redis stops answering, both fall back to this instance's connections.
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use chrono::Utc;
+use dashmap::DashMap;
+use redis::aio::MultiplexedConnection;
+use serde_json::json;
+
+use crate::errors::{AppError, Result};
+use crate::handlers::ws_handler::{fixture_channel, publish_presence, PresenceCountPayload};
+use crate::models::vote::OnlineUser;
+use crate::state::AppState;
+
+/// A connection that hasn't sent anything for this long is gone.
+pub const PRESENCE_TTL_SECS: i64 = 90;
+
+/// How often stale connections are swept and counts are broadcast.
+const PRESENCE_INTERVAL: Duration = Duration::from_secs(30);
+
+const REDIS_USERS_PREFIX: &str = "fanclash:presence:";
+const REDIS_NAMES_PREFIX: &str = "fanclash:presence-names:";
+const REDIS_CONNECTIONS_PREFIX: &str = "fanclash:presence-conns:";
+const REDIS_BROADCAST_PREFIX: &str = "fanclash:presence-broadcast:";
+
+/// Add a connection to its user's set on a fixture and the user to the
+/// fixture's set. Returns 1 if the user had no other live connection there
+/// on any replica.
+const JOIN_SCRIPT: &str = r"
+redis.call('ZREM', KEYS[1], ARGV[3])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', ARGV[2])
+local others = redis.call('ZCARD', KEYS[1])
+redis.call('ZADD', KEYS[1], ARGV[1], ARGV[3])
+redis.call('ZREMRANGEBYSCORE', KEYS[2], '-inf', ARGV[2])
+redis.call('ZADD', KEYS[2], ARGV[1], ARGV[4])
+redis.call('HSET', KEYS[3], ARGV[4], ARGV[5])
+for i = 1, 3 do
+  redis.call('EXPIRE', KEYS[i], ARGV[6])
+end
+if others == 0 then
+  return 1
+end
+return 0
+";
+
+/// Remove a connection from its user's set on a fixture, and the user from
+/// the fixture once no live connection is left. Returns 1 if it was the last.
+const LEAVE_SCRIPT: &str = r"
+redis.call('ZREM', KEYS[1], ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', ARGV[1])
+if redis.call('ZCARD', KEYS[1]) > 0 then
+  return 0
+end
+redis.call('ZREM', KEYS[2], ARGV[3])
+redis.call('HDEL', KEYS[3], ARGV[3])
+return 1
+";
+
+struct Connection {
+    user_id: String,
+    username: String,
+    fixtures: HashSet<String>,
+    last_seen: i64,
+    /// Swept for going quiet; its rooms are released until it heartbeats.
+    idle: bool,
+}
+
+pub struct PresenceTracker {
+    redis: Option<MultiplexedConnection>,
+    join_script: redis::Script,
+    leave_script: redis::Script,
+    /// connection id -> who it is and what it is watching
+    connections: DashMap<String, Connection>,
+    /// fixture id -> user id -> their connections there that aren't idle
+    rooms: DashMap<String, HashMap<String, HashSet<String>>>,
+}
+
+impl PresenceTracker {
+    pub fn in_memory() -> Self {
+        PresenceTracker {
+            redis: None,
+            join_script: redis::Script::new(JOIN_SCRIPT),
+            leave_script: redis::Script::new(LEAVE_SCRIPT),
+            connections: DashMap::new(),
+            rooms: DashMap::new(),
+        }
+    }
+
+    pub async fn with_redis(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(|e| AppError::redis(e.to_string()))?;
+        let connection = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .map_err(|e| AppError::redis(e.to_string()))?;
+        Ok(PresenceTracker {
+            redis: Some(connection),
+            ..Self::in_memory()
+        })
+    }
+
+    // ========== CONNECTION LIFECYCLE ==========
+
+    /// Register a socket before it joins any fixture.
+    pub fn connect(&self, connection_id: &str, user_id: &str, username: &str) {
+        self.connections.insert(
+            connection_id.to_string(),
+            Connection {
+                user_id: user_id.to_string(),
+                username: username.to_string(),
+                fixtures: HashSet::new(),
+                last_seen: Utc::now().timestamp(),
+                idle: false,
+            },
+        );
+    }
+
+    /// Any message from the client keeps it present. Returns the fixtures
+    /// its user is back in if the connection had been swept as silent.
+    pub async fn heartbeat(&self, connection_id: &str) -> Vec<String> {
+        let Some((user_id, username, fixtures)) =
+            self.connections.get_mut(connection_id).and_then(|mut c| {
+                c.last_seen = Utc::now().timestamp();
+                let was_idle = std::mem::replace(&mut c.idle, false);
+                was_idle.then(|| (c.user_id.clone(), c.username.clone(), c.fixtures.clone()))
+            })
+        else {
+            return Vec::new();
+        };
+
+        let mut returned = Vec::new();
+        for fixture_id in fixtures {
+            if self.enter(connection_id, &fixture_id, &user_id, &username).await {
+                returned.push(fixture_id);
+            }
+        }
+        returned
+    }
+
+    /// Put the connection in a fixture room. True if its user wasn't
+    /// already there on another socket.
+    pub async fn join(&self, connection_id: &str, fixture_id: &str) -> bool {
+        let Some((user_id, username)) = self.connections.get_mut(connection_id).map(|mut c| {
+            c.fixtures.insert(fixture_id.to_string());
+            c.last_seen = Utc::now().timestamp();
+            c.idle = false;
+            (c.user_id.clone(), c.username.clone())
+        }) else {
+            return false;
+        };
+        self.enter(connection_id, fixture_id, &user_id, &username).await
+    }
+
+    /// True if the user has just one live socket on the fixture, this one.
+    async fn enter(
+        &self,
+        connection_id: &str,
+        fixture_id: &str,
+        user_id: &str,
+        username: &str,
+    ) -> bool {
+        let local_sockets = self.index_add(fixture_id, user_id, connection_id);
+        match self
+            .redis_join(connection_id, fixture_id, user_id, username)
+            .await
+        {
+            Some(first) => first,
+            None => local_sockets == 1,
+        }
+    }
+
+    /// Take the connection out of a fixture room. True if that was its
+    /// user's last socket there.
+    pub async fn leave(&self, connection_id: &str, fixture_id: &str) -> bool {
+        let Some(user_id) = self.connections.get_mut(connection_id).and_then(|mut c| {
+            // An idle connection's rooms were already released
+            (c.fixtures.remove(fixture_id) && !c.idle).then(|| c.user_id.clone())
+        }) else {
+            return false;
+        };
+        self.release(connection_id, fixture_id, &user_id).await
+    }
+
+    /// Drop the connection. Returns the fixtures its user has now left.
+    pub async fn disconnect(&self, connection_id: &str) -> Vec<String> {
+        let Some((_, connection)) = self.connections.remove(connection_id) else {
+            return Vec::new();
+        };
+        if connection.idle {
+            return Vec::new();
+        }
+        let mut left = Vec::new();
+        for fixture_id in connection.fixtures {
+            if self
+                .release(connection_id, &fixture_id, &connection.user_id)
+                .await
+            {
+                left.push(fixture_id);
+            }
+        }
+        left
+    }
+
+    /// True if that was the user's last live socket on the fixture.
+    async fn release(&self, connection_id: &str, fixture_id: &str, user_id: &str) -> bool {
+        let local_sockets = self.index_remove(fixture_id, user_id, connection_id);
+        match self.redis_leave(connection_id, fixture_id, user_id).await {
+            Some(last) => last,
+            None => local_sockets == 0,
+        }
+    }
+
+    // ========== QUERIES ==========
+
+    /// Distinct users watching the fixture.
+    pub async fn online_count(&self, fixture_id: &str) -> usize {
+        match self.redis_count(fixture_id).await {
+            Some(count) => count,
+            None => self.local_users(fixture_id).len(),
+        }
+    }
+
+    /// Up to `limit` users watching the fixture, most recently active first.
+    pub async fn online_users(&self, fixture_id: &str, limit: usize) -> Vec<OnlineUser> {
+        if let Some(users) = self.redis_users(fixture_id, limit).await {
+            return users;
+        }
+        let mut users: Vec<(i64, OnlineUser)> = self
+            .local_users(fixture_id)
+            .into_iter()
+            .map(|(user_id, (username, last_seen))| (last_seen, OnlineUser { user_id, username }))
+            .collect();
+        users.sort_by_key(|(last_seen, _)| std::cmp::Reverse(*last_seen));
+        users.into_iter().take(limit).map(|(_, user)| user).collect()
+    }
+
+    // ========== PERIODIC REFRESH ==========
+
+    /// Idle connections that stopped heartbeating and re-announce this
+    /// instance's users to Redis. Returns the fixtures watched from here and
+    /// the fixtures users went offline from in the sweep.
+    async fn refresh(&self) -> (Vec<String>, Vec<(String, OnlineUser)>) {
+        let cutoff = Utc::now().timestamp() - PRESENCE_TTL_SECS;
+        let mut silent = Vec::new();
+        for mut c in self.connections.iter_mut() {
+            if !c.idle && c.last_seen < cutoff {
+                c.idle = true;
+                let user = OnlineUser {
+                    user_id: c.user_id.clone(),
+                    username: c.username.clone(),
+                };
+                silent.push((c.key().clone(), user, c.fixtures.clone()));
+            }
+        }
+
+        let mut went_offline = Vec::new();
+        for (connection_id, user, fixtures) in silent {
+            tracing::info!("👻 Connection {} of user {} went silent", connection_id, user.user_id);
+            for fixture_id in fixtures {
+                if self.release(&connection_id, &fixture_id, &user.user_id).await {
+                    went_offline.push((fixture_id, user.clone()));
+                }
+            }
+        }
+
+        let watched: Vec<String> = self.rooms.iter().map(|room| room.key().clone()).collect();
+        for fixture_id in &watched {
+            self.redis_refresh(fixture_id).await;
+        }
+        (watched, went_offline)
+    }
+
+    /// Whether this instance should broadcast the fixture's count this
+    /// round; with Redis only one replica does.
+    async fn claim_broadcast(&self, fixture_id: &str) -> bool {
+        let Some(mut connection) = self.redis.clone() else {
+            return true;
+        };
+        let result: redis::RedisResult<Option<String>> = redis::cmd("SET")
+            .arg(format!("{}{}", REDIS_BROADCAST_PREFIX, fixture_id))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(PRESENCE_INTERVAL.as_secs().saturating_sub(1).max(1))
+            .query_async(&mut connection)
+            .await;
+        match result {
+            Ok(claimed) => claimed.is_some(),
+            Err(_) => true,
+        }
+    }
+
+    // ========== LOCAL STATE ==========
+
+    /// Returns the user's live sockets on the fixture, this one included.
+    fn index_add(&self, fixture_id: &str, user_id: &str, connection_id: &str) -> usize {
+        let mut room = self.rooms.entry(fixture_id.to_string()).or_default();
+        let sockets = room.entry(user_id.to_string()).or_default();
+        sockets.insert(connection_id.to_string());
+        sockets.len()
+    }
+
+    /// Returns the user's live sockets left on the fixture.
+    fn index_remove(&self, fixture_id: &str, user_id: &str, connection_id: &str) -> usize {
+        let Some(mut room) = self.rooms.get_mut(fixture_id) else {
+            return 0;
+        };
+        let remaining = room.get_mut(user_id).map_or(0, |sockets| {
+            sockets.remove(connection_id);
+            sockets.len()
+        });
+        if remaining == 0 {
+            room.remove(user_id);
+        }
+        drop(room);
+        self.rooms.remove_if(fixture_id, |_, room| room.is_empty());
+        remaining
+    }
+
+    /// user id -> their live sockets on the fixture.
+    fn local_room(&self, fixture_id: &str) -> HashMap<String, HashSet<String>> {
+        self.rooms
+            .get(fixture_id)
+            .map(|room| room.clone())
+            .unwrap_or_default()
+    }
+
+    /// user id -> (username, last seen) for fresh connections on the fixture.
+    fn local_users(&self, fixture_id: &str) -> HashMap<String, (String, i64)> {
+        let cutoff = Utc::now().timestamp() - PRESENCE_TTL_SECS;
+        let mut users: HashMap<String, (String, i64)> = HashMap::new();
+        for (user_id, sockets) in self.local_room(fixture_id) {
+            for connection_id in sockets {
+                let Some(c) = self.connections.get(&connection_id) else {
+                    continue;
+                };
+                if c.idle || c.last_seen < cutoff {
+                    continue;
+                }
+                let entry = users
+                    .entry(user_id.clone())
+                    .or_insert_with(|| (c.username.clone(), c.last_seen));
+                entry.1 = entry.1.max(c.last_seen);
+            }
+        }
+        users
+    }
+
+    // ========== REDIS ==========
+
+    /// True if the user had no other live socket on the fixture anywhere.
+    async fn redis_join(
+        &self,
+        connection_id: &str,
+        fixture_id: &str,
+        user_id: &str,
+        username: &str,
+    ) -> Option<bool> {
+        let mut connection = self.redis.clone()?;
+        let now = Utc::now().timestamp();
+        let result: redis::RedisResult<i64> = self
+            .join_script
+            .key(connections_key(fixture_id, user_id))
+            .key(format!("{}{}", REDIS_USERS_PREFIX, fixture_id))
+            .key(format!("{}{}", REDIS_NAMES_PREFIX, fixture_id))
+            .arg(now)
+            .arg(now - PRESENCE_TTL_SECS)
+            .arg(connection_id)
+            .arg(user_id)
+            .arg(username)
+            .arg(PRESENCE_TTL_SECS * 2)
+            .invoke_async(&mut connection)
+            .await;
+
+        match result {
+            Ok(first) => Some(first == 1),
+            Err(e) => {
+                tracing::warn!("⚠️ Redis presence join failed, tracking in memory: {}", e);
+                None
+            }
+        }
+    }
+
+    /// True if that was the user's last live socket on the fixture anywhere.
+    async fn redis_leave(
+        &self,
+        connection_id: &str,
+        fixture_id: &str,
+        user_id: &str,
+    ) -> Option<bool> {
+        let mut connection = self.redis.clone()?;
+        let result: redis::RedisResult<i64> = self
+            .leave_script
+            .key(connections_key(fixture_id, user_id))
+            .key(format!("{}{}", REDIS_USERS_PREFIX, fixture_id))
+            .key(format!("{}{}", REDIS_NAMES_PREFIX, fixture_id))
+            .arg(Utc::now().timestamp() - PRESENCE_TTL_SECS)
+            .arg(connection_id)
+            .arg(user_id)
+            .invoke_async(&mut connection)
+            .await;
+
+        match result {
+            Ok(last) => Some(last == 1),
+            Err(e) => {
+                tracing::warn!("⚠️ Redis presence leave failed, tracking in memory: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn redis_refresh(&self, fixture_id: &str) {
+        let Some(mut connection) = self.redis.clone() else {
+            return;
+        };
+        let now = Utc::now().timestamp();
+        let users = self.local_users(fixture_id);
+        let room = self.local_room(fixture_id);
+        let users_key = format!("{}{}", REDIS_USERS_PREFIX, fixture_id);
+        let names_key = format!("{}{}", REDIS_NAMES_PREFIX, fixture_id);
+
+        let mut pipe = redis::pipe();
+        pipe.zrembyscore(&users_key, "-inf", now - PRESENCE_TTL_SECS).ignore();
+        for (user_id, (username, _)) in &users {
+            pipe.zadd(&users_key, user_id, now)
+                .ignore()
+                .hset(&names_key, user_id, username)
+                .ignore();
+            let connections_key = connections_key(fixture_id, user_id);
+            for connection_id in room.get(user_id).into_iter().flatten() {
+                pipe.zadd(&connections_key, connection_id, now).ignore();
+            }
+            pipe.expire(&connections_key, PRESENCE_TTL_SECS * 2).ignore();
+        }
+        pipe.expire(&users_key, PRESENCE_TTL_SECS * 2)
+            .ignore()
+            .expire(&names_key, PRESENCE_TTL_SECS * 2)
+            .ignore();
+
+        let result: redis::RedisResult<()> = pipe.query_async(&mut connection).await;
+        if let Err(e) = result {
+            tracing::warn!("⚠️ Redis presence refresh failed for {}: {}", fixture_id, e);
+        }
+    }
+
+    async fn redis_count(&self, fixture_id: &str) -> Option<usize> {
+        let mut connection = self.redis.clone()?;
+        let cutoff = Utc::now().timestamp() - PRESENCE_TTL_SECS;
+        let result: redis::RedisResult<usize> = redis::cmd("ZCOUNT")
+            .arg(format!("{}{}", REDIS_USERS_PREFIX, fixture_id))
+            .arg(cutoff)
+            .arg("+inf")
+            .query_async(&mut connection)
+            .await;
+        match result {
+            Ok(count) => Some(count),
+            Err(e) => {
+                tracing::warn!("⚠️ Redis presence count failed, counting in memory: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn redis_users(&self, fixture_id: &str, limit: usize) -> Option<Vec<OnlineUser>> {
+        let mut connection = self.redis.clone()?;
+        let cutoff = Utc::now().timestamp() - PRESENCE_TTL_SECS;
+        let user_ids: Vec<String> = match redis::cmd("ZREVRANGEBYSCORE")
+            .arg(format!("{}{}", REDIS_USERS_PREFIX, fixture_id))
+            .arg("+inf")
+            .arg(cutoff)
+            .arg("LIMIT")
+            .arg(0)
+            .arg(limit)
+            .query_async(&mut connection)
+            .await
+        {
+            Ok(user_ids) => user_ids,
+            Err(e) => {
+                tracing::warn!("⚠️ Redis presence lookup failed, using memory: {}", e);
+                return None;
+            }
+        };
+        if user_ids.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let usernames: Vec<Option<String>> = redis::cmd("HMGET")
+            .arg(format!("{}{}", REDIS_NAMES_PREFIX, fixture_id))
+            .arg(&user_ids)
+            .query_async(&mut connection)
+            .await
+            .unwrap_or_default();
+
+        Some(
+            user_ids
+                .into_iter()
+                .enumerate()
+                .map(|(i, user_id)| OnlineUser {
+                    username: usernames
+                        .get(i)
+                        .cloned()
+                        .flatten()
+                        .unwrap_or_else(|| "Anonymous".to_string()),
+                    user_id,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A user's live connections on a fixture, across replicas.
+fn connections_key(fixture_id: &str, user_id: &str) -> String {
+    format!("{}{}:{}", REDIS_CONNECTIONS_PREFIX, fixture_id, user_id)
+}
+
+// ========== PERIODIC BROADCASTS ==========
+
+/// Start the presence loop: sweep silent sockets and broadcast each
+/// watched fixture's online count. Called once from `main`.
+pub fn spawn_presence_job(state: AppState) {
+    tokio::spawn(async move {
+        tracing::info!("👥 Presence job started ({}s interval)", PRESENCE_INTERVAL.as_secs());
+        let mut interval = tokio::time::interval(PRESENCE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let (watched, went_offline) = state.presence.refresh().await;
+
+            for (fixture_id, user) in went_offline {
+                publish_presence(
+                    &state,
+                    &fixture_id,
+                    &user.user_id,
+                    &user.username,
+                    "offline",
+                )
+                .await;
+            }
+
+            for fixture_id in watched {
+                if !state.presence.claim_broadcast(&fixture_id).await {
+                    continue;
+                }
+                let online = state.presence.online_count(&fixture_id).await;
+                let frame = json!({
+                    "type": "presence.count",
+                    "payload": PresenceCountPayload {
+                        fixture_id: fixture_id.clone(),
+                        online,
+                    },
+                    "timestamp": Utc::now().to_rfc3339(),
+                });
+                state
+                    .comment_broadcaster
+                    .publish(&fixture_channel(&fixture_id), frame.to_string())
+                    .await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn online_and_offline_only_for_first_and_last_socket() {
+        let presence = PresenceTracker::in_memory();
+        presence.connect("a", "user-1", "One");
+        presence.connect("b", "user-1", "One");
+
+        assert!(presence.join("a", "fixture-1").await);
+        assert!(!presence.join("b", "fixture-1").await);
+        assert_eq!(presence.online_count("fixture-1").await, 1);
+
+        assert!(!presence.leave("a", "fixture-1").await);
+        assert_eq!(presence.online_count("fixture-1").await, 1);
+        assert_eq!(presence.disconnect("b").await, vec!["fixture-1".to_string()]);
+        assert_eq!(presence.online_count("fixture-1").await, 0);
+        assert!(presence.rooms.is_empty());
+    }
+}
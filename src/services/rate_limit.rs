@@ -0,0 +1,184 @@
+// src/services/rate_limit.rs
+//
+// Fixed-window request counters. In memory by default; with REDIS_URL set
+// the counters live in Redis so every instance shares them. If Redis stops
+// answering, counting falls back to memory rather than failing requests.
+use chrono::Utc;
+use dashmap::DashMap;
+use redis::aio::MultiplexedConnection;
+
+use crate::errors::{AppError, Result};
+
+/// Limits for one group of routes. A caller is counted both by address and,
+/// when they send a valid access token, by user; either bucket can trip.
+/// A limit of 0 disables that bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitPolicy {
+    pub name: &'static str,
+    pub per_ip: u32,
+    pub per_user: u32,
+    pub window_secs: i64,
+}
+
+pub const COMMENTS: RateLimitPolicy = RateLimitPolicy {
+    name: "comments",
+    per_ip: 30,
+    per_user: 10,
+    window_secs: 60,
+};
+
+pub const VOTES: RateLimitPolicy = RateLimitPolicy {
+    name: "votes",
+    per_ip: 120,
+    per_user: 60,
+    window_secs: 60,
+};
+
+/// Every STK push pops a real prompt on someone's phone.
+pub const STK_PUSH: RateLimitPolicy = RateLimitPolicy {
+    name: "stk_push",
+    per_ip: 10,
+    per_user: 3,
+    window_secs: 300,
+};
+
+pub const NOTIFICATIONS: RateLimitPolicy = RateLimitPolicy {
+    name: "notifications",
+    per_ip: 20,
+    per_user: 5,
+    window_secs: 60,
+};
+
+/// Sending and guessing OTPs; callers are not logged in yet.
+pub const OTP: RateLimitPolicy = RateLimitPolicy {
+    name: "otp",
+    per_ip: 10,
+    per_user: 0,
+    window_secs: 600,
+};
+
+/// Every withdrawal is a real B2C payout.
+pub const WITHDRAWALS: RateLimitPolicy = RateLimitPolicy {
+    name: "withdrawals",
+    per_ip: 10,
+    per_user: 3,
+    window_secs: 600,
+};
+
+/// Creating and accepting pledges, both of which move stakes into escrow.
+pub const PLEDGES: RateLimitPolicy = RateLimitPolicy {
+    name: "pledges",
+    per_ip: 60,
+    per_user: 20,
+    window_secs: 60,
+};
+
+pub const CHAT: RateLimitPolicy = RateLimitPolicy {
+    name: "chat",
+    per_ip: 60,
+    per_user: 20,
+    window_secs: 60,
+};
+
+/// Each submission uploads a document for review.
+pub const KYC: RateLimitPolicy = RateLimitPolicy {
+    name: "kyc",
+    per_ip: 10,
+    per_user: 5,
+    window_secs: 3600,
+};
+
+/// In-memory buckets kept before expired ones are swept.
+const MAX_MEMORY_BUCKETS: usize = 50_000;
+
+pub struct RateLimiter {
+    redis: Option<MultiplexedConnection>,
+    /// bucket -> (window resets at, hits)
+    memory: DashMap<String, (i64, u32)>,
+}
+
+impl RateLimiter {
+    pub fn in_memory() -> Self {
+        RateLimiter {
+            redis: None,
+            memory: DashMap::new(),
+        }
+    }
+
+    pub async fn with_redis(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(|e| AppError::redis(e.to_string()))?;
+        let connection = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .map_err(|e| AppError::redis(e.to_string()))?;
+        Ok(RateLimiter {
+            redis: Some(connection),
+            memory: DashMap::new(),
+        })
+    }
+
+    /// Count one request against `bucket`. Once it is over `limit` for the
+    /// current window, returns the seconds until the window resets.
+    pub async fn hit(&self, bucket: &str, limit: u32, window_secs: i64) -> std::result::Result<(), i64> {
+        let now = Utc::now().timestamp();
+        let window = now / window_secs;
+        let resets_at = (window + 1) * window_secs;
+
+        let hits = match self.redis_hit(bucket, window, window_secs).await {
+            Some(hits) => hits,
+            None => self.memory_hit(bucket, resets_at, now),
+        };
+
+        if hits > limit {
+            Err((resets_at - now).max(1))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Count one request against `policy`'s per-user bucket, the same one
+    /// the route middleware uses, for traffic that doesn't go through it
+    /// (WebSocket frames).
+    pub async fn hit_user(&self, policy: RateLimitPolicy, user_id: &str) -> std::result::Result<(), i64> {
+        if policy.per_user == 0 {
+            return Ok(());
+        }
+        let bucket = format!("{}:user:{}", policy.name, user_id);
+        self.hit(&bucket, policy.per_user, policy.window_secs).await
+    }
+
+    async fn redis_hit(&self, bucket: &str, window: i64, window_secs: i64) -> Option<u32> {
+        let mut connection = self.redis.clone()?;
+        let key = format!("rl:{}:{}", bucket, window);
+        let result: redis::RedisResult<(u32,)> = redis::pipe()
+            .atomic()
+            .incr(&key, 1)
+            .expire(&key, window_secs)
+            .ignore()
+            .query_async(&mut connection)
+            .await;
+
+        match result {
+            Ok((hits,)) => Some(hits),
+            Err(e) => {
+                tracing::warn!("⚠️ Redis rate limiting failed, counting in memory: {}", e);
+                None
+            }
+        }
+    }
+
+    fn memory_hit(&self, bucket: &str, resets_at: i64, now: i64) -> u32 {
+        if self.memory.len() > MAX_MEMORY_BUCKETS {
+            self.memory.retain(|_, (bucket_resets_at, _)| *bucket_resets_at > now);
+        }
+
+        let mut entry = self.memory.entry(bucket.to_string()).or_insert((resets_at, 0));
+        let (bucket_resets_at, hits) = entry.value_mut();
+        if *bucket_resets_at != resets_at {
+            *bucket_resets_at = resets_at;
+            *hits = 0;
+        }
+        *hits += 1;
+        *hits
+    }
+}
@@ -0,0 +1,76 @@
+// src/services/replay_guard.rs
+//
+// Remembers one-time values (poller webhook signatures) for a while so a
+// replay can be told apart from the first use. In memory by default; with
+// REDIS_URL set they are claimed with SET NX EX so a request replayed
+// against another instance is caught too. If Redis stops answering, claims
+// fall back to memory like the rate limiter's counters.
+use chrono::Utc;
+use dashmap::DashMap;
+use redis::aio::MultiplexedConnection;
+
+use crate::errors::{AppError, Result};
+
+pub struct ReplayGuard {
+    redis: Option<MultiplexedConnection>,
+    /// value -> unix time it was first seen
+    memory: DashMap<String, i64>,
+}
+
+impl ReplayGuard {
+    pub fn in_memory() -> Self {
+        ReplayGuard {
+            redis: None,
+            memory: DashMap::new(),
+        }
+    }
+
+    pub async fn with_redis(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(|e| AppError::redis(e.to_string()))?;
+        let connection = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .map_err(|e| AppError::redis(e.to_string()))?;
+        Ok(ReplayGuard {
+            redis: Some(connection),
+            memory: DashMap::new(),
+        })
+    }
+
+    /// True the first time `value` is seen within `ttl_secs`, false for a replay.
+    pub async fn first_use(&self, scope: &str, value: &str, ttl_secs: i64) -> bool {
+        match self.redis_claim(scope, value, ttl_secs).await {
+            Some(first) => first,
+            None => self.memory_claim(scope, value, ttl_secs),
+        }
+    }
+
+    async fn redis_claim(&self, scope: &str, value: &str, ttl_secs: i64) -> Option<bool> {
+        let mut connection = self.redis.clone()?;
+        let key = format!("fanclash:seen:{}:{}", scope, value);
+        let result: redis::RedisResult<Option<String>> = redis::cmd("SET")
+            .arg(&key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_secs)
+            .query_async(&mut connection)
+            .await;
+
+        match result {
+            Ok(claimed) => Some(claimed.is_some()),
+            Err(e) => {
+                tracing::warn!("⚠️ Redis replay check failed, checking in memory: {}", e);
+                None
+            }
+        }
+    }
+
+    fn memory_claim(&self, scope: &str, value: &str, ttl_secs: i64) -> bool {
+        let now = Utc::now().timestamp();
+        self.memory.retain(|_, seen_at| now - *seen_at <= ttl_secs);
+        self.memory
+            .insert(format!("{}:{}", scope, value), now)
+            .is_none()
+    }
+}
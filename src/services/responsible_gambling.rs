@@ -0,0 +1,308 @@
+// src/services/responsible_gambling.rs
+//
+// Player-set deposit, stake and loss limits, cool-offs and self-exclusion,
+// kept on the user profile. Tightening a limit applies at once; raising or
+// removing one only applies after LIMIT_INCREASE_DELAY_HOURS so it can't be
+// done on impulse mid-session. Usage is measured over rolling windows:
+//
+//   deposit = STK pushes started (pending or completed)
+//   stake   = escrow holds taken, less those released (cancelled / void)
+//   loss    = stakes minus payouts and refunds, from the wallet ledger
+use chrono::{Duration, Utc};
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, to_bson, Bson, DateTime, Document},
+    Collection,
+};
+
+use crate::errors::{AppError, Result};
+use crate::models::escrow::HOLD_RELEASED;
+use crate::models::gambling::{
+    GamblingLimits, LimitKind, LimitPeriod, LimitUsage, LimitsOverview, PendingLimitChange,
+    SetLimitRequest,
+};
+use crate::models::ledger::{EntryKind, LedgerAccount};
+use crate::models::money::Money;
+use crate::models::user_profile::UserProfile;
+use crate::state::AppState;
+
+pub const LIMIT_INCREASE_DELAY_HOURS: i64 = 24;
+pub const MAX_COOL_OFF_HOURS: i64 = 42 * 24;
+pub const MIN_SELF_EXCLUSION_MONTHS: i64 = 6;
+pub const MAX_SELF_EXCLUSION_MONTHS: i64 = 60;
+
+fn profiles(state: &AppState) -> Collection<UserProfile> {
+    state.db.collection("user_profiles")
+}
+
+/// The user's limits with any pending change that has come due applied.
+/// Users without a profile have no limits.
+pub async fn load(state: &AppState, user_id: &str) -> Result<GamblingLimits> {
+    let Some(profile) = profiles(state).find_one(doc! { "user_id": user_id }).await? else {
+        return Ok(GamblingLimits::default());
+    };
+    let mut limits = profile.gambling_limits;
+
+    let now = DateTime::now();
+    let (due, waiting): (Vec<PendingLimitChange>, Vec<PendingLimitChange>) = limits
+        .pending
+        .drain(..)
+        .partition(|change| change.effective_at <= now);
+    limits.pending = waiting;
+
+    if !due.is_empty() {
+        for change in &due {
+            limits.limits_mut(change.kind).set(change.period, change.amount);
+        }
+        save(state, user_id, &limits).await?;
+        println!("⏳ Applied {} pending limit changes for user {}", due.len(), user_id);
+    }
+    Ok(limits)
+}
+
+async fn save(state: &AppState, user_id: &str, limits: &GamblingLimits) -> Result<()> {
+    let limits = to_bson(limits).map_err(|e| AppError::internal_server_error(e.to_string()))?;
+    let result = profiles(state)
+        .update_one(
+            doc! { "user_id": user_id },
+            doc! { "$set": { "gambling_limits": limits, "updated_at": DateTime::now() } },
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::invalid_data(
+            "Create a profile before setting gambling limits",
+        ));
+    }
+    Ok(())
+}
+
+async fn sum_amount(collection: Collection<Document>, pipeline: Vec<Document>) -> Result<Money> {
+    let rows: Vec<Document> = collection.aggregate(pipeline).await?.try_collect().await?;
+    let total = rows.first().and_then(|row| match row.get("total") {
+        Some(Bson::Int64(n)) => Some(*n),
+        Some(Bson::Int32(n)) => Some(i64::from(*n)),
+        _ => None,
+    });
+    Ok(total.map(Money::from_cents).unwrap_or(Money::ZERO))
+}
+
+/// How much of `kind` the user has used over the last `period`.
+pub async fn usage(
+    state: &AppState,
+    user_id: &str,
+    kind: LimitKind,
+    period: LimitPeriod,
+) -> Result<Money> {
+    let since = Utc::now() - period.duration();
+
+    match kind {
+        LimitKind::Deposit => {
+            // Transactions keep created_at as an RFC 3339 string
+            let pipeline = vec![
+                doc! { "$match": {
+                    "user_id": user_id,
+                    "status": { "$in": ["pending", "completed"] },
+                    "created_at": { "$gte": since.to_rfc3339() },
+                }},
+                doc! { "$group": { "_id": null, "total": { "$sum": "$amount" } } },
+            ];
+            sum_amount(state.db.collection("transactions"), pipeline).await
+        }
+        LimitKind::Stake => {
+            let pipeline = vec![
+                doc! { "$match": {
+                    "user_id": user_id,
+                    "status": { "$ne": HOLD_RELEASED },
+                    "created_at": { "$gte": DateTime::from_chrono(since) },
+                }},
+                doc! { "$group": { "_id": null, "total": { "$sum": "$amount" } } },
+            ];
+            sum_amount(state.db.collection("escrow_holds"), pipeline).await
+        }
+        LimitKind::Loss => {
+            let wallet = LedgerAccount::wallet_id(user_id);
+            let pipeline = vec![
+                doc! { "$match": {
+                    "postings.account_id": &wallet,
+                    "status": "posted",
+                    "kind": { "$in": [
+                        EntryKind::Stake.as_str(),
+                        EntryKind::Payout.as_str(),
+                        EntryKind::Refund.as_str(),
+                    ]},
+                    "created_at": { "$gte": DateTime::from_chrono(since) },
+                }},
+                doc! { "$unwind": "$postings" },
+                doc! { "$match": { "postings.account_id": &wallet } },
+                doc! { "$group": { "_id": null, "total": { "$sum": "$postings.amount" } } },
+            ];
+            // Stakes leave the wallet, so a net outflow is a loss
+            let net = sum_amount(state.db.collection("ledger_entries"), pipeline).await?;
+            Ok(if net.is_negative() { -net } else { Money::ZERO })
+        }
+    }
+}
+
+fn ensure_not_blocked(limits: &GamblingLimits) -> Result<()> {
+    let now = DateTime::now();
+    if let Some(until) = limits.self_excluded_until.filter(|until| *until > now) {
+        return Err(AppError::ResponsibleGambling(format!(
+            "Self-excluded until {}",
+            until.to_chrono().to_rfc3339()
+        )));
+    }
+    if let Some(until) = limits.cool_off_until.filter(|until| *until > now) {
+        return Err(AppError::ResponsibleGambling(format!(
+            "Cooling off until {}",
+            until.to_chrono().to_rfc3339()
+        )));
+    }
+    Ok(())
+}
+
+async fn ensure_within(
+    state: &AppState,
+    user_id: &str,
+    limits: &GamblingLimits,
+    kind: LimitKind,
+    amount: Money,
+) -> Result<()> {
+    for period in LimitPeriod::ALL {
+        let Some(limit) = limits.limits(kind).get(period) else {
+            continue;
+        };
+        let used = usage(state, user_id, kind, period).await?;
+        if used + amount > limit {
+            return Err(AppError::ResponsibleGambling(format!(
+                "{:?} {:?} limit of KES {} would be exceeded (KES {} used)",
+                period, kind, limit, used
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Gate a deposit or a stake of `amount`. A stake is also checked against the
+/// loss limits as if it were lost.
+pub async fn check(state: &AppState, user_id: &str, kind: LimitKind, amount: Money) -> Result<()> {
+    let limits = load(state, user_id).await?;
+    ensure_not_blocked(&limits)?;
+    ensure_within(state, user_id, &limits, kind, amount).await?;
+    if kind == LimitKind::Stake {
+        ensure_within(state, user_id, &limits, LimitKind::Loss, amount).await?;
+    }
+    Ok(())
+}
+
+/// Current usage against every limit.
+pub async fn overview(state: &AppState, user_id: &str) -> Result<LimitsOverview> {
+    let limits = load(state, user_id).await?;
+
+    let mut usage_rows = Vec::new();
+    for kind in [LimitKind::Deposit, LimitKind::Stake, LimitKind::Loss] {
+        for period in LimitPeriod::ALL {
+            let limit = limits.limits(kind).get(period);
+            let used = usage(state, user_id, kind, period).await?;
+            usage_rows.push(LimitUsage {
+                kind,
+                period,
+                limit,
+                used,
+                remaining: limit.map(|limit| if used < limit { limit - used } else { Money::ZERO }),
+            });
+        }
+    }
+
+    Ok(LimitsOverview {
+        usage: usage_rows,
+        pending: limits.pending,
+        cool_off_until: limits.cool_off_until,
+        self_excluded_until: limits.self_excluded_until,
+    })
+}
+
+/// Set one limit. Lowering or adding a limit applies now; raising or
+/// removing one is queued behind the delay, replacing any earlier request.
+pub async fn set_limit(
+    state: &AppState,
+    user_id: &str,
+    request: &SetLimitRequest,
+) -> Result<GamblingLimits> {
+    if request.amount.is_some_and(|amount| !amount.is_positive()) {
+        return Err(AppError::invalid_data("Limit must be greater than 0"));
+    }
+
+    let mut limits = load(state, user_id).await?;
+    limits
+        .pending
+        .retain(|change| change.kind != request.kind || change.period != request.period);
+
+    let current = limits.limits(request.kind).get(request.period);
+    let tightening = match (current, request.amount) {
+        (_, None) => current.is_none(),
+        (None, Some(_)) => true,
+        (Some(current), Some(new)) => new <= current,
+    };
+
+    if tightening {
+        limits
+            .limits_mut(request.kind)
+            .set(request.period, request.amount);
+    } else {
+        limits.pending.push(PendingLimitChange {
+            kind: request.kind,
+            period: request.period,
+            amount: request.amount,
+            effective_at: DateTime::from_chrono(
+                Utc::now() + Duration::hours(LIMIT_INCREASE_DELAY_HOURS),
+            ),
+        });
+    }
+
+    save(state, user_id, &limits).await?;
+    println!(
+        "🛡️ User {} set {:?} {:?} limit to {:?} ({})",
+        user_id,
+        request.period,
+        request.kind,
+        request.amount,
+        if tightening { "now" } else { "pending" }
+    );
+    Ok(limits)
+}
+
+/// Block deposits and stakes for `hours`. An existing, longer block stands.
+pub async fn cool_off(state: &AppState, user_id: &str, hours: i64) -> Result<GamblingLimits> {
+    if !(1..=MAX_COOL_OFF_HOURS).contains(&hours) {
+        return Err(AppError::invalid_data(format!(
+            "Cool-off must be between 1 and {} hours",
+            MAX_COOL_OFF_HOURS
+        )));
+    }
+
+    let mut limits = load(state, user_id).await?;
+    let until = DateTime::from_chrono(Utc::now() + Duration::hours(hours));
+    limits.cool_off_until = limits.cool_off_until.max(Some(until));
+    save(state, user_id, &limits).await?;
+
+    println!("🧊 User {} cooling off for {} hours", user_id, hours);
+    Ok(limits)
+}
+
+/// Block deposits and stakes for `months` (of 30 days). Cannot be shortened.
+pub async fn self_exclude(state: &AppState, user_id: &str, months: i64) -> Result<GamblingLimits> {
+    if !(MIN_SELF_EXCLUSION_MONTHS..=MAX_SELF_EXCLUSION_MONTHS).contains(&months) {
+        return Err(AppError::invalid_data(format!(
+            "Self-exclusion must be between {} and {} months",
+            MIN_SELF_EXCLUSION_MONTHS, MAX_SELF_EXCLUSION_MONTHS
+        )));
+    }
+
+    let mut limits = load(state, user_id).await?;
+    let until = DateTime::from_chrono(Utc::now() + Duration::days(30 * months));
+    limits.self_excluded_until = limits.self_excluded_until.max(Some(until));
+    save(state, user_id, &limits).await?;
+
+    println!("🚫 User {} self-excluded for {} months", user_id, months);
+    Ok(limits)
+}
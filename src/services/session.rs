@@ -0,0 +1,204 @@
+// src/services/session.rs
+//
+// Login sessions, one per device. Logging in creates a session and hands
+// out a short-lived access token plus a refresh token; every refresh
+// rotates the refresh token. Presenting an already-rotated refresh token
+// means it was copied, so the whole session is revoked.
+//
+// Refresh tokens look like `<session_id>.<secret>`; only a bcrypt hash of
+// the secret is stored.
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{Duration, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Collection,
+};
+use rand::RngCore;
+use uuid::Uuid;
+
+use crate::errors::{AppError, Result};
+use crate::middleware::auth::{issue_token, TOKEN_TTL_SECS};
+use crate::models::notification::FCMToken;
+use crate::models::session::{Session, SessionTokens};
+use crate::models::user::{Role, User};
+use crate::state::AppState;
+
+/// Lifetime of a refresh token; each refresh starts a new window.
+pub const REFRESH_TTL_SECS: i64 = 30 * 24 * 3600;
+
+/// The secret is 256 random bits, so the hash only has to stop a database
+/// leak from being replayed; the minimum cost is plenty.
+const REFRESH_HASH_COST: u32 = 4;
+
+pub const DEFAULT_DEVICE: &str = "default";
+
+fn sessions(state: &AppState) -> Collection<Session> {
+    state.db.collection("auth_sessions")
+}
+
+fn new_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hash_secret(secret: &str) -> Result<String> {
+    bcrypt::hash(secret, REFRESH_HASH_COST)
+        .map_err(|e| AppError::internal_server_error(format!("Refresh token hashing failed: {}", e)))
+}
+
+/// Roles go into the access token, so a change applies from the next refresh.
+fn roles_for(state: &AppState, user: &User) -> Vec<Role> {
+    let mut roles = user.roles.clone();
+    if state.config.admin_phones.contains(&user.phone) && !roles.contains(&Role::Admin) {
+        roles.push(Role::Admin);
+    }
+    roles
+}
+
+fn tokens(state: &AppState, user: &User, session_id: &str, secret: &str) -> Result<SessionTokens> {
+    let user_id = user.id.map(|id| id.to_hex()).unwrap_or_default();
+    Ok(SessionTokens {
+        token: issue_token(
+            &state.config.jwt_secret,
+            &user_id,
+            &user.username,
+            &user.phone,
+            &roles_for(state, user),
+            session_id,
+        )?,
+        refresh_token: format!("{}.{}", session_id, secret),
+        expires_in: TOKEN_TTL_SECS,
+    })
+}
+
+/// Log `user` in on `device_id`, replacing any session that device had.
+pub async fn create(state: &AppState, user: &User, device_id: Option<&str>) -> Result<SessionTokens> {
+    let user_id = user.id.ok_or(AppError::UserNotFound)?.to_hex();
+    let device_id = device_id
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .unwrap_or(DEFAULT_DEVICE);
+    let collection = sessions(state);
+    let now = Utc::now();
+
+    collection
+        .update_many(
+            doc! { "user_id": &user_id, "device_id": device_id, "revoked_at": null },
+            doc! { "$set": { "revoked_at": DateTime::from_chrono(now) } },
+        )
+        .await?;
+
+    let secret = new_secret();
+    let session = Session {
+        session_id: Uuid::new_v4().to_string(),
+        user_id,
+        device_id: device_id.to_string(),
+        refresh_hash: hash_secret(&secret)?,
+        expires_at: DateTime::from_chrono(now + Duration::seconds(REFRESH_TTL_SECS)),
+        revoked_at: None,
+        created_at: DateTime::from_chrono(now),
+        last_used_at: DateTime::from_chrono(now),
+    };
+    collection.insert_one(&session).await?;
+
+    println!("🔑 Session {} started on device {}", session.session_id, session.device_id);
+    tokens(state, user, &session.session_id, &secret)
+}
+
+/// Exchange a refresh token for a new access token and refresh token.
+pub async fn refresh(state: &AppState, refresh_token: &str) -> Result<SessionTokens> {
+    let (session_id, secret) = refresh_token
+        .trim()
+        .split_once('.')
+        .ok_or(AppError::AuthError)?;
+    let collection = sessions(state);
+    let now = Utc::now();
+
+    let session = collection
+        .find_one(doc! { "session_id": session_id, "revoked_at": null })
+        .await?
+        .ok_or(AppError::AuthError)?;
+    if session.expires_at.to_chrono() <= now {
+        return Err(AppError::AuthError);
+    }
+
+    if !bcrypt::verify(secret, &session.refresh_hash).unwrap_or(false) {
+        tracing::warn!(
+            "🚨 Reused refresh token on session {} (user {}) — revoking it",
+            session_id,
+            session.user_id
+        );
+        revoke(state, session_id).await?;
+        return Err(AppError::AuthError);
+    }
+
+    // Conditional on the old hash so two concurrent refreshes can't both win
+    let secret = new_secret();
+    let rotated = collection
+        .update_one(
+            doc! {
+                "session_id": session_id,
+                "refresh_hash": &session.refresh_hash,
+                "revoked_at": null,
+            },
+            doc! { "$set": {
+                "refresh_hash": hash_secret(&secret)?,
+                "expires_at": DateTime::from_chrono(now + Duration::seconds(REFRESH_TTL_SECS)),
+                "last_used_at": DateTime::from_chrono(now),
+            }},
+        )
+        .await?;
+    if rotated.modified_count == 0 {
+        return Err(AppError::AuthError);
+    }
+
+    let object_id = ObjectId::parse_str(&session.user_id).map_err(|_| AppError::AuthError)?;
+    let user = state
+        .db
+        .collection::<User>("users")
+        .find_one(doc! { "_id": object_id })
+        .await?
+        .ok_or(AppError::AuthError)?;
+
+    tokens(state, &user, session_id, &secret)
+}
+
+/// Whether access tokens of this session are still honoured.
+pub async fn is_active(state: &AppState, session_id: &str) -> Result<bool> {
+    Ok(sessions(state)
+        .find_one(doc! { "session_id": session_id, "revoked_at": null })
+        .await?
+        .is_some())
+}
+
+/// Log one device out.
+pub async fn revoke(state: &AppState, session_id: &str) -> Result<()> {
+    sessions(state)
+        .update_one(
+            doc! { "session_id": session_id, "revoked_at": null },
+            doc! { "$set": { "revoked_at": DateTime::from_chrono(Utc::now()) } },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Log every device of a user out and stop pushing notifications to them.
+/// Returns the number of sessions revoked.
+pub async fn revoke_all(state: &AppState, user_id: &str) -> Result<u64> {
+    let revoked = sessions(state)
+        .update_many(
+            doc! { "user_id": user_id, "revoked_at": null },
+            doc! { "$set": { "revoked_at": DateTime::from_chrono(Utc::now()) } },
+        )
+        .await?;
+
+    let fcm_tokens: Collection<FCMToken> = state.db.collection("fcm_tokens");
+    let removed = fcm_tokens.delete_many(doc! { "user_id": user_id }).await?;
+
+    println!(
+        "🚪 Logged user {} out everywhere: {} sessions, {} FCM tokens",
+        user_id, revoked.modified_count, removed.deleted_count
+    );
+    Ok(revoked.modified_count)
+}
@@ -0,0 +1,241 @@
+// src/services/settlement.rs
+//
+// Settles every active bet on a fixture once the game is completed. The
+// final score decides the outcome ("home_win" / "away_win" / "draw"); the
+// side whose selection matches it takes the pot less the house commission
+// (see `CommissionConfig`), and bets where neither
+// side picked the outcome are voided and both stakes refunded. Bets from
+// before escrow have no stakes to pay out of; they are parked in
+// "review" for staff to settle by hand rather than paid nothing.
+use chrono::Utc;
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Collection,
+};
+use serde_json::json;
+
+use crate::errors::Result;
+use crate::handlers::ws_handler::{broadcast_live_match_update, notify_user, BetSettledPayload};
+use crate::models::bets::Bet;
+use crate::models::game::Game;
+use crate::models::money::Money;
+use crate::services::escrow::{self, SettledPayout};
+use crate::state::AppState;
+
+/// Bets settlement could not pay out automatically.
+pub const BET_REVIEW: &str = "review";
+
+/// Map a final score to the `winning_selection` stored on a bet.
+pub fn outcome_for_score(home_score: i32, away_score: i32) -> &'static str {
+    if home_score > away_score {
+        "home_win"
+    } else if away_score > home_score {
+        "away_win"
+    } else {
+        "draw"
+    }
+}
+
+/// Bet selections use team names ("home_team"), outcomes use results ("home_win").
+fn selection_wins(selection: &str, outcome: &str) -> bool {
+    matches!(
+        (selection, outcome),
+        ("home_team", "home_win") | ("away_team", "away_win") | ("draw", "draw")
+    )
+}
+
+/// Spawn settlement for a fixture in the background so the caller (usually
+/// the poller) isn't held up by payouts.
+pub fn spawn_settle_fixture(state: &AppState, match_id: &str) {
+    let state = state.clone();
+    let match_id = match_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = settle_fixture(&state, &match_id).await {
+            tracing::error!("❌ Settlement failed for fixture {}: {}", match_id, e);
+        }
+    });
+}
+
+/// Settle all active bets on a completed fixture. Safe to call repeatedly:
+/// each bet is claimed with a conditional update before any money moves.
+pub async fn settle_fixture(state: &AppState, match_id: &str) -> Result<usize> {
+    let games: Collection<Game> = state.db.collection("games");
+    let Some(game) = games.find_one(doc! { "match_id": match_id }).await? else {
+        tracing::warn!("⚠️ Settlement skipped: fixture {} not found", match_id);
+        return Ok(0);
+    };
+
+    if !game.is_completed() {
+        return Ok(0);
+    }
+    let (Some(home_score), Some(away_score)) = (game.home_score, game.away_score) else {
+        tracing::warn!("⚠️ Settlement skipped: fixture {} has no final score", match_id);
+        return Ok(0);
+    };
+
+    let bets: Collection<Bet> = state.db.collection("bets");
+    // Older bets were created without a match_id, so fall back to the teams
+    let filter = doc! {
+        "status": "active",
+        "$or": [
+            { "match_id": match_id },
+            { "match_id": null, "home_team": &game.home_team, "away_team": &game.away_team },
+        ],
+    };
+    let active: Vec<Bet> = bets.find(filter).await?.try_collect().await?;
+
+    tracing::info!(
+        "⚖️ Settling {} bets for fixture {} ({} {}-{} {}, {})",
+        active.len(),
+        match_id,
+        game.home_team,
+        home_score,
+        away_score,
+        game.away_team,
+        outcome_for_score(home_score, away_score)
+    );
+
+    let mut settled = 0;
+    for bet in active {
+        match settle_one(state, &bet, match_id, home_score, away_score).await {
+            Ok(Some(payload)) => {
+                settled += 1;
+                // Both bettors hear about it even if they aren't watching the fixture
+                let frame = json!({
+                    "type": "bet.settled",
+                    "fixture_id": match_id,
+                    "payload": &payload,
+                    "timestamp": Utc::now().to_rfc3339(),
+                });
+                notify_user(state, &bet.starter_id, frame.clone()).await;
+                notify_user(state, &bet.finisher_id, frame).await;
+                broadcast_live_match_update(state, match_id, "bet_settled", json!(payload)).await;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::error!("❌ Failed to settle bet {:?}: {}", bet.id, e),
+        }
+    }
+
+    tracing::info!("✅ Settled {} bets for fixture {}", settled, match_id);
+    Ok(settled)
+}
+
+async fn settle_one(
+    state: &AppState,
+    bet: &Bet,
+    match_id: &str,
+    home_score: i32,
+    away_score: i32,
+) -> Result<Option<BetSettledPayload>> {
+    let Some(bet_id) = bet.id else {
+        return Ok(None);
+    };
+    let bets: Collection<Bet> = state.db.collection("bets");
+    let outcome = outcome_for_score(home_score, away_score);
+    let winner = if selection_wins(&bet.starter_selection, outcome) {
+        Some((bet.starter_id.clone(), bet.starter_username.clone()))
+    } else if selection_wins(&bet.finisher_selection, outcome) {
+        Some((bet.finisher_id.clone(), bet.finisher_username.clone()))
+    } else {
+        None
+    };
+    let status = if winner.is_some() { "completed" } else { "void" };
+    let now = Utc::now();
+    let id = bet_id.to_hex();
+
+    if !escrow::bet_has_holds(state, &id).await? {
+        let parked = bets
+            .update_one(
+                doc! { "_id": bet_id, "status": "active" },
+                doc! { "$set": {
+                    "status": BET_REVIEW,
+                    "review_reason": format!("No escrowed stakes; result was {}", outcome),
+                    "winning_selection": outcome,
+                    "updated_at": now,
+                }},
+            )
+            .await?;
+        if parked.modified_count > 0 {
+            tracing::error!(
+                "🚩 Bet {} on fixture {} has no escrow holds; parked for manual review ({})",
+                id,
+                match_id,
+                outcome
+            );
+        }
+        return Ok(None);
+    }
+
+    // Claim the bet first; whoever flips it out of "active" owns the payout
+    let claimed = bets
+        .update_one(
+            doc! { "_id": bet_id, "status": "active" },
+            doc! { "$set": {
+                "status": status,
+                "winner_id": winner.as_ref().map(|w| w.0.clone()),
+                "winner_username": winner.as_ref().map(|w| w.1.clone()),
+                "winning_selection": outcome,
+                "completed_at": now,
+                "updated_at": now,
+            }},
+        )
+        .await?;
+    if claimed.modified_count == 0 {
+        return Ok(None);
+    }
+
+    let rule = state.config.commission.rule_for(&bet.league, &bet.sport_type);
+    let paid = match &winner {
+        Some((winner_id, _)) => escrow::settle_bet(state, &id, winner_id, rule).await,
+        None => escrow::release_bet(state, &id)
+            .await
+            .map(|_| SettledPayout::default()),
+    };
+
+    let payout = match paid {
+        Ok(payout) => payout,
+        Err(e) => {
+            // Hand the bet back so the next settlement run retries it
+            bets.update_one(
+                doc! { "_id": bet_id, "status": status },
+                doc! {
+                    "$set": { "status": "active", "updated_at": Utc::now() },
+                    "$unset": {
+                        "winner_id": "",
+                        "winner_username": "",
+                        "winning_selection": "",
+                        "completed_at": "",
+                    },
+                },
+            )
+            .await?;
+            return Err(e);
+        }
+    };
+    record_commission(&bets, bet_id, payout.commission).await;
+
+    Ok(Some(BetSettledPayload {
+        fixture_id: match_id.to_string(),
+        bet_id: id,
+        pledge_id: bet.pledge_id.clone(),
+        status: status.to_string(),
+        winning_selection: outcome.to_string(),
+        winner_id: winner.as_ref().map(|w| w.0.clone()),
+        winner_username: winner.map(|w| w.1),
+        payout: payout.paid,
+        commission: payout.commission,
+        home_score,
+        away_score,
+    }))
+}
+
+/// Store the house cut on the bet. Best effort: the ledger already has it.
+pub(crate) async fn record_commission(bets: &Collection<Bet>, bet_id: ObjectId, commission: Money) {
+    if let Err(e) = bets
+        .update_one(doc! { "_id": bet_id }, doc! { "$set": { "commission": commission } })
+        .await
+    {
+        tracing::error!("❌ Failed to record commission on bet {}: {}", bet_id, e);
+    }
+}
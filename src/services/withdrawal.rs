@@ -0,0 +1,593 @@
+// src/services/withdrawal.rs
+//
+// Wallet withdrawals paid out through M-Pesa B2C. Requesting a withdrawal
+// moves the amount from the user's wallet into their withdrawal account
+// before anything is sent to Daraja; the B2C result callback then either
+// pays it out to the M-Pesa clearing account or refunds it to the wallet.
+// A queue timeout, or a B2C request whose response never came back, leaves
+// the outcome unknown, so the funds stay held until a transaction status
+// query settles it. Requests go out under our own OriginatorConversationID
+// (the withdrawal id), stored before sending, so that query is always possible.
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson, DateTime as BsonDateTime},
+    options::ReturnDocument,
+    Collection,
+};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::errors::{AppError, Result};
+use crate::models::ledger::{EntryKind, LedgerPosting, MPESA_CLEARING_ACCOUNT};
+use crate::models::withdrawal::{
+    CreateWithdrawalRequest, Withdrawal, WithdrawalStatusChange, MIN_WITHDRAWAL,
+    WITHDRAWAL_COMPLETED, WITHDRAWAL_FAILED, WITHDRAWAL_PENDING, WITHDRAWAL_PROCESSING,
+    WITHDRAWAL_REVIEW, WITHDRAWAL_UNKNOWN,
+};
+use crate::services::ledger::{self, NewEntry};
+use crate::services::mpesa_service::B2CError;
+use crate::state::AppState;
+
+const WITHDRAWALS: &str = "withdrawals";
+
+fn withdrawals(state: &AppState) -> Collection<Withdrawal> {
+    state.db.collection(WITHDRAWALS)
+}
+
+/// Debit the wallet and issue the B2C request.
+pub async fn request_withdrawal(
+    state: &AppState,
+    request: CreateWithdrawalRequest,
+) -> Result<Withdrawal> {
+    if request.user_id.is_empty() {
+        return Err(AppError::missing_field("user_id"));
+    }
+    if request.phone_number.is_empty() {
+        return Err(AppError::missing_field("phone_number"));
+    }
+    if request.amount < MIN_WITHDRAWAL || request.amount.cents() % 100 != 0 {
+        return Err(AppError::invalid_data(format!(
+            "amount must be a whole number of at least {}",
+            MIN_WITHDRAWAL
+        )));
+    }
+
+    let mpesa_service = state
+        .mpesa_service
+        .clone()
+        .ok_or_else(|| AppError::service("M-Pesa service is not available"))?;
+
+    let withdrawal_id = ObjectId::new();
+    let id = withdrawal_id.to_hex();
+
+    ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("withdrawal:{}:hold", id),
+            kind: EntryKind::Withdrawal,
+            reference: Some(id.clone()),
+            description: format!("Withdrawal to {}", request.phone_number),
+            postings: vec![
+                LedgerPosting::user_wallet(&request.user_id, -request.amount),
+                LedgerPosting::user_withdrawal(&request.user_id, request.amount),
+            ],
+        },
+    )
+    .await?;
+
+    let now = BsonDateTime::now();
+    let withdrawal = Withdrawal {
+        id: Some(withdrawal_id),
+        user_id: request.user_id.clone(),
+        phone_number: request.phone_number.clone(),
+        amount: request.amount,
+        status: WITHDRAWAL_PENDING.to_string(),
+        conversation_id: None,
+        originator_conversation_id: Some(id.clone()),
+        mpesa_transaction_id: None,
+        result_code: None,
+        result_desc: None,
+        status_query_conversation_id: None,
+        status_query_originator_id: None,
+        review_reason: None,
+        status_history: vec![status_change(WITHDRAWAL_PENDING, None)],
+        created_at: now,
+        updated_at: now,
+    };
+
+    let collection = withdrawals(state);
+    if let Err(e) = collection.insert_one(&withdrawal).await {
+        refund(state, &withdrawal).await?;
+        return Err(e.into());
+    }
+
+    let sent = mpesa_service
+        .send_b2c_payment(
+            &id,
+            &request.phone_number,
+            &(request.amount.cents() / 100).to_string(),
+            "BusinessPayment",
+            "Wallet withdrawal",
+            Some(&id),
+        )
+        .await;
+
+    match sent {
+        Ok(response) => {
+            let updated = transition(
+                state,
+                doc! { "_id": withdrawal_id, "status": WITHDRAWAL_PENDING },
+                WITHDRAWAL_PROCESSING,
+                Some(response.response_description.clone()),
+                doc! {
+                    "conversation_id": &response.conversation_id,
+                    "originator_conversation_id": &response.originator_conversation_id,
+                },
+            )
+            .await?
+            .ok_or(AppError::DocumentNotFound)?;
+
+            tracing::info!(
+                "💸 Withdrawal {} submitted (ConversationID {})",
+                id,
+                response.conversation_id
+            );
+            Ok(updated)
+        }
+        Err(B2CError::Unknown(e)) => {
+            // Daraja may have taken it; the status job finds out
+            tracing::error!("❓ B2C request for withdrawal {} may have gone through: {}", id, e);
+            let unknown = transition(
+                state,
+                doc! { "_id": withdrawal_id, "status": WITHDRAWAL_PENDING },
+                WITHDRAWAL_UNKNOWN,
+                Some(format!("B2C response lost, checking its status: {}", e)),
+                doc! {},
+            )
+            .await?
+            .ok_or(AppError::DocumentNotFound)?;
+            Ok(unknown)
+        }
+        Err(B2CError::Rejected(e)) => {
+            tracing::error!("❌ B2C request for withdrawal {} failed: {}", id, e);
+            let failed = transition(
+                state,
+                doc! { "_id": withdrawal_id, "status": WITHDRAWAL_PENDING },
+                WITHDRAWAL_FAILED,
+                Some(e.clone()),
+                doc! {},
+            )
+            .await?;
+            if let Some(failed) = &failed {
+                refund(state, failed).await?;
+            }
+            Err(AppError::mpesa(format!("Withdrawal could not be sent: {}", e)))
+        }
+    }
+}
+
+/// Finalize a withdrawal from the B2C result callback.
+pub async fn complete_from_result(
+    state: &AppState,
+    conversation_id: &str,
+    originator_conversation_id: &str,
+    result_code: i32,
+    result_desc: &str,
+    transaction_id: &str,
+) -> Result<()> {
+    let filter = doc! {
+        "$or": [
+            { "conversation_id": conversation_id },
+            { "originator_conversation_id": originator_conversation_id },
+        ],
+        "status": { "$in": [WITHDRAWAL_PROCESSING, WITHDRAWAL_UNKNOWN] },
+    };
+
+    if result_code != 0 {
+        return fail(state, filter, result_code, result_desc).await;
+    }
+
+    if complete(state, filter, result_desc, transaction_id).await? {
+        return Ok(());
+    }
+
+    // Nothing left to settle: the payment may have landed after we gave up on it
+    let failed = withdrawals(state)
+        .find_one(doc! {
+            "$or": [
+                { "conversation_id": conversation_id },
+                { "originator_conversation_id": originator_conversation_id },
+            ],
+            "status": WITHDRAWAL_FAILED,
+        })
+        .await?;
+    match failed {
+        Some(withdrawal) => reverse_refund(state, withdrawal, result_desc, transaction_id).await,
+        None => {
+            tracing::info!("ℹ️ No unsettled withdrawal for ConversationID {}", conversation_id);
+            Ok(())
+        }
+    }
+}
+
+/// Mark the withdrawal matching `filter` completed and pay it out of the
+/// withdrawal account. Returns false when nothing matched.
+async fn complete(
+    state: &AppState,
+    filter: mongodb::bson::Document,
+    result_desc: &str,
+    transaction_id: &str,
+) -> Result<bool> {
+    let Some(withdrawal) = transition(
+        state,
+        filter,
+        WITHDRAWAL_COMPLETED,
+        Some(result_desc.to_string()),
+        doc! {
+            "result_code": 0,
+            "result_desc": result_desc,
+            "mpesa_transaction_id": transaction_id,
+        },
+    )
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    let id = withdrawal.id.map(|id| id.to_hex()).unwrap_or_default();
+    ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("withdrawal:{}:payout", id),
+            kind: EntryKind::Withdrawal,
+            reference: Some(transaction_id.to_string()),
+            description: format!("M-Pesa payout {}", transaction_id),
+            postings: vec![
+                LedgerPosting::user_withdrawal(&withdrawal.user_id, -withdrawal.amount),
+                LedgerPosting::system(MPESA_CLEARING_ACCOUNT, withdrawal.amount),
+            ],
+        },
+    )
+    .await?;
+
+    tracing::info!("✅ Withdrawal {} completed ({})", id, transaction_id);
+    notify(
+        state,
+        &withdrawal,
+        "✅ Withdrawal sent",
+        &format!("Ksh {} has been sent to {}", withdrawal.amount, withdrawal.phone_number),
+    )
+    .await;
+    Ok(true)
+}
+
+/// A success result for a withdrawal already marked failed. If the refund
+/// went through, the money is taken back out of the wallet; when the wallet
+/// can no longer cover it the withdrawal is parked for review.
+async fn reverse_refund(
+    state: &AppState,
+    withdrawal: Withdrawal,
+    result_desc: &str,
+    transaction_id: &str,
+) -> Result<()> {
+    let id = withdrawal.id.map(|id| id.to_hex()).unwrap_or_default();
+    let failed = doc! { "_id": withdrawal.id, "status": WITHDRAWAL_FAILED };
+    tracing::error!(
+        "🚨 Withdrawal {} was paid out ({}) after being marked failed",
+        id,
+        transaction_id
+    );
+
+    // Failed but never refunded: the funds are still held, pay out as usual
+    if !ledger::entry_posted(state, &format!("withdrawal:{}:refund", id)).await? {
+        complete(state, failed, result_desc, transaction_id).await?;
+        return Ok(());
+    }
+
+    let reversal = ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("withdrawal:{}:refund-reversal", id),
+            kind: EntryKind::Withdrawal,
+            reference: Some(transaction_id.to_string()),
+            description: format!("Refund reversed, M-Pesa payout {} went through", transaction_id),
+            postings: vec![
+                LedgerPosting::user_wallet(&withdrawal.user_id, -withdrawal.amount),
+                LedgerPosting::system(MPESA_CLEARING_ACCOUNT, withdrawal.amount),
+            ],
+        },
+    )
+    .await;
+
+    match reversal {
+        Ok(_) => {
+            let completed = transition(
+                state,
+                failed,
+                WITHDRAWAL_COMPLETED,
+                Some("Paid out after refund, refund reversed".to_string()),
+                doc! {
+                    "result_code": 0,
+                    "result_desc": result_desc,
+                    "mpesa_transaction_id": transaction_id,
+                },
+            )
+            .await?;
+            tracing::warn!("↪️ Reversed refund of withdrawal {}", id);
+            if let Some(completed) = completed {
+                notify(
+                    state,
+                    &completed,
+                    "✅ Withdrawal sent",
+                    &format!(
+                        "Ksh {} reached {} after all, so the refund has been reversed",
+                        completed.amount, completed.phone_number
+                    ),
+                )
+                .await;
+            }
+            Ok(())
+        }
+        Err(AppError::InsufficientFunds) => {
+            transition(
+                state,
+                failed,
+                WITHDRAWAL_REVIEW,
+                Some("Paid out after refund".to_string()),
+                doc! {
+                    "result_code": 0,
+                    "result_desc": result_desc,
+                    "mpesa_transaction_id": transaction_id,
+                    "review_reason": "Paid out after refund; wallet can't cover the reversal",
+                },
+            )
+            .await?;
+            tracing::error!(
+                "🚨 Withdrawal {} paid out twice and wallet can't cover Ksh {}, needs review",
+                id,
+                withdrawal.amount
+            );
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// A B2C request timed out in Daraja's queue. That doesn't mean it failed,
+/// so the funds stay held while Daraja is asked what happened.
+pub async fn mark_timed_out(
+    state: &AppState,
+    conversation_id: &str,
+    originator_conversation_id: &str,
+) -> Result<()> {
+    let filter = doc! {
+        "$or": [
+            { "conversation_id": conversation_id },
+            { "originator_conversation_id": originator_conversation_id },
+        ],
+        "status": WITHDRAWAL_PROCESSING,
+    };
+    let Some(withdrawal) = transition(
+        state,
+        filter,
+        WITHDRAWAL_UNKNOWN,
+        Some("B2C request timed out, checking its status".to_string()),
+        doc! {},
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    tracing::warn!("⏳ Withdrawal {:?} timed out, querying its status", withdrawal.id);
+    query_status(state, &withdrawal).await
+}
+
+/// Settle an `unknown` withdrawal from a transaction status result.
+/// `transaction_status` is Daraja's TransactionStatus ("Completed", "Failed", ...);
+/// anything inconclusive is left for the next query.
+pub async fn resolve_from_status(
+    state: &AppState,
+    query_conversation_id: &str,
+    query_originator_id: &str,
+    result_code: i32,
+    result_desc: &str,
+    transaction_status: Option<&str>,
+    receipt: Option<&str>,
+) -> Result<()> {
+    let filter = doc! {
+        "$or": [
+            { "status_query_conversation_id": query_conversation_id },
+            { "status_query_originator_id": query_originator_id },
+        ],
+        "status": WITHDRAWAL_UNKNOWN,
+    };
+
+    match (result_code, transaction_status) {
+        (0, Some("Completed")) => {
+            complete(state, filter, result_desc, receipt.unwrap_or_default()).await?;
+        }
+        (0, Some(status @ ("Failed" | "Cancelled"))) => {
+            // No B2C result code exists; -1 marks the failure as found by a status query
+            fail(state, filter, -1, &format!("M-Pesa reports the payment as {}", status)).await?;
+        }
+        _ => tracing::warn!(
+            "❓ Status query {} inconclusive ({}: {}), will ask again",
+            query_conversation_id,
+            result_code,
+            result_desc
+        ),
+    }
+    Ok(())
+}
+
+/// Ask Daraja for the outcome of a withdrawal's B2C request. The answer
+/// arrives through `resolve_from_status`.
+async fn query_status(state: &AppState, withdrawal: &Withdrawal) -> Result<()> {
+    let originator_conversation_id = withdrawal
+        .originator_conversation_id
+        .as_deref()
+        .ok_or_else(|| AppError::missing_field("originator_conversation_id"))?;
+    let mpesa_service = state
+        .mpesa_service
+        .clone()
+        .ok_or_else(|| AppError::service("M-Pesa service is not available"))?;
+
+    let ack = mpesa_service
+        .query_transaction_status(originator_conversation_id, "Withdrawal status")
+        .await
+        .map_err(|e| AppError::mpesa(e.to_string()))?;
+
+    withdrawals(state)
+        .update_one(
+            doc! { "_id": withdrawal.id },
+            doc! { "$set": {
+                "status_query_conversation_id": &ack.conversation_id,
+                "status_query_originator_id": &ack.originator_conversation_id,
+                "updated_at": BsonDateTime::now(),
+            } },
+        )
+        .await?;
+    Ok(())
+}
+
+const STATUS_RETRY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Re-query withdrawals still `unknown`, e.g. because the status query
+/// itself timed out. Called once from `main`.
+pub fn spawn_withdrawal_status_job(state: AppState) {
+    tokio::spawn(async move {
+        tracing::info!(
+            "⏳ Withdrawal status job started ({}s interval)",
+            STATUS_RETRY_INTERVAL.as_secs()
+        );
+        let mut interval = tokio::time::interval(STATUS_RETRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = requery_unknown(&state).await {
+                tracing::error!("❌ Withdrawal status sweep failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn requery_unknown(state: &AppState) -> Result<()> {
+    let cutoff = BsonDateTime::from_millis(
+        BsonDateTime::now().timestamp_millis() - STATUS_RETRY_INTERVAL.as_millis() as i64,
+    );
+    let unresolved: Vec<Withdrawal> = withdrawals(state)
+        .find(doc! { "status": WITHDRAWAL_UNKNOWN, "updated_at": { "$lt": cutoff } })
+        .await?
+        .try_collect()
+        .await?;
+
+    for withdrawal in unresolved {
+        if let Err(e) = query_status(state, &withdrawal).await {
+            tracing::error!("❌ Status query for withdrawal {:?} failed: {}", withdrawal.id, e);
+        }
+    }
+    Ok(())
+}
+
+async fn fail(
+    state: &AppState,
+    filter: mongodb::bson::Document,
+    result_code: i32,
+    result_desc: &str,
+) -> Result<()> {
+    let Some(withdrawal) = transition(
+        state,
+        filter,
+        WITHDRAWAL_FAILED,
+        Some(result_desc.to_string()),
+        doc! { "result_code": result_code, "result_desc": result_desc },
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    refund(state, &withdrawal).await?;
+    tracing::warn!(
+        "↩️ Withdrawal {:?} failed and was refunded: {}",
+        withdrawal.id,
+        result_desc
+    );
+    notify(
+        state,
+        &withdrawal,
+        "↩️ Withdrawal failed",
+        &format!("Ksh {} has been returned to your wallet", withdrawal.amount),
+    )
+    .await;
+    Ok(())
+}
+
+async fn refund(state: &AppState, withdrawal: &Withdrawal) -> Result<bool> {
+    let id = withdrawal.id.map(|id| id.to_hex()).unwrap_or_default();
+    ledger::post_entry(
+        state,
+        NewEntry {
+            entry_id: format!("withdrawal:{}:refund", id),
+            kind: EntryKind::Refund,
+            reference: Some(id.clone()),
+            description: "Withdrawal refunded".to_string(),
+            postings: vec![
+                LedgerPosting::user_withdrawal(&withdrawal.user_id, -withdrawal.amount),
+                LedgerPosting::user_wallet(&withdrawal.user_id, withdrawal.amount),
+            ],
+        },
+    )
+    .await
+}
+
+/// Move a withdrawal matching `filter` to `status`, recording the change in
+/// its history. Returns `None` when nothing matched (already finalized).
+async fn transition(
+    state: &AppState,
+    filter: mongodb::bson::Document,
+    status: &str,
+    note: Option<String>,
+    mut fields: mongodb::bson::Document,
+) -> Result<Option<Withdrawal>> {
+    let change = to_bson(&status_change(status, note))
+        .map_err(|e| AppError::internal_server_error(format!("Withdrawal serialization: {}", e)))?;
+
+    fields.insert("status", status);
+    fields.insert("updated_at", BsonDateTime::now());
+
+    Ok(withdrawals(state)
+        .find_one_and_update(
+            filter,
+            doc! { "$set": fields, "$push": { "status_history": change } },
+        )
+        .return_document(ReturnDocument::After)
+        .await?)
+}
+
+fn status_change(status: &str, note: Option<String>) -> WithdrawalStatusChange {
+    WithdrawalStatusChange {
+        status: status.to_string(),
+        note,
+        at: BsonDateTime::now(),
+    }
+}
+
+async fn notify(state: &AppState, withdrawal: &Withdrawal, title: &str, body: &str) {
+    let Some(fcm_service) = state.fcm_service.as_ref() else {
+        return;
+    };
+
+    let data = json!({
+        "type": "withdrawal",
+        "withdrawal_id": withdrawal.id.map(|id| id.to_hex()),
+        "status": withdrawal.status,
+        "amount": withdrawal.amount,
+    });
+    if let Err(e) = fcm_service
+        .send_to_user(state, &withdrawal.user_id, title, body, data, "withdrawal")
+        .await
+    {
+        tracing::warn!("⚠️ Withdrawal notification failed: {}", e);
+    }
+}
@@ -1,37 +1,66 @@
-use dashmap::DashMap;
 use mongodb::Database;
 use std::sync::Arc;
-use tokio::sync::broadcast;
 
+use crate::config::AppConfig;
 use crate::errors::AppError;
+use crate::services::broadcaster::{Broadcaster, InMemoryBroadcaster};
 use crate::services::cloudinary::CloudinaryService;
 use crate::services::fcm_service::FCMService;
 use crate::services::mpesa_service::MpesaService;
-
-/// One broadcast channel per fixtureId.
-/// Key = fixtureId, Value = sender half of the channel.
-pub type CommentBroadcaster = Arc<DashMap<String, broadcast::Sender<String>>>;
+use crate::services::presence::PresenceTracker;
+use crate::services::rate_limit::RateLimiter;
+use crate::services::replay_guard::ReplayGuard;
+use crate::services::sms_service::{LogSmsSender, SMSService, SmsSender};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Database,
+    pub config: Arc<AppConfig>,
     pub mpesa_service: Option<Arc<MpesaService>>,
     pub fcm_service: Option<Arc<FCMService>>,
     pub cloudinary: CloudinaryService,
-    /// Shared in-memory broadcaster — no Redis needed
-    pub comment_broadcaster: CommentBroadcaster,
+    pub sms: Arc<dyn SmsSender>,
+    pub rate_limiter: Arc<RateLimiter>,
+    /// WebSocket fan-out, one channel per fixtureId; in-memory or Redis
+    pub comment_broadcaster: Arc<dyn Broadcaster>,
+    /// Who is watching each fixture, deduplicated per user
+    pub presence: Arc<PresenceTracker>,
+    /// Poller webhook signatures seen inside the replay window
+    pub webhook_replays: Arc<ReplayGuard>,
 }
 
 impl AppState {
-    pub fn new(db: Database) -> Result<Self, AppError> {
+    pub fn new(db: Database, config: Arc<AppConfig>) -> Result<Self, AppError> {
         let cloudinary = CloudinaryService::new()?;
 
+        // Logging OTPs instead of sending them would let anyone with log
+        // access log in as anyone, so it has to be asked for explicitly
+        let sms: Arc<dyn SmsSender> = if config.is_sms_configured() {
+            Arc::new(SMSService::new(
+                config.sms_api_key.clone(),
+                config.sms_username.clone(),
+                config.sms_from.clone(),
+            ))
+        } else if config.sms_log_only {
+            tracing::warn!("⚠️  SMS_LOG_ONLY set — OTP codes are logged, not sent");
+            Arc::new(LogSmsSender)
+        } else {
+            return Err(AppError::service(
+                "SMS_API_KEY is not set (set SMS_LOG_ONLY=true to log OTPs in development)",
+            ));
+        };
+
         Ok(AppState {
             db,
+            config,
             mpesa_service: None,
             fcm_service: None,
             cloudinary,
-            comment_broadcaster: Arc::new(DashMap::new()),
+            sms,
+            rate_limiter: Arc::new(RateLimiter::in_memory()),
+            comment_broadcaster: Arc::new(InMemoryBroadcaster::new()),
+            presence: Arc::new(PresenceTracker::in_memory()),
+            webhook_replays: Arc::new(ReplayGuard::in_memory()),
         })
     }
 
@@ -40,20 +69,60 @@ impl AppState {
         self
     }
 
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     pub fn with_fcm(mut self, fcm_service: Arc<FCMService>) -> Self {
         self.fcm_service = Some(fcm_service);
         self
     }
 
-    /// Get or create a broadcast sender for a given fixtureId.
-    pub fn get_or_create_broadcaster(&self, fixture_id: &str) -> broadcast::Sender<String> {
-        if let Some(tx) = self.comment_broadcaster.get(fixture_id) {
-            return tx.clone();
+    pub fn with_broadcaster(mut self, broadcaster: Arc<dyn Broadcaster>) -> Self {
+        self.comment_broadcaster = broadcaster;
+        self
+    }
+
+    pub fn with_replay_guard(mut self, webhook_replays: Arc<ReplayGuard>) -> Self {
+        self.webhook_replays = webhook_replays;
+        self
+    }
+
+    pub fn with_presence(mut self, presence: Arc<PresenceTracker>) -> Self {
+        self.presence = presence;
+        self
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State over a throwaway database on `TEST_MONGODB_URI` (default
+    /// localhost), with OTPs logged and everything else in memory. Tests
+    /// drop the database when they are done.
+    pub async fn for_tests() -> Self {
+        let uri = std::env::var("TEST_MONGODB_URI")
+            .unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
+        let client = mongodb::Client::with_uri_str(&uri)
+            .await
+            .expect("TEST_MONGODB_URI is not a valid MongoDB URI");
+        let db = client.database(&format!("fanclash_test_{}", uuid::Uuid::new_v4().simple()));
+
+        let mut config = AppConfig::from_env();
+        config.sms_api_key.clear();
+        config.sms_log_only = true;
+
+        AppState {
+            db,
+            config: Arc::new(config),
+            mpesa_service: None,
+            fcm_service: None,
+            cloudinary: CloudinaryService::for_tests(),
+            sms: Arc::new(LogSmsSender),
+            rate_limiter: Arc::new(RateLimiter::in_memory()),
+            comment_broadcaster: Arc::new(InMemoryBroadcaster::new()),
+            presence: Arc::new(PresenceTracker::in_memory()),
+            webhook_replays: Arc::new(ReplayGuard::in_memory()),
         }
-        // capacity 64: if a slow client misses messages that's fine
-        let (tx, _) = broadcast::channel(64);
-        self.comment_broadcaster
-            .insert(fixture_id.to_string(), tx.clone());
-        tx
     }
 }
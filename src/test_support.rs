@@ -0,0 +1,58 @@
+// src/test_support.rs
+//
+// Shared setup for handler tests. Tests that touch the database run
+// against a throwaway database on a real MongoDB and are `#[ignore]`d so
+// plain `cargo test` stays offline:
+//
+//     TEST_MONGODB_URI=mongodb://localhost:27017 cargo test -- --ignored
+use std::future::Future;
+
+use axum::{http::StatusCode, response::Response};
+
+use crate::middleware::auth::AuthUser;
+use crate::models::user::Role;
+use crate::state::AppState;
+
+/// The user test resources are seeded for.
+pub const OWNER: &str = "owner";
+
+/// Run `test` against a fresh database, dropped afterwards.
+pub async fn with_test_db<F, Fut>(test: F)
+where
+    F: FnOnce(AppState) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let state = AppState::for_tests().await;
+    test(state.clone()).await;
+    state.db.drop().await.unwrap();
+}
+
+/// Check an edit or delete route lets the owner and moderators through and
+/// turns anyone else away. `seed` creates a resource owned by OWNER and
+/// returns its id; `act` calls the route on it as the given user.
+pub async fn assert_owner_or_moderator_only<Seed, SeedFut, Act, ActFut>(seed: Seed, act: Act)
+where
+    Seed: Fn(AppState) -> SeedFut,
+    SeedFut: Future<Output = String>,
+    Act: Fn(AppState, String, AuthUser) -> ActFut,
+    ActFut: Future<Output = Response>,
+{
+    with_test_db(|state| async move {
+        let id = seed(state.clone()).await;
+
+        let stranger = AuthUser::for_tests("stranger", vec![]);
+        let denied = act(state.clone(), id.clone(), stranger).await;
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+
+        let owner = AuthUser::for_tests(OWNER, vec![]);
+        let allowed = act(state.clone(), id, owner).await;
+        assert!(allowed.status().is_success(), "owner got {}", allowed.status());
+
+        // Seeded again in case the owner's call deleted it
+        let id = seed(state.clone()).await;
+        let moderator = AuthUser::for_tests("moderator", vec![Role::Moderator]);
+        let allowed = act(state, id, moderator).await;
+        assert!(allowed.status().is_success(), "moderator got {}", allowed.status());
+    })
+    .await;
+}